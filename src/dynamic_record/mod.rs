@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::Display};
+use std::{cmp::Ordering, collections::HashMap, fmt::Display};
+
+use crate::sql_parser::query::{Condition, Function, Predicate};
+use crate::utils::unquote;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DynamicRecord {
@@ -22,42 +25,108 @@ impl DynamicRecord {
         self.fields = filtered_map; // Update self.fields with the filtered map
     }
 
-    pub fn entry_should_be_included(
-        &self,
-        where_clauses: &Option<&HashMap<String, String>>,
-    ) -> bool {
-        match where_clauses {
-            None => true,
-            Some(where_clauses) => {
-                for key in where_clauses.keys() {
-                    if self.fields.contains_key(key) {
-                        return self
-                            .fields
-                            .get(key)
-                            .unwrap()
-                            .fullfills(where_clauses.get(key).unwrap());
-                    }
-                }
-
-                true
-            }
-        }
+    pub fn entry_should_be_included(&self, where_clauses: &[Condition]) -> bool {
+        where_clauses
+            .iter()
+            .all(|condition| match self.fields.get(&condition.column_name) {
+                Some(value) => match condition.function {
+                    Some(function) => value.apply(function).satisfies(&condition.predicate),
+                    None => value.satisfies(&condition.predicate),
+                },
+                None => false,
+            })
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Values of different types are ordered by their type, like index keys.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Value {
-    Int(i32),
+    Int(i64),
     Text(String),
 }
 
 impl Value {
     pub fn fullfills(&self, value_to_match: &str) -> bool {
+        self.compare(value_to_match) == Some(Ordering::Equal)
+    }
+
+    pub fn satisfies(&self, predicate: &Predicate) -> bool {
+        match predicate {
+            Predicate::Equal(literal) => self.compare(literal) == Some(Ordering::Equal),
+            Predicate::LessThan(literal) => self.compare(literal) == Some(Ordering::Less),
+            Predicate::LessThanOrEqual(literal) => {
+                matches!(
+                    self.compare(literal),
+                    Some(Ordering::Less | Ordering::Equal)
+                )
+            }
+            Predicate::GreaterThan(literal) => self.compare(literal) == Some(Ordering::Greater),
+            Predicate::GreaterThanOrEqual(literal) => {
+                matches!(
+                    self.compare(literal),
+                    Some(Ordering::Greater | Ordering::Equal)
+                )
+            }
+            Predicate::Between(lower, upper) => {
+                matches!(
+                    self.compare(lower),
+                    Some(Ordering::Greater | Ordering::Equal)
+                ) && matches!(self.compare(upper), Some(Ordering::Less | Ordering::Equal))
+            }
+            Predicate::Like(pattern) => like_matches(&self.to_string(), unquote(pattern)),
+        }
+    }
+
+    pub fn apply(&self, function: Function) -> Value {
+        match (self, function) {
+            (Self::Text(text), Function::Lower) => Self::Text(text.to_lowercase()),
+            (Self::Text(text), Function::Upper) => Self::Text(text.to_uppercase()),
+            _ => self.clone(),
+        }
+    }
+
+    /// Compares the value with a literal of a query, interpreting the literal as the value's type.
+    pub fn compare(&self, literal: &str) -> Option<Ordering> {
+        let literal = unquote(literal);
+
         match self {
-            Self::Int(value) => value_to_match == value.to_string(),
-            Self::Text(value) => value_to_match == value,
+            Self::Int(value) => literal
+                .parse::<i64>()
+                .ok()
+                .map(|literal| value.cmp(&literal)),
+            Self::Text(value) => Some(value.as_str().cmp(literal)),
+        }
+    }
+}
+
+/// SQL LIKE matching, where `%` matches any sequence of characters and `_` a single one.
+fn like_matches(text: &str, pattern: &str) -> bool {
+    let text: Vec<char> = text.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+
+    // matches[j] is true if the text consumed so far matches the first j pattern characters
+    let mut matches = vec![false; pattern.len() + 1];
+    matches[0] = true;
+
+    for j in 1..=pattern.len() {
+        matches[j] = matches[j - 1] && pattern[j - 1] == '%';
+    }
+
+    for char in text {
+        let mut next_matches = vec![false; pattern.len() + 1];
+
+        for j in 1..=pattern.len() {
+            next_matches[j] = match pattern[j - 1] {
+                '%' => next_matches[j - 1] || matches[j],
+                '_' => matches[j - 1],
+                pattern_char => matches[j - 1] && pattern_char == char,
+            };
         }
+
+        matches = next_matches;
     }
+
+    matches[pattern.len()]
 }
 
 impl Display for Value {
@@ -84,4 +153,23 @@ mod tests {
         assert!(Value::Text(String::from("here")).fullfills("here"));
         assert!(!Value::Text(String::from("here")).fullfills("here "));
     }
+
+    #[test]
+    fn test_compares_int_values_numerically() {
+        let value = Value::Int(10);
+
+        assert!(value.satisfies(&Predicate::GreaterThan(String::from("9"))));
+        assert!(value.satisfies(&Predicate::Between(String::from("2"), String::from("10"))));
+        assert!(!value.satisfies(&Predicate::LessThan(String::from("9"))));
+    }
+
+    #[test]
+    fn test_can_match_like_patterns() {
+        let value = Value::Text(String::from("john@mail.com"));
+
+        assert!(value.satisfies(&Predicate::Like(String::from("'john%'"))));
+        assert!(value.satisfies(&Predicate::Like(String::from("'%@mail._om'"))));
+        assert!(!value.satisfies(&Predicate::Like(String::from("'jane%'"))));
+        assert!(!value.satisfies(&Predicate::Like(String::from("'john'"))));
+    }
 }
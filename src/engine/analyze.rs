@@ -0,0 +1,4 @@
+pub trait Analyze {
+    /// Collects the statistics the planner estimates rows with, for the table or all tables if there is none.
+    fn analyze(&self, table_name: Option<String>) -> super::EngineResult;
+}
@@ -0,0 +1,169 @@
+use crate::engine::analyze::Analyze;
+use crate::metadata::StatisticsCollector;
+
+use super::executor::{Operator, SeqScan, TableScan};
+
+impl Analyze for super::BinaryEngine {
+    fn analyze(&self, table_name: Option<String>) -> super::EngineResult {
+        let table_names = match table_name {
+            Some(table_name) => vec![table_name],
+            None => self
+                .table_manager
+                .table_names()
+                .map_err(|error| format!("Failed to list the tables: {}", error))?,
+        };
+
+        self.pager.start_statement();
+
+        let messages = table_names
+            .iter()
+            .map(|table_name| self.analyze_table(table_name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(super::EngineResponse {
+            table: None,
+            records: None,
+            message: Some(messages.join("\n")),
+        })
+    }
+}
+
+impl super::BinaryEngine {
+    /// Reads every row of the table the transaction sees and stores what it found in the metadata.
+    /// The rows are read like a SELECT reads them, the table is only locked to store the statistics.
+    fn analyze_table(&self, table_name: &str) -> Result<String, String> {
+        let scan_guard = self.lock_for_scan(table_name)?;
+
+        let Ok(table) = self.table_manager.load_meta_data(table_name) else {
+            return Err(format!("Table '{}' does not exist.", table_name));
+        };
+
+        let column_names: Vec<&str> = table
+            .columns
+            .iter()
+            .chain([&table.primary_key])
+            .map(|column| column.name.as_str())
+            .collect();
+
+        let mut collector = StatisticsCollector::new(&column_names);
+        let mut scan = SeqScan::new(
+            &self.data_manager,
+            TableScan {
+                table_name: table_name.to_string(),
+                where_clauses: vec![],
+                snapshot: self.pager.snapshot(),
+                columns: None,
+                qualifier: None,
+            },
+        );
+
+        while let Some(row) = scan
+            .next()
+            .map_err(|error| format!("Failed to read '{}': {}", table_name, error))?
+        {
+            collector.add(&row);
+        }
+
+        let statistics = collector.finish(self.data_manager.data_page_count(table_name));
+        drop(scan_guard);
+
+        let _guard = self.lock_for_write(table_name)?;
+
+        let Ok(mut table) = self.table_manager.load_meta_data(table_name) else {
+            return Err(format!("Table '{}' does not exist.", table_name));
+        };
+
+        let message = format!(
+            "Analyzed '{}': {} rows on {} data pages.",
+            table_name, statistics.row_count, statistics.data_page_count
+        );
+
+        table.statistics = Some(statistics);
+
+        self.table_manager
+            .store_meta_data(&table)
+            .map_err(|error| error.to_string())?;
+
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{binary_engine::BinaryEngine, Engine};
+    use crate::io_test_context::FileTestContext;
+    use crate::sql_parser::SqlParser;
+
+    fn execute(engine: &BinaryEngine, query: String) -> Vec<String> {
+        engine
+            .execute(SqlParser().parse_query(query).unwrap())
+            .unwrap()
+            .records
+            .unwrap_or_default()
+            .iter()
+            .map(|record| record.fields["plan"].to_string())
+            .collect()
+    }
+
+    fn estimated_rows(plan_line: &str) -> f64 {
+        let (_, rows) = plan_line.rsplit_once("estimated rows: ").unwrap();
+
+        rows.trim_end_matches(')').parse().unwrap()
+    }
+
+    #[test]
+    fn test_estimates_rows_with_the_collected_statistics() {
+        let context = FileTestContext::new();
+        let table_name = context.table_name();
+        let engine = BinaryEngine::new();
+
+        let values: Vec<String> = (0..300)
+            .map(|number| format!("('user{}', {})", number, number % 50))
+            .collect();
+
+        execute(
+            &engine,
+            format!("CREATE TABLE {} (name VARCHAR, age INTEGER);", table_name),
+        );
+        execute(
+            &engine,
+            format!(
+                "INSERT INTO {} (name, age) VALUES {};",
+                table_name,
+                values.join(", ")
+            ),
+        );
+
+        let grouped = format!(
+            "EXPLAIN SELECT age, COUNT(*) FROM {} GROUP BY age;",
+            table_name
+        );
+        let ranged = format!("EXPLAIN SELECT name FROM {} WHERE age < 10;", table_name);
+
+        assert_eq!(estimated_rows(&execute(&engine, grouped.clone())[1]), 30.0);
+        assert_eq!(estimated_rows(&execute(&engine, ranged.clone())[1]), 100.0);
+
+        execute(&engine, format!("ANALYZE {};", table_name));
+
+        let table = engine.table_manager.load_meta_data(table_name).unwrap();
+        let statistics = table.statistics.unwrap();
+
+        assert_eq!(statistics.row_count, 300);
+        assert_eq!(statistics.columns["age"].distinct_count, 50);
+        assert_eq!(statistics.columns["name"].distinct_count, 300);
+        assert_eq!(statistics.columns["id"].null_fraction, 0.0);
+
+        assert_eq!(estimated_rows(&execute(&engine, grouped)[1]), 50.0);
+
+        let rows = estimated_rows(&execute(&engine, ranged)[1]);
+        assert!((50.0..=70.0).contains(&rows), "{}", rows);
+
+        assert!(engine
+            .execute(
+                SqlParser()
+                    .parse_query(String::from("ANALYZE missing_table;"))
+                    .unwrap()
+            )
+            .is_err());
+    }
+}
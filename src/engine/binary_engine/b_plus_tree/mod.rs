@@ -0,0 +1,713 @@
+mod node;
+
+use std::io;
+use std::sync::Arc;
+
+use serde::Serialize;
+
+pub use self::node::Entry;
+use self::node::{Header, Node};
+use super::page::{INDEX_NODE, PAGE_HEADER_SIZE};
+use super::pager::{PagedFile, Pager};
+use super::record_id::RecordId;
+use crate::metadata::KeyRange;
+
+pub const NODE_SIZE: usize = 4096;
+pub const MAX_ENTRY_SIZE: usize = NODE_SIZE / 8;
+/// What a node holds behind its page header.
+pub const NODE_CAPACITY: usize = NODE_SIZE - PAGE_HEADER_SIZE;
+
+// nodes below this size get merged with or refilled from a sibling
+const MIN_NODE_FILL: usize = NODE_SIZE / 4;
+const HEADER_NODE: usize = 0;
+
+/// A B+tree stored in fixed-size nodes of a single file. Node 0 holds the header,
+/// and only the nodes on the path of an operation are read from disk.
+pub struct BPlusTree {
+    file: PagedFile,
+    header: Header,
+}
+
+impl BPlusTree {
+    pub fn create(pager: &Arc<Pager>, path: &str) -> io::Result<()> {
+        pager.write(path, vec![]);
+
+        let mut tree = BPlusTree {
+            file: pager.file(path),
+            header: Header {
+                root: 1,
+                node_count: 2,
+                free_list: None,
+            },
+        };
+
+        tree.write_node(
+            1,
+            &Node::Leaf {
+                entries: vec![],
+                next: None,
+            },
+        )?;
+        tree.write_header()
+    }
+
+    pub fn open(pager: &Arc<Pager>, path: &str) -> io::Result<Self> {
+        let file = pager.file(path);
+
+        let buffer = read_block(&file, HEADER_NODE)?;
+        let header = bincode::deserialize(&buffer[..]).map_err(|_| corrupted(HEADER_NODE))?;
+
+        Ok(BPlusTree { file, header })
+    }
+
+    pub fn insert(&mut self, entry: Entry) -> io::Result<()> {
+        if serialized_size(&entry) > MAX_ENTRY_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Index key is too large.",
+            ));
+        }
+
+        let root = self.header.root;
+
+        if let Some((separator, right_node)) = self.insert_into(root, entry)? {
+            let new_root = self.allocate()?;
+
+            self.write_node(
+                new_root,
+                &Node::Internal {
+                    keys: vec![separator],
+                    children: vec![root, right_node],
+                },
+            )?;
+            self.header.root = new_root;
+        }
+
+        self.write_header()
+    }
+
+    pub fn remove(&mut self, entry: &Entry) -> io::Result<bool> {
+        let root = self.header.root;
+        let removed = self.remove_from(root, entry)?;
+
+        if let Node::Internal { keys, children } = self.read_node(root)? {
+            if keys.is_empty() {
+                self.header.root = children[0];
+                self.free(root)?;
+            }
+        }
+
+        self.write_header()?;
+
+        Ok(removed)
+    }
+
+    /// Walks the leaf chain from the start of the key range until its end,
+    /// collecting all entries whose keys the range includes.
+    pub fn scan(&mut self, key_range: &KeyRange) -> io::Result<Vec<Entry>> {
+        let start = Entry::new(key_range.start(), RecordId::default());
+        let mut node_id = self.header.root;
+
+        while let Node::Internal { keys, children } = self.read_node(node_id)? {
+            node_id = children[keys.partition_point(|key| key <= &start)];
+        }
+
+        let mut found = vec![];
+        let mut next_leaf = Some(node_id);
+
+        while let Some(leaf_id) = next_leaf {
+            let Node::Leaf { entries, next } = self.read_node(leaf_id)? else {
+                return Err(corrupted(leaf_id));
+            };
+
+            for entry in entries.into_iter().filter(|entry| *entry >= start) {
+                if !key_range.in_range(&entry.key) {
+                    return Ok(found);
+                }
+
+                if key_range.includes(&entry.key) {
+                    found.push(entry);
+                }
+            }
+
+            next_leaf = next;
+        }
+
+        Ok(found)
+    }
+
+    /// Returns the separator and id of the new right sibling if the node had to be split.
+    fn insert_into(&mut self, node_id: usize, entry: Entry) -> io::Result<Option<(Entry, usize)>> {
+        match self.read_node(node_id)? {
+            Node::Leaf { mut entries, next } => {
+                match entries.binary_search(&entry) {
+                    Ok(_) => return Ok(None),
+                    Err(position) => entries.insert(position, entry),
+                }
+
+                let node = Node::Leaf { entries, next };
+
+                if serialized_size(&node) <= NODE_CAPACITY {
+                    self.write_node(node_id, &node)?;
+                    return Ok(None);
+                }
+
+                let Node::Leaf { mut entries, next } = node else {
+                    unreachable!()
+                };
+
+                let right_entries = entries.split_off(split_point(&entries));
+                let separator = right_entries[0].without_included();
+                let right_node = self.allocate()?;
+
+                self.write_node(
+                    right_node,
+                    &Node::Leaf {
+                        entries: right_entries,
+                        next,
+                    },
+                )?;
+                self.write_node(
+                    node_id,
+                    &Node::Leaf {
+                        entries,
+                        next: Some(right_node),
+                    },
+                )?;
+
+                Ok(Some((separator, right_node)))
+            }
+            Node::Internal {
+                mut keys,
+                mut children,
+            } => {
+                let position = keys.partition_point(|key| key <= &entry);
+
+                let Some((separator, right_child)) = self.insert_into(children[position], entry)?
+                else {
+                    return Ok(None);
+                };
+
+                keys.insert(position, separator);
+                children.insert(position + 1, right_child);
+
+                let node = Node::Internal { keys, children };
+
+                if serialized_size(&node) <= NODE_CAPACITY {
+                    self.write_node(node_id, &node)?;
+                    return Ok(None);
+                }
+
+                let Node::Internal { keys, children } = node else {
+                    unreachable!()
+                };
+
+                let (left, separator, right) = split_internal(keys, children);
+                let right_node = self.allocate()?;
+
+                self.write_node(right_node, &right)?;
+                self.write_node(node_id, &left)?;
+
+                Ok(Some((separator, right_node)))
+            }
+            Node::Free { .. } => Err(corrupted(node_id)),
+        }
+    }
+
+    fn remove_from(&mut self, node_id: usize, entry: &Entry) -> io::Result<bool> {
+        match self.read_node(node_id)? {
+            Node::Leaf { mut entries, next } => match entries.binary_search(entry) {
+                Ok(position) => {
+                    entries.remove(position);
+                    self.write_node(node_id, &Node::Leaf { entries, next })?;
+
+                    Ok(true)
+                }
+                Err(_) => Ok(false),
+            },
+            Node::Internal { keys, children } => {
+                let position = keys.partition_point(|key| key <= entry);
+                let removed = self.remove_from(children[position], entry)?;
+
+                if removed && serialized_size(&self.read_node(children[position])?) < MIN_NODE_FILL
+                {
+                    self.rebalance(node_id, position)?;
+                }
+
+                Ok(removed)
+            }
+            Node::Free { .. } => Err(corrupted(node_id)),
+        }
+    }
+
+    /// Merges the underfull child at `position` with a sibling, or evens out their entries
+    /// if both don't fit into a single node.
+    fn rebalance(&mut self, parent_id: usize, position: usize) -> io::Result<()> {
+        let Node::Internal {
+            mut keys,
+            mut children,
+        } = self.read_node(parent_id)?
+        else {
+            return Err(corrupted(parent_id));
+        };
+
+        if children.len() < 2 {
+            return Ok(());
+        }
+
+        let left_position = position.saturating_sub(1);
+        let (left_id, right_id) = (children[left_position], children[left_position + 1]);
+
+        match (self.read_node(left_id)?, self.read_node(right_id)?) {
+            (
+                Node::Leaf {
+                    entries: mut left_entries,
+                    ..
+                },
+                Node::Leaf {
+                    entries: right_entries,
+                    next,
+                },
+            ) => {
+                left_entries.extend(right_entries);
+
+                let merged = Node::Leaf {
+                    entries: left_entries,
+                    next,
+                };
+
+                if serialized_size(&merged) <= NODE_CAPACITY {
+                    self.write_node(left_id, &merged)?;
+                    self.free(right_id)?;
+
+                    keys.remove(left_position);
+                    children.remove(left_position + 1);
+                } else {
+                    let Node::Leaf {
+                        entries: mut left_entries,
+                        next,
+                    } = merged
+                    else {
+                        unreachable!()
+                    };
+
+                    let right_entries = left_entries.split_off(split_point(&left_entries));
+                    keys[left_position] = right_entries[0].without_included();
+
+                    self.write_node(
+                        left_id,
+                        &Node::Leaf {
+                            entries: left_entries,
+                            next: Some(right_id),
+                        },
+                    )?;
+                    self.write_node(
+                        right_id,
+                        &Node::Leaf {
+                            entries: right_entries,
+                            next,
+                        },
+                    )?;
+                }
+            }
+            (
+                Node::Internal {
+                    keys: mut left_keys,
+                    children: mut left_children,
+                },
+                Node::Internal {
+                    keys: right_keys,
+                    children: right_children,
+                },
+            ) => {
+                left_keys.push(keys[left_position].clone());
+                left_keys.extend(right_keys);
+                left_children.extend(right_children);
+
+                let merged = Node::Internal {
+                    keys: left_keys,
+                    children: left_children,
+                };
+
+                if serialized_size(&merged) <= NODE_CAPACITY {
+                    self.write_node(left_id, &merged)?;
+                    self.free(right_id)?;
+
+                    keys.remove(left_position);
+                    children.remove(left_position + 1);
+                } else {
+                    let Node::Internal {
+                        keys: left_keys,
+                        children: left_children,
+                    } = merged
+                    else {
+                        unreachable!()
+                    };
+
+                    let (left, separator, right) = split_internal(left_keys, left_children);
+                    keys[left_position] = separator;
+
+                    self.write_node(left_id, &left)?;
+                    self.write_node(right_id, &right)?;
+                }
+            }
+            _ => return Err(corrupted(parent_id)),
+        }
+
+        self.write_node(parent_id, &Node::Internal { keys, children })
+    }
+
+    fn allocate(&mut self) -> io::Result<usize> {
+        match self.header.free_list {
+            Some(node_id) => {
+                let Node::Free { next } = self.read_node(node_id)? else {
+                    return Err(corrupted(node_id));
+                };
+
+                self.header.free_list = next;
+
+                Ok(node_id)
+            }
+            None => {
+                let node_id = self.header.node_count;
+                self.header.node_count += 1;
+
+                Ok(node_id)
+            }
+        }
+    }
+
+    fn free(&mut self, node_id: usize) -> io::Result<()> {
+        self.write_node(
+            node_id,
+            &Node::Free {
+                next: self.header.free_list,
+            },
+        )?;
+        self.header.free_list = Some(node_id);
+
+        Ok(())
+    }
+
+    /// Describes every node of the file that is corrupted, whether it is in use or free.
+    pub fn check_nodes(&self) -> Vec<String> {
+        check_blocks(&self.file, self.header.node_count)
+    }
+
+    fn read_node(&mut self, node_id: usize) -> io::Result<Node> {
+        let buffer = read_block(&self.file, node_id)?;
+
+        bincode::deserialize(&buffer[..]).map_err(|_| corrupted(node_id))
+    }
+
+    fn write_node(&mut self, node_id: usize, node: &Node) -> io::Result<()> {
+        write_block(&self.file, node_id, &bincode::serialize(node).unwrap())
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        let serialized = bincode::serialize(&self.header).unwrap();
+
+        write_block(&self.file, HEADER_NODE, &serialized)
+    }
+}
+
+/// Reads the block and returns what it holds behind the page header, failing if it is corrupted.
+pub(super) fn read_block(file: &PagedFile, node_id: usize) -> io::Result<Vec<u8>> {
+    let buffer = file.read_at(node_id * NODE_SIZE, NODE_SIZE)?;
+
+    INDEX_NODE
+        .decode(&buffer)
+        .map(<[u8]>::to_vec)
+        .map_err(|reason| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Index node {} is corrupted: {}.", node_id, reason),
+            )
+        })
+}
+
+pub(super) fn write_block(file: &PagedFile, node_id: usize, bytes: &[u8]) -> io::Result<()> {
+    let mut buffer = bytes.to_vec();
+    buffer.resize(NODE_CAPACITY, 0);
+
+    file.write_at(node_id * NODE_SIZE, &INDEX_NODE.encode(&buffer))
+}
+
+/// Reads every block of the first `block_count` and describes those that are corrupted.
+pub(super) fn check_blocks(file: &PagedFile, block_count: usize) -> Vec<String> {
+    (0..block_count)
+        .filter_map(|block_id| read_block(file, block_id).err())
+        .map(|error| error.to_string())
+        .collect()
+}
+
+/// Splits an oversized internal node, returning the left half, the separator moving up and the right half.
+fn split_internal(mut keys: Vec<Entry>, mut children: Vec<usize>) -> (Node, Entry, Node) {
+    let middle = split_point(&keys).clamp(1, keys.len() - 2);
+
+    let right_keys = keys.split_off(middle + 1);
+    let separator = keys.pop().unwrap();
+    let right_children = children.split_off(middle + 1);
+
+    (
+        Node::Internal { keys, children },
+        separator,
+        Node::Internal {
+            keys: right_keys,
+            children: right_children,
+        },
+    )
+}
+
+/// Index of the first item of the right half, chosen so that both halves hold about the same number of bytes.
+fn split_point(items: &[Entry]) -> usize {
+    let total_size: usize = items.iter().map(serialized_size).sum();
+    let mut left_size = 0;
+
+    for (position, item) in items.iter().enumerate() {
+        left_size += serialized_size(item);
+
+        if left_size * 2 >= total_size {
+            return (position + 1).clamp(1, items.len() - 1);
+        }
+    }
+
+    items.len() / 2
+}
+
+pub(super) fn serialized_size<T: Serialize>(value: &T) -> usize {
+    bincode::serialized_size(value).unwrap() as usize
+}
+
+fn corrupted(node_id: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Index node {} is corrupted.", node_id),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use super::*;
+    use crate::engine::binary_engine::pager::tests::TempPager;
+    use crate::metadata::{ColumnRange, IndexKey};
+
+    struct TreeFile(TempPager);
+
+    impl TreeFile {
+        fn new() -> Self {
+            let temp = TempPager::new();
+            BPlusTree::create(&temp.pager, &temp.path("index.bin")).unwrap();
+
+            TreeFile(temp)
+        }
+
+        fn open(&self) -> BPlusTree {
+            BPlusTree::open(&self.0.pager, &self.0.path("index.bin")).unwrap()
+        }
+    }
+
+    fn entry(key: usize, data_page: usize) -> Entry {
+        Entry::new(
+            vec![IndexKey::Int(key as i64)],
+            RecordId::new(data_page, key),
+        )
+    }
+
+    fn record_ids(entries: Vec<Entry>) -> Vec<RecordId> {
+        entries.iter().map(|entry| entry.record_id).collect()
+    }
+
+    fn find(tree: &mut BPlusTree, key: IndexKey) -> Vec<RecordId> {
+        record_ids(
+            tree.scan(&KeyRange {
+                prefix: vec![key],
+                last: None,
+            })
+            .unwrap(),
+        )
+    }
+
+    fn range(
+        tree: &mut BPlusTree,
+        lower: Bound<IndexKey>,
+        upper: Bound<IndexKey>,
+    ) -> Vec<RecordId> {
+        record_ids(
+            tree.scan(&KeyRange {
+                prefix: vec![],
+                last: Some(ColumnRange::Range(lower, upper)),
+            })
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_can_find_entries_after_splitting_nodes() {
+        let tree_file = TreeFile::new();
+        let mut tree = tree_file.open();
+
+        for key in (0..3000).rev() {
+            tree.insert(entry(key, 1)).unwrap();
+        }
+
+        let mut tree = tree_file.open();
+
+        assert!(tree.header.node_count > 10);
+        for key in [0, 1, 1500, 2999] {
+            assert_eq!(
+                find(&mut tree, IndexKey::Int(key as i64)),
+                vec![RecordId::new(1, key)]
+            );
+        }
+        assert!(find(&mut tree, IndexKey::Int(3000)).is_empty());
+    }
+
+    #[test]
+    fn test_keeps_all_record_ids_of_duplicate_keys() {
+        let tree_file = TreeFile::new();
+        let mut tree = tree_file.open();
+
+        for data_page in 0..500 {
+            tree.insert(Entry::new(
+                vec![IndexKey::Text(String::from("same"))],
+                RecordId::new(data_page, 0),
+            ))
+            .unwrap();
+            tree.insert(Entry::new(
+                vec![IndexKey::Text(String::from("other"))],
+                RecordId::new(data_page, 1),
+            ))
+            .unwrap();
+        }
+
+        let record_ids = find(&mut tree, IndexKey::Text(String::from("same")));
+
+        assert_eq!(record_ids.len(), 500);
+        assert!(record_ids.iter().all(|record_id| record_id.slot == 0));
+    }
+
+    #[test]
+    fn test_merges_nodes_and_reuses_them_after_removals() {
+        let tree_file = TreeFile::new();
+        let mut tree = tree_file.open();
+
+        for key in 0..3000 {
+            tree.insert(entry(key, 1)).unwrap();
+        }
+
+        let node_count = tree.header.node_count;
+
+        for key in (0..3000).filter(|key| key % 10 != 0) {
+            assert!(tree.remove(&entry(key, 1)).unwrap());
+        }
+
+        assert!(!tree.remove(&entry(1, 1)).unwrap());
+        assert!(tree.header.free_list.is_some());
+
+        for key in 0..3000 {
+            let expected = if key % 10 == 0 {
+                vec![RecordId::new(1, key)]
+            } else {
+                vec![]
+            };
+
+            assert_eq!(find(&mut tree, IndexKey::Int(key as i64)), expected);
+        }
+
+        for key in (0..3000).filter(|key| key % 10 != 0) {
+            tree.insert(entry(key, 1)).unwrap();
+        }
+
+        assert_eq!(tree.header.node_count, node_count);
+    }
+
+    #[test]
+    fn test_rejects_keys_larger_than_a_node_allows() {
+        let tree_file = TreeFile::new();
+        let mut tree = tree_file.open();
+
+        assert!(tree
+            .insert(Entry::new(
+                vec![IndexKey::Text("a".repeat(NODE_SIZE))],
+                RecordId::default()
+            ))
+            .is_err());
+    }
+
+    #[test]
+    fn test_can_scan_ranges_and_prefixes() {
+        let tree_file = TreeFile::new();
+        let mut tree = tree_file.open();
+
+        for key in 0..1000 {
+            tree.insert(entry(key, 1)).unwrap();
+            tree.insert(Entry::new(
+                vec![IndexKey::Text(format!("name_{}", key))],
+                RecordId::new(2, key),
+            ))
+            .unwrap();
+        }
+
+        let record_ids = range(
+            &mut tree,
+            Bound::Excluded(IndexKey::Int(9)),
+            Bound::Included(IndexKey::Int(100)),
+        );
+
+        assert_eq!(record_ids.len(), 91);
+        assert_eq!(record_ids.first().unwrap(), &RecordId::new(1, 10));
+        assert_eq!(record_ids.last().unwrap(), &RecordId::new(1, 100));
+
+        assert_eq!(
+            range(
+                &mut tree,
+                Bound::Unbounded,
+                Bound::Excluded(IndexKey::Int(10))
+            )
+            .len(),
+            10
+        );
+
+        // name_99, name_990 - name_999
+        let prefix_range = KeyRange {
+            prefix: vec![],
+            last: Some(ColumnRange::Prefix(String::from("name_99"))),
+        };
+        assert_eq!(tree.scan(&prefix_range).unwrap().len(), 11);
+    }
+
+    #[test]
+    fn test_can_scan_the_leftmost_prefix_of_tuple_keys() {
+        let tree_file = TreeFile::new();
+        let mut tree = tree_file.open();
+
+        for a in 0..20 {
+            for b in 0..20 {
+                tree.insert(Entry::new(
+                    vec![IndexKey::Int(a), IndexKey::Int(b)],
+                    RecordId::new(a as usize, b as usize),
+                ))
+                .unwrap();
+            }
+        }
+
+        assert_eq!(find(&mut tree, IndexKey::Int(10)).len(), 20);
+
+        let key_range = KeyRange {
+            prefix: vec![IndexKey::Int(10)],
+            last: Some(ColumnRange::Range(
+                Bound::Excluded(IndexKey::Int(15)),
+                Bound::Unbounded,
+            )),
+        };
+        assert_eq!(
+            record_ids(tree.scan(&key_range).unwrap()),
+            (16..20)
+                .map(|b| RecordId::new(10, b))
+                .collect::<Vec<RecordId>>()
+        );
+    }
+}
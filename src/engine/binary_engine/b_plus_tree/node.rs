@@ -0,0 +1,84 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use super::super::record_id::RecordId;
+use crate::metadata::IndexKey;
+
+/// Keys are tuples with one value per indexed column. The record id is part of the sort order,
+/// which keeps entries unique even for duplicate keys. The values of the index's INCLUDE columns
+/// are carried along, but take no part in comparisons.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Entry {
+    pub key: Vec<IndexKey>,
+    pub record_id: RecordId,
+    pub included: Vec<Option<IndexKey>>,
+}
+
+impl Entry {
+    pub fn new(key: Vec<IndexKey>, record_id: RecordId) -> Self {
+        Entry {
+            key,
+            record_id,
+            included: vec![],
+        }
+    }
+
+    pub fn with_included(
+        key: Vec<IndexKey>,
+        record_id: RecordId,
+        included: Vec<Option<IndexKey>>,
+    ) -> Self {
+        Entry {
+            included,
+            ..Entry::new(key, record_id)
+        }
+    }
+
+    /// The entry without its included values, as used for separators in internal nodes.
+    pub fn without_included(&self) -> Self {
+        Entry::new(self.key.clone(), self.record_id)
+    }
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.key, self.record_id).cmp(&(&other.key, other.record_id))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Node {
+    Leaf {
+        entries: Vec<Entry>,
+        next: Option<usize>,
+    },
+    Internal {
+        keys: Vec<Entry>,
+        children: Vec<usize>,
+    },
+    Free {
+        next: Option<usize>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Header {
+    pub root: usize,
+    pub node_count: usize,
+    pub free_list: Option<usize>,
+}
@@ -0,0 +1,57 @@
+use super::{EngineResponse, EngineResult};
+use crate::engine::checkpoint::Checkpoint;
+
+impl Checkpoint for super::BinaryEngine {
+    fn checkpoint(&self) -> EngineResult {
+        self.check_writable()?;
+
+        let lsn = self
+            .pager
+            .checkpoint()
+            .map_err(|error| format!("Failed to checkpoint: {}", error))?;
+
+        Ok(EngineResponse {
+            table: None,
+            records: None,
+            message: Some(format!("Checkpointed up to LSN {}.", lsn)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::engine::{binary_engine::BinaryEngine, Engine};
+    use crate::io_test_context::FileTestContext;
+    use crate::sql_parser::SqlParser;
+
+    #[test]
+    fn test_checkpoint_keeps_committed_records() {
+        let context = FileTestContext::new();
+        let engine = BinaryEngine::new();
+        let parser = SqlParser();
+
+        for query in [
+            format!("CREATE TABLE {} (name VARCHAR);", context.table_name()),
+            format!("INSERT INTO {} (name) VALUES ('a');", context.table_name()),
+            String::from("CHECKPOINT;"),
+        ] {
+            engine.execute(parser.parse_query(query).unwrap()).unwrap();
+        }
+
+        assert!(fs::metadata(engine.file_paths.data_page(context.table_name(), 1)).is_ok());
+
+        let records = engine
+            .execute(
+                parser
+                    .parse_query(format!("SELECT * FROM {};", context.table_name()))
+                    .unwrap(),
+            )
+            .unwrap()
+            .records
+            .unwrap();
+
+        assert_eq!(records.len(), 1);
+    }
+}
@@ -1,14 +1,14 @@
-use std::path;
+use std::collections::BTreeSet;
+use std::{io, path};
 
 use crate::{engine::create_index::CreateIndex, metadata};
 
+// number of data pages between two progress reports while backfilling an index
+const BACKFILL_PROGRESS_INTERVAL: usize = 50;
+
 impl CreateIndex for super::BinaryEngine {
-    fn create_index(
-        &self,
-        table_name: String,
-        column_name: String,
-        index_name: String,
-    ) -> super::EngineResult {
+    fn create_index(&self, table_name: String, index: metadata::Index) -> super::EngineResult {
+        let _guard = self.lock_for_write(&table_name)?;
         let table_path = self.file_paths.table_path(&table_name);
 
         if !path::Path::new(&table_path).exists() {
@@ -19,36 +19,56 @@ impl CreateIndex for super::BinaryEngine {
 
         match table {
             Ok(table) => {
-                if table
+                if index.columns.is_empty() {
+                    return Err(String::from("An index needs at least one column."));
+                }
+
+                let column_names = index
                     .columns
                     .iter()
-                    .filter(|column| column.name == column_name)
-                    .collect::<Vec<&metadata::Column>>()
-                    .is_empty()
-                {
-                    return Err(format!(
-                        "'{}' does not exist on '{}'",
-                        column_name, table_name
-                    ));
+                    .map(|column| &column.name)
+                    .chain(index.where_clauses.iter().map(|clause| &clause.column_name))
+                    .chain(index.included_columns.iter());
+
+                for column_name in column_names {
+                    if table.column_type(column_name).is_none() {
+                        return Err(format!(
+                            "'{}' does not exist on '{}'",
+                            column_name, table_name
+                        ));
+                    }
                 }
 
-                if !table
-                    .indices
-                    .iter()
-                    .filter(|index| index.column_name == column_name)
-                    .collect::<Vec<&metadata::Index>>()
-                    .is_empty()
-                {
+                if table.indices.iter().any(|existing| {
+                    existing.columns == index.columns
+                        && existing.where_clauses == index.where_clauses
+                        && existing.included_columns == index.included_columns
+                }) {
                     return Err(format!(
                         "'{}({})' is already indexed.",
-                        table_name, column_name
+                        table_name,
+                        index.column_list()
                     ));
                 }
 
+                if self
+                    .index_manager
+                    .create_index_file(&table_name, &index)
+                    .is_err()
+                {
+                    return Err("Error creating the index file.".to_string());
+                }
+
+                if let Err(error) = self.backfill_index(&table_name, &index) {
+                    self.index_manager
+                        .delete_index_file(&table_name, &index)
+                        .ok();
+
+                    return Err(format!("Error building the index: {}", error));
+                }
+
                 let mut table = table;
-                table
-                    .indices
-                    .push(metadata::Index::new(index_name, &column_name));
+                table.indices.push(index);
 
                 let result = self.table_manager.store_meta_data(&table);
 
@@ -59,6 +79,7 @@ impl CreateIndex for super::BinaryEngine {
                 Ok(super::EngineResponse {
                     table: Some(table),
                     records: None,
+                    message: None,
                 })
             }
             Err(error) => Err(error.to_string()),
@@ -66,10 +87,62 @@ impl CreateIndex for super::BinaryEngine {
     }
 }
 
+impl super::BinaryEngine {
+    /// Adds all stored versions to a freshly created index, including deleted ones that some
+    /// snapshot may still see. The index only becomes usable once it is part of the stored
+    /// meta-data, which happens after this has finished. Fails on the first duplicate key
+    /// among the versions that are not deleted if the index is unique.
+    fn backfill_index(&self, table_name: &str, index: &metadata::Index) -> io::Result<()> {
+        let data_page_count = self.data_manager.data_page_count(table_name);
+        let mut indexed_rows = 0;
+        let mut undeleted_keys = BTreeSet::new();
+
+        for data_page_index in 1..=data_page_count {
+            for (record_id, version) in self
+                .data_manager
+                .load_data_page(table_name, data_page_index)?
+            {
+                let record = version.record;
+
+                if let Some(key) = index.key_of(&record) {
+                    if index.unique && version.xmax.is_none() && !undeleted_keys.insert(key.clone())
+                    {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!(
+                                "duplicate key {} violates unique index '{}'.",
+                                metadata::IndexKey::format_key(&key),
+                                index.name
+                            ),
+                        ));
+                    }
+
+                    self.index_manager
+                        .insert(table_name, index, &record, record_id)?;
+                    indexed_rows += 1;
+                }
+            }
+
+            if data_page_index % BACKFILL_PROGRESS_INTERVAL == 0 {
+                println!(
+                    "\tBuilding index '{}': {}/{} data pages, {} rows",
+                    index.name, data_page_index, data_page_count, indexed_rows
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::engine::{CreateIndex, CreateTable, Insert, Select};
+    use std::path::Path;
+
+    use crate::metadata::{Index, IndexColumn, IndexKey, KeyRange};
+    use crate::sql_parser::query::{Condition, Predicate};
     use crate::{engine::binary_engine::BinaryEngine, io_test_context::FileTestContext};
-    use crate::engine::{CreateTable, CreateIndex, Insert};
 
     #[test]
     fn test_can_set_indices_after_table_creation() {
@@ -89,15 +162,17 @@ mod tests {
         engine
             .create_index(
                 context.table_name().to_string(),
-                String::from("email"),
-                String::from("email_index"),
+                Index::new(String::from("email_index"), vec![IndexColumn::new("email")]),
             )
             .unwrap();
 
-        let table = engine.table_manager.load_meta_data(context.table_name()).unwrap();
+        let table = engine
+            .table_manager
+            .load_meta_data(context.table_name())
+            .unwrap();
 
         assert_eq!(table.indices.len(), 2);
-        assert_eq!(table.indices.last().unwrap().column_name, "email");
+        assert_eq!(table.indices.last().unwrap().column_list(), "email");
     }
 
     #[test]
@@ -118,15 +193,13 @@ mod tests {
         engine
             .create_index(
                 context.table_name().to_string(),
-                String::from("email"),
-                String::from("email_index"),
+                Index::new(String::from("email_index"), vec![IndexColumn::new("email")]),
             )
             .unwrap();
 
         match engine.create_index(
             context.table_name().to_string(),
-            String::from("email"),
-            String::from("email_index"),
+            Index::new(String::from("email_index"), vec![IndexColumn::new("email")]),
         ) {
             Ok(..) => panic!(),
             Err(message) => assert_eq!(
@@ -163,13 +236,122 @@ mod tests {
             )
             .unwrap();
 
-        let table = engine.table_manager.load_meta_data(context.table_name()).unwrap();
+        let table = engine
+            .table_manager
+            .load_meta_data(context.table_name())
+            .unwrap();
+
+        assert_eq!(
+            engine
+                .index_manager
+                .scan(
+                    context.table_name(),
+                    table.indices.first().unwrap(),
+                    &KeyRange {
+                        prefix: vec![IndexKey::Int(2)],
+                        last: None
+                    }
+                )
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_backfills_existing_rows_when_creating_an_index() {
+        let context = FileTestContext::new();
+        let engine = BinaryEngine::new();
+
+        engine
+            .create_table(
+                context.table_name().to_string(),
+                vec![
+                    vec!["name".to_string(), "VARCHAR".to_string()],
+                    vec!["email".to_string(), "VARCHAR".to_string()],
+                ],
+            )
+            .unwrap();
+
+        engine
+            .insert(
+                context.table_name().to_string(),
+                vec!["name".to_string(), "email".to_string()],
+                vec![
+                    vec!["john".to_string(), "john@mail.com".to_string()],
+                    vec!["doe".to_string(), "doe@mail.com".to_string()],
+                    vec!["jane".to_string(), "doe@mail.com".to_string()],
+                ],
+            )
+            .unwrap();
+
+        engine
+            .create_index(
+                context.table_name().to_string(),
+                Index::new(String::from("email_index"), vec![IndexColumn::new("email")]),
+            )
+            .unwrap();
+
+        let where_clauses = vec![Condition::new(
+            "email",
+            Predicate::Equal(String::from("doe@mail.com")),
+        )];
 
-        assert!(table
-            .indices
-            .first()
+        let records = engine
+            .select(context.table_name().to_string(), vec![], where_clauses)
             .unwrap()
-            .data_page_indices("2")
-            .is_ok());
+            .records
+            .unwrap();
+
+        assert_eq!(records.len(), 2);
+    }
+
+    #[test]
+    fn test_cannot_create_a_unique_index_over_duplicate_rows() {
+        let context = FileTestContext::new();
+        let engine = BinaryEngine::new();
+
+        engine
+            .create_table(
+                context.table_name().to_string(),
+                vec![vec!["email".to_string(), "VARCHAR".to_string()]],
+            )
+            .unwrap();
+
+        engine
+            .insert(
+                context.table_name().to_string(),
+                vec!["email".to_string()],
+                vec![
+                    vec!["john@mail.com".to_string()],
+                    vec!["doe@mail.com".to_string()],
+                    vec!["john@mail.com".to_string()],
+                ],
+            )
+            .unwrap();
+
+        let error = engine
+            .create_index(
+                context.table_name().to_string(),
+                Index {
+                    unique: true,
+                    ..Index::new(String::from("email_index"), vec![IndexColumn::new("email")])
+                },
+            )
+            .unwrap_err();
+
+        assert!(error.contains("('john@mail.com')"));
+        assert!(!Path::new(
+            &engine
+                .file_paths
+                .index_path(context.table_name(), "email_index")
+        )
+        .exists());
+
+        let table = engine
+            .table_manager
+            .load_meta_data(context.table_name())
+            .unwrap();
+        assert_eq!(table.indices.len(), 1);
     }
 }
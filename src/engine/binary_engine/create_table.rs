@@ -1,17 +1,16 @@
-use std::{fs, path};
-
 use crate::{engine::create_table::CreateTable, metadata};
 
 impl CreateTable for super::BinaryEngine {
     fn create_table(&self, table_name: String, columns: Vec<Vec<String>>) -> super::EngineResult {
-        let table_path = self.file_paths.table_path(&table_name);
+        let _guard = self.lock_for_write(&table_name)?;
+        let table = metadata::Table::new(table_name, columns);
 
-        if !path::Path::new(&table_path).exists() {
-            fs::create_dir(table_path.clone()).expect("\tFailed to create dir for new table.");
+        for index in &table.indices {
+            self.index_manager
+                .create_index_file(&table.name, index)
+                .expect("\tFailed to create index file.");
         }
 
-        let table = metadata::Table::new(table_name, columns);
-
         self.table_manager
             .store_meta_data(&table)
             .expect("\tFailed to store meta-data.");
@@ -19,14 +18,13 @@ impl CreateTable for super::BinaryEngine {
         Ok(super::EngineResponse {
             table: Some(table),
             records: None,
+            message: None,
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
-
     use super::*;
     use crate::engine::{CreateTable, Insert, Select};
     use crate::{engine::binary_engine::BinaryEngine, io_test_context::FileTestContext};
@@ -146,7 +144,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(table.indices.len(), 1);
-        assert_eq!(table.indices.first().unwrap().column_name, "id");
+        assert_eq!(table.indices.first().unwrap().column_list(), "id");
     }
 
     #[test]
@@ -170,7 +168,7 @@ mod tests {
             .unwrap();
 
         let records = engine
-            .select(context.table_name().to_string(), vec![], HashMap::new())
+            .select(context.table_name().to_string(), vec![], vec![])
             .unwrap()
             .records
             .unwrap();
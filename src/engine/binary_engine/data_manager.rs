@@ -1,149 +1,718 @@
 use crate::engine::file_paths::FilePaths;
+use crate::sql_parser::query::Condition;
 
 use super::dynamic_record;
-use super::utils::selected_all_columns;
-use std::collections::HashMap;
-use std::fs::{self, OpenOptions};
-use std::io::{self, Read, Write};
-use std::path;
-use std::path::Path;
-use std::rc::Rc;
+use super::mvcc::{RowVersion, Snapshot, TransactionId};
+use super::overflow_manager::OverflowManager;
+use super::page::{DATA_PAGE, PAGE_HEADER_SIZE};
+use super::pager::Pager;
+use super::record_id::RecordId;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::sync::Arc;
+
+pub const DATA_PAGE_SIZE: usize = 16000;
+
+// the page header, followed by the length bincode prefixes the records vector with
+const DATA_PAGE_HEADER_SIZE: usize = PAGE_HEADER_SIZE + 8;
+
+// every version is stored behind the tag of its slot and its transaction stamps,
+// which take 1 + 8 + 9 bytes once the version is deleted
+const VERSION_HEADER_SIZE: usize = 18;
+const MAX_INLINE_RECORD_SIZE: usize = DATA_PAGE_SIZE - DATA_PAGE_HEADER_SIZE - VERSION_HEADER_SIZE;
+
+// values larger than this always go to overflow pages so that data pages stay dense
+const OVERFLOW_THRESHOLD: usize = DATA_PAGE_SIZE / 4;
+
+/// A record as it is stored on a data page, with values too large to be stored inline
+/// moved to overflow pages. Its values are laid out like those of `dynamic_record::Value`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct StoredRecord {
+    fields: HashMap<String, StoredValue>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+enum StoredValue {
+    Int(i64),
+    Text(String),
+    /// Points at the first overflow page holding the value.
+    Overflow(usize),
+}
+
+impl From<dynamic_record::Value> for StoredValue {
+    fn from(value: dynamic_record::Value) -> Self {
+        match value {
+            dynamic_record::Value::Int(integer) => StoredValue::Int(integer),
+            dynamic_record::Value::Text(text) => StoredValue::Text(text),
+        }
+    }
+}
 
 pub struct DataManager {
-    file_paths: Rc<FilePaths>,
+    file_paths: Arc<FilePaths>,
+    pager: Arc<Pager>,
+    overflow_manager: OverflowManager,
 }
 
 impl DataManager {
-    pub fn new(file_paths: Rc<FilePaths>) -> Self {
-        DataManager { file_paths }
+    pub fn new(file_paths: Arc<FilePaths>, pager: Arc<Pager>) -> Self {
+        DataManager {
+            overflow_manager: OverflowManager::new(Arc::clone(&file_paths), Arc::clone(&pager)),
+            file_paths,
+            pager,
+        }
     }
 
     pub fn save_record(
         &self,
         record: dynamic_record::DynamicRecord,
         table_name: &str,
-    ) -> std::io::Result<usize> {
+    ) -> std::io::Result<RecordId> {
+        self.pager
+            .atomically(|| self.append_record(record, table_name))
+    }
+
+    fn append_record(
+        &self,
+        record: dynamic_record::DynamicRecord,
+        table_name: &str,
+    ) -> io::Result<RecordId> {
+        let version = RowVersion::new(self.pager.transaction_id()?, record);
+
+        self.append_version(version, table_name)
+    }
+
+    fn append_version(&self, version: RowVersion, table_name: &str) -> io::Result<RecordId> {
+        let version = RowVersion {
+            xmin: version.xmin,
+            xmax: version.xmax,
+            record: self.spill_large_values(version.record, table_name)?,
+        };
+        let record_size =
+            bincode::serialized_size(&version.record).unwrap() as usize + VERSION_HEADER_SIZE;
+        let version = Some(version);
+
         let mut file_path;
         let mut data_page_index: usize = 1;
 
         loop {
             file_path = self.file_paths.data_page(table_name, data_page_index);
 
-            if !Path::new(&file_path).exists() {
+            if !self.pager.exists(&file_path) {
                 break;
             }
 
-            let path_size = fs::metadata(&file_path).unwrap().len();
+            let page_size = self.pager.len(&file_path)?;
 
-            if path_size < 16000 {
+            if page_size + record_size <= DATA_PAGE_SIZE {
                 break;
             }
 
             data_page_index += 1;
         }
 
-        let mut existing_contents: Vec<dynamic_record::DynamicRecord> = vec![];
-        if Path::new(&file_path).exists() {
-            existing_contents = self.load_records(file_path.as_str(), None, None).unwrap();
+        let mut existing_contents: Vec<Option<RowVersion<StoredRecord>>> = vec![];
+        if self.pager.exists(&file_path) {
+            existing_contents = self.read_data_page(table_name, data_page_index)?;
         }
 
-        existing_contents.push(record);
+        let slot = existing_contents.len();
+        existing_contents.push(version);
 
-        let serialized = bincode::serialize(&existing_contents).unwrap();
+        self.write_data_page(&file_path, &existing_contents);
 
-        let mut file = OpenOptions::new()
-            .write(true)
-            .append(false)
-            .create(true)
-            .open(&file_path)
-            .unwrap();
+        Ok(RecordId::new(data_page_index, slot))
+    }
+
+    /// Marks the versions visible to the snapshot of the session that match the where clauses
+    /// as deleted by its transaction, and returns them. Fails if one of them was deleted
+    /// by a transaction that committed after the snapshot was taken.
+    pub fn delete_records(
+        &self,
+        table_name: &str,
+        where_clauses: &[Condition],
+        record_ids: Option<Vec<RecordId>>,
+    ) -> io::Result<Vec<(RecordId, dynamic_record::DynamicRecord)>> {
+        self.pager.atomically(|| {
+            let transaction_id = self.pager.transaction_id()?;
+            // taken after the transaction id, so that it sees the own changes
+            let snapshot = self.pager.snapshot();
+            let mut deleted = vec![];
+
+            for (data_page_index, slots) in self.slots_per_data_page(table_name, record_ids) {
+                let path = self.file_paths.data_page(table_name, data_page_index);
+                let mut versions = self.read_data_page(table_name, data_page_index)?;
+                let mut changed = false;
+
+                for (slot, version) in versions.iter_mut().enumerate() {
+                    let Some(version) = version else {
+                        continue;
+                    };
+
+                    if slots.as_ref().is_some_and(|slots| !slots.contains(&slot))
+                        || !snapshot.sees_changes_of(version.xmin)
+                    {
+                        continue;
+                    }
+
+                    let deleted_concurrently = match version.xmax {
+                        Some(xmax) if snapshot.sees_changes_of(xmax) => continue,
+                        Some(_) => true,
+                        None => false,
+                    };
 
-        file.write_all(&serialized)?;
+                    let record = self.load_record(&version.record, table_name)?;
 
-        Ok(data_page_index)
+                    if !record.entry_should_be_included(where_clauses) {
+                        continue;
+                    }
+
+                    if deleted_concurrently {
+                        return Err(io::Error::other(format!(
+                            "Could not serialize access to '{}' because of a concurrent delete.",
+                            table_name
+                        )));
+                    }
+
+                    version.xmax = Some(transaction_id);
+                    changed = true;
+                    deleted.push((RecordId::new(data_page_index, slot), record));
+                }
+
+                if changed {
+                    self.write_data_page(&path, &versions);
+                }
+            }
+
+            Ok(deleted)
+        })
     }
 
-    pub fn load_table_contents(
+    /// Empties the slots of all versions that were deleted before the horizon, so that no
+    /// snapshot can see them anymore, frees their overflow pages and returns their records.
+    /// The slots stay in place, since the record ids of all other versions must not change.
+    pub fn collect_garbage(
         &self,
         table_name: &str,
-        column_names: Vec<String>,
-        where_clauses: HashMap<String, String>,
-        data_page_indices: Option<Vec<usize>>,
-    ) -> io::Result<Vec<dynamic_record::DynamicRecord>> {
-        let mut records: Vec<dynamic_record::DynamicRecord> = vec![];
+        horizon: TransactionId,
+    ) -> io::Result<Vec<(RecordId, dynamic_record::DynamicRecord)>> {
+        self.pager.atomically(|| {
+            let mut collected = vec![];
 
-        match data_page_indices {
-            Some(indices) => {
-                for index in indices {
-                    let path = self.file_paths.data_page(table_name, index);
+            for data_page_index in 1..=self.data_page_count(table_name) {
+                let path = self.file_paths.data_page(table_name, data_page_index);
+                let mut versions = self.read_data_page(table_name, data_page_index)?;
+                let mut changed = false;
 
-                    records.extend(
-                        self.load_records(&path, Some(&column_names), Some(&where_clauses))
-                            .unwrap(),
+                for (slot, version) in versions.iter_mut().enumerate() {
+                    let collectable = matches!(
+                        version,
+                        Some(RowVersion { xmax: Some(xmax), .. }) if *xmax < horizon
                     );
+
+                    if !collectable {
+                        continue;
+                    }
+
+                    let stored_record = version.take().unwrap().record;
+                    let record = self.load_record(&stored_record, table_name)?;
+                    self.free_overflow_pages(&stored_record, table_name)?;
+
+                    collected.push((RecordId::new(data_page_index, slot), record));
+                    changed = true;
                 }
 
-                Ok(records)
+                if changed {
+                    self.write_data_page(&path, &versions);
+                }
             }
-            None => {
-                let mut data_page_index = 1;
-                let mut records: Vec<dynamic_record::DynamicRecord> = vec![];
 
-                loop {
-                    let path = self.file_paths.data_page(table_name, data_page_index);
+            Ok(collected)
+        })
+    }
 
-                    if !path::Path::new(&path).exists() {
-                        break;
+    /// Rewrites the table into as few data pages as possible, dropping the versions deleted before
+    /// the horizon and the empty slots of collected ones, and moves large values into fresh overflow
+    /// pages. Returns the remaining versions with their new record ids.
+    pub fn compact(
+        &self,
+        table_name: &str,
+        horizon: TransactionId,
+    ) -> io::Result<Vec<(RecordId, RowVersion)>> {
+        self.pager.atomically(|| {
+            let mut versions = vec![];
+
+            for data_page_index in 1..=self.data_page_count(table_name) {
+                for version in self
+                    .read_data_page(table_name, data_page_index)?
+                    .into_iter()
+                    .flatten()
+                {
+                    let record = self.load_record(&version.record, table_name)?;
+                    self.free_overflow_pages(&version.record, table_name)?;
+
+                    if version.xmax.is_some_and(|xmax| xmax < horizon) {
+                        continue;
                     }
 
-                    records.extend(
-                        self.load_records(&path, Some(&column_names), Some(&where_clauses))
-                            .unwrap(),
-                    );
+                    versions.push(RowVersion {
+                        xmin: version.xmin,
+                        xmax: version.xmax,
+                        record,
+                    });
+                }
+
+                self.pager
+                    .remove(&self.file_paths.data_page(table_name, data_page_index))?;
+            }
+
+            versions
+                .into_iter()
+                .map(|version| Ok((self.append_version(version.clone(), table_name)?, version)))
+                .collect()
+        })
+    }
+
+    /// Whether any of the versions has not been deleted, no matter which snapshots see it.
+    pub fn contains_undeleted_version(
+        &self,
+        table_name: &str,
+        record_ids: Vec<RecordId>,
+    ) -> io::Result<bool> {
+        for (data_page_index, slots) in self.slots_per_data_page(table_name, Some(record_ids)) {
+            let versions = self.read_data_page(table_name, data_page_index)?;
+
+            if slots.unwrap_or_default().into_iter().any(|slot| {
+                versions.get(slot).is_some_and(|version| {
+                    version
+                        .as_ref()
+                        .is_some_and(|version| version.xmax.is_none())
+                })
+            }) {
+                return Ok(true);
+            }
+        }
 
-                    data_page_index += 1;
+        Ok(false)
+    }
+
+    pub fn data_page_count(&self, table_name: &str) -> usize {
+        let mut data_page_count = 0;
+
+        while self
+            .pager
+            .exists(&self.file_paths.data_page(table_name, data_page_count + 1))
+        {
+            data_page_count += 1;
+        }
+
+        data_page_count
+    }
+
+    /// Loads all versions stored on a data page together with their record ids,
+    /// no matter which snapshots see them.
+    pub fn load_data_page(
+        &self,
+        table_name: &str,
+        data_page_index: usize,
+    ) -> io::Result<Vec<(RecordId, RowVersion)>> {
+        let mut versions = vec![];
+
+        for (slot, version) in self
+            .read_data_page(table_name, data_page_index)?
+            .into_iter()
+            .enumerate()
+        {
+            if let Some(version) = version {
+                let record = self.load_record(&version.record, table_name)?;
+
+                versions.push((
+                    RecordId::new(data_page_index, slot),
+                    RowVersion {
+                        xmin: version.xmin,
+                        xmax: version.xmax,
+                        record,
+                    },
+                ));
+            }
+        }
+
+        Ok(versions)
+    }
+
+    /// Groups the record ids by their data page, or lists every data page
+    /// without restricting its slots if there are no record ids.
+    pub fn slots_per_data_page(
+        &self,
+        table_name: &str,
+        record_ids: Option<Vec<RecordId>>,
+    ) -> BTreeMap<usize, Option<Vec<usize>>> {
+        match record_ids {
+            Some(record_ids) => {
+                let mut slots_per_data_page: BTreeMap<usize, Option<Vec<usize>>> = BTreeMap::new();
+
+                for record_id in record_ids {
+                    slots_per_data_page
+                        .entry(record_id.data_page)
+                        .or_insert_with(|| Some(vec![]))
+                        .get_or_insert_with(Vec::new)
+                        .push(record_id.slot);
                 }
 
-                Ok(records)
+                slots_per_data_page
             }
+            None => (1..=self.data_page_count(table_name))
+                .map(|data_page_index| (data_page_index, None))
+                .collect(),
         }
     }
 
-    fn load_records(
+    /// Loads the records of a data page the snapshot sees that match the where clauses,
+    /// optionally only those in the slots.
+    pub fn load_records(
         &self,
-        path: &str,
-        selected_columns: Option<&Vec<String>>,
-        where_clauses: Option<&HashMap<String, String>>,
+        table_name: &str,
+        data_page_index: usize,
+        slots: Option<&[usize]>,
+        where_clauses: &[Condition],
+        snapshot: &Snapshot,
     ) -> io::Result<Vec<dynamic_record::DynamicRecord>> {
-        let mut records: Vec<dynamic_record::DynamicRecord> = vec![];
+        let mut current_data_page_records = self
+            .read_data_page(table_name, data_page_index)?
+            .into_iter()
+            .enumerate()
+            .filter(|(slot, _)| slots.is_none_or(|slots| slots.contains(slot)))
+            .filter_map(|(_, version)| version.filter(|version| snapshot.sees(version)))
+            .map(|version| self.load_record(&version.record, table_name))
+            .collect::<io::Result<Vec<dynamic_record::DynamicRecord>>>()?;
 
-        let mut buffer = Vec::new();
-        let mut file = fs::File::open(path).unwrap();
-        file.read_to_end(&mut buffer)?;
+        current_data_page_records.retain(|record| record.entry_should_be_included(where_clauses));
 
-        match bincode::deserialize::<Vec<dynamic_record::DynamicRecord>>(&buffer[..]) {
-            Ok(mut current_data_page_records) => {
-                if where_clauses.is_some() {
-                    current_data_page_records
-                        .retain(|record| record.entry_should_be_included(&where_clauses));
-                }
+        Ok(current_data_page_records)
+    }
 
-                if selected_columns.is_some()
-                    && !selected_columns.unwrap().is_empty()
-                    && !selected_all_columns(selected_columns.unwrap())
+    /// Reads the versions stored on a data page, failing if the page does not pass verification.
+    fn read_data_page(
+        &self,
+        table_name: &str,
+        data_page_index: usize,
+    ) -> io::Result<Vec<Option<RowVersion<StoredRecord>>>> {
+        let buffer = self
+            .pager
+            .read(&self.file_paths.data_page(table_name, data_page_index))?;
+
+        decode_data_page(&buffer).map_err(|reason| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Data page {} of table '{}' is corrupted: {}.",
+                    data_page_index, table_name, reason
+                ),
+            )
+        })
+    }
+
+    fn write_data_page(&self, path: &str, versions: &[Option<RowVersion<StoredRecord>>]) {
+        self.pager.write(path, encode_data_page(versions));
+    }
+
+    /// Moves values into overflow pages until the record fits into a single data page.
+    fn spill_large_values(
+        &self,
+        record: dynamic_record::DynamicRecord,
+        table_name: &str,
+    ) -> io::Result<StoredRecord> {
+        let mut record = StoredRecord {
+            fields: record
+                .fields
+                .into_iter()
+                .map(|(column_name, value)| (column_name, StoredValue::from(value)))
+                .collect(),
+        };
+
+        loop {
+            let record_size = bincode::serialized_size(&record).unwrap() as usize;
+
+            let largest_inline_value = record
+                .fields
+                .iter()
+                .filter(|(_, value)| !matches!(value, StoredValue::Overflow(_)))
+                .map(|(column, value)| {
+                    (
+                        column.clone(),
+                        bincode::serialized_size(value).unwrap() as usize,
+                    )
+                })
+                .max_by_key(|(_, value_size)| *value_size);
+
+            let column_name = match largest_inline_value {
+                Some((column_name, value_size))
+                    if value_size > OVERFLOW_THRESHOLD || record_size > MAX_INLINE_RECORD_SIZE =>
                 {
-                    for record in current_data_page_records.iter_mut() {
-                        record.filter_columns(selected_columns.unwrap());
-                    }
+                    column_name
+                }
+                _ if record_size > MAX_INLINE_RECORD_SIZE => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "Record does not fit into a data page.",
+                    ))
                 }
+                _ => return Ok(record),
+            };
 
-                records.extend(current_data_page_records);
-            }
-            Err(e) => {
-                eprintln!("Error deserializing records: {:?}", e);
+            let value = record.fields.remove(&column_name).unwrap();
+            let first_page_index = self
+                .overflow_manager
+                .store(table_name, &bincode::serialize(&value).unwrap())?;
+
+            record
+                .fields
+                .insert(column_name, StoredValue::Overflow(first_page_index));
+        }
+    }
+
+    /// Reassembles the record, loading the values moved to overflow pages.
+    fn load_record(
+        &self,
+        record: &StoredRecord,
+        table_name: &str,
+    ) -> io::Result<dynamic_record::DynamicRecord> {
+        let mut fields = HashMap::with_capacity(record.fields.len());
+
+        for (column_name, value) in &record.fields {
+            let value = match value {
+                StoredValue::Int(integer) => dynamic_record::Value::Int(*integer),
+                StoredValue::Text(text) => dynamic_record::Value::Text(text.clone()),
+                StoredValue::Overflow(first_page_index) => {
+                    let payload = self.overflow_manager.load(table_name, *first_page_index)?;
+
+                    bincode::deserialize(&payload[..]).map_err(|_| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("Overflow value of '{}' is corrupted.", table_name),
+                        )
+                    })?
+                }
+            };
+
+            fields.insert(column_name.clone(), value);
+        }
+
+        Ok(dynamic_record::DynamicRecord::new(fields))
+    }
+
+    /// Frees the overflow pages of a record that is removed from its data page.
+    fn free_overflow_pages(&self, record: &StoredRecord, table_name: &str) -> io::Result<()> {
+        for value in record.fields.values() {
+            if let StoredValue::Overflow(first_page_index) = value {
+                self.overflow_manager.free(table_name, *first_page_index)?;
             }
+        }
+
+        Ok(())
+    }
+}
+
+fn encode_data_page(versions: &[Option<RowVersion<StoredRecord>>]) -> Vec<u8> {
+    DATA_PAGE.encode(&bincode::serialize(versions).unwrap())
+}
+
+fn decode_data_page(buffer: &[u8]) -> Result<Vec<Option<RowVersion<StoredRecord>>>, String> {
+    let payload = DATA_PAGE.decode(buffer)?;
+
+    bincode::deserialize(payload).map_err(|error| format!("its records are invalid ({})", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use crate::engine::binary_engine::BinaryEngine;
+    use crate::engine::{CreateTable, Delete, Insert, Select};
+    use crate::io_test_context::{FileTestContext, TestDatabase};
+
+    use super::DATA_PAGE_SIZE;
+
+    #[test]
+    fn test_can_store_and_read_a_record_larger_than_a_data_page() {
+        let context = FileTestContext::new();
+        let engine = BinaryEngine::new();
+
+        engine
+            .create_table(
+                context.table_name().to_string(),
+                vec![vec!["payload".to_string(), "VARCHAR".to_string()]],
+            )
+            .unwrap();
+
+        let payload = "a".repeat(DATA_PAGE_SIZE * 3);
+
+        engine
+            .insert(
+                context.table_name().to_string(),
+                vec!["payload".to_string()],
+                vec![vec![payload.clone()], vec!["small".to_string()]],
+            )
+            .unwrap();
+
+        let records = engine
+            .select(context.table_name().to_string(), vec![], vec![])
+            .unwrap()
+            .records
+            .unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert!(records
+            .first()
+            .unwrap()
+            .fields
+            .get("payload")
+            .unwrap()
+            .fullfills(&payload));
+
+        let data_page_size = fs::metadata(engine.file_paths.data_page(context.table_name(), 1))
+            .unwrap()
+            .len() as usize;
+        assert!(data_page_size <= DATA_PAGE_SIZE);
+    }
+
+    #[test]
+    fn test_frees_the_overflow_pages_of_collected_versions() {
+        let database = TestDatabase::new();
+        let engine = BinaryEngine::open(&database.options()).unwrap();
+        let table_name = "payloads".to_string();
+        let overflow_page = |page_index| engine.file_paths.overflow_page(&table_name, page_index);
+
+        engine
+            .create_table(
+                table_name.clone(),
+                vec![vec!["payload".to_string(), "VARCHAR".to_string()]],
+            )
+            .unwrap();
+
+        let insert = |payload: &str| {
+            engine
+                .insert(
+                    table_name.clone(),
+                    vec!["payload".to_string()],
+                    vec![vec![payload.to_string()]],
+                )
+                .unwrap()
         };
 
-        Ok(records)
+        insert(&"a".repeat(DATA_PAGE_SIZE * 2));
+        assert!(Path::new(&overflow_page(1)).exists());
+
+        engine.delete(table_name.clone(), vec![]).unwrap();
+        // no snapshot sees the deleted version anymore, so the insert collects it
+        insert("small");
+        assert!(!Path::new(&overflow_page(1)).exists());
+
+        let payload = "b".repeat(DATA_PAGE_SIZE * 2);
+        insert(&payload);
+        assert!(Path::new(&overflow_page(1)).exists());
+
+        let records = engine
+            .select(table_name.clone(), vec![], vec![])
+            .unwrap()
+            .records
+            .unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().any(|record| record
+            .fields
+            .get("payload")
+            .unwrap()
+            .fullfills(&payload)));
+    }
+
+    #[test]
+    fn test_starts_a_new_data_page_once_the_records_do_not_fit() {
+        let context = FileTestContext::new();
+        let engine = BinaryEngine::new();
+
+        engine
+            .create_table(
+                context.table_name().to_string(),
+                vec![vec!["payload".to_string(), "VARCHAR".to_string()]],
+            )
+            .unwrap();
+
+        let payload = "b".repeat(DATA_PAGE_SIZE / 5);
+
+        engine
+            .insert(
+                context.table_name().to_string(),
+                vec!["payload".to_string()],
+                vec![vec![payload.clone()]; 6],
+            )
+            .unwrap();
+
+        for data_page_index in 1..=2 {
+            let data_page_size = fs::metadata(
+                engine
+                    .file_paths
+                    .data_page(context.table_name(), data_page_index),
+            )
+            .unwrap()
+            .len() as usize;
+
+            assert!(data_page_size <= DATA_PAGE_SIZE);
+        }
+
+        let records = engine
+            .select(context.table_name().to_string(), vec![], vec![])
+            .unwrap()
+            .records
+            .unwrap();
+
+        assert_eq!(records.len(), 6);
+    }
+
+    #[test]
+    fn test_fails_to_read_a_corrupted_data_page() {
+        let context = FileTestContext::new();
+        let engine = BinaryEngine::new();
+
+        engine
+            .create_table(
+                context.table_name().to_string(),
+                vec![vec!["payload".to_string(), "VARCHAR".to_string()]],
+            )
+            .unwrap();
+
+        engine
+            .insert(
+                context.table_name().to_string(),
+                vec!["payload".to_string()],
+                vec![vec!["a".to_string()], vec!["b".to_string()]],
+            )
+            .unwrap();
+
+        let path = engine.file_paths.data_page(context.table_name(), 1);
+        let contents = fs::read(&path).unwrap();
+
+        let mut flipped = contents.clone();
+        *flipped.last_mut().unwrap() ^= 1;
+        fs::write(&path, flipped).unwrap();
+
+        assert_eq!(
+            engine
+                .select(context.table_name().to_string(), vec![], vec![])
+                .unwrap_err(),
+            format!(
+                "Data page 1 of table '{}' is corrupted: its checksum does not match.",
+                context.table_name()
+            )
+        );
+
+        fs::write(&path, &contents[..4]).unwrap();
+
+        assert!(engine
+            .select(context.table_name().to_string(), vec![], vec![])
+            .unwrap_err()
+            .ends_with("is corrupted: it is truncated to 4 bytes."));
     }
 }
@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::fs::{self, TryLockError};
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::engine::OpenOptions;
+
+// how often a busy lock is retried until the busy timeout runs out
+const BUSY_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+struct DatabaseLock {
+    file: fs::File,
+    exclusive: bool,
+}
+
+// the lock file of every database opened by this process, keyed by its path. The lock is
+// advisory and held until the process exits, since all engines of the process share it.
+static DATABASE_LOCKS: Mutex<BTreeMap<String, DatabaseLock>> = Mutex::new(BTreeMap::new());
+
+/// Locks the database against other processes and opens it. Writers hold the lock
+/// exclusively and readers share it, and both wait up to the busy timeout for it.
+/// The database is opened with the exclusive lock held if the process is allowed to
+/// recover it, which a reader is if no other process has the database open.
+pub fn open_locked<T>(
+    path: &str,
+    options: &OpenOptions,
+    open: impl FnOnce(bool) -> io::Result<T>,
+) -> io::Result<T> {
+    let mut database_locks = DATABASE_LOCKS
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+
+    if let Some(lock) = database_locks.get_mut(path) {
+        if !options.read_only && !lock.exclusive {
+            lock.file.unlock()?;
+
+            if let Err(error) = wait_for(|| lock.file.try_lock(), options.busy_timeout) {
+                lock.file.lock_shared()?;
+
+                return Err(error);
+            }
+
+            lock.exclusive = true;
+        }
+
+        return open(lock.exclusive);
+    }
+
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+
+    let (opened, exclusive) = if !options.read_only {
+        wait_for(|| file.try_lock(), options.busy_timeout)?;
+
+        (open(true)?, true)
+    } else if file.try_lock().is_ok() {
+        let opened = open(true);
+
+        file.unlock()?;
+        wait_for(|| file.try_lock_shared(), options.busy_timeout)?;
+
+        (opened?, false)
+    } else {
+        wait_for(|| file.try_lock_shared(), options.busy_timeout)?;
+
+        (open(false)?, false)
+    };
+
+    database_locks.insert(path.to_string(), DatabaseLock { file, exclusive });
+
+    Ok(opened)
+}
+
+fn wait_for(
+    mut try_lock: impl FnMut() -> Result<(), TryLockError>,
+    busy_timeout: Duration,
+) -> io::Result<()> {
+    let deadline = Instant::now() + busy_timeout;
+
+    loop {
+        match try_lock() {
+            Ok(()) => return Ok(()),
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                thread::sleep(BUSY_RETRY_INTERVAL)
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    "The database is locked by another process.",
+                ))
+            }
+            Err(TryLockError::Error(error)) => return Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{distributions::Alphanumeric, Rng};
+
+    use super::*;
+
+    fn lock_path() -> String {
+        let file_name: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(10)
+            .map(char::from)
+            .collect();
+
+        std::env::temp_dir()
+            .join(format!("{}.lock", file_name))
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    fn options(read_only: bool, busy_timeout: Duration) -> OpenOptions {
+        OpenOptions {
+            read_only,
+            busy_timeout,
+            ..OpenOptions::default()
+        }
+    }
+
+    // another process, as far as locks are concerned
+    fn open_elsewhere(path: &str) -> fs::File {
+        fs::File::open(path).unwrap()
+    }
+
+    #[test]
+    fn test_a_writer_excludes_other_processes() {
+        let path = lock_path();
+
+        assert!(open_locked(&path, &options(false, Duration::ZERO), Ok).unwrap());
+
+        assert!(matches!(
+            open_elsewhere(&path).try_lock_shared(),
+            Err(TryLockError::WouldBlock)
+        ));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_readers_share_the_lock_and_wait_for_writers() {
+        let path = lock_path();
+        fs::write(&path, []).unwrap();
+
+        let reader = open_elsewhere(&path);
+        reader.try_lock_shared().unwrap();
+
+        // another reader has the database open, so it is not recovered
+        assert!(!open_locked(&path, &options(true, Duration::ZERO), Ok).unwrap());
+        assert_eq!(
+            open_locked(&path, &options(false, Duration::ZERO), Ok)
+                .unwrap_err()
+                .to_string(),
+            "The database is locked by another process."
+        );
+
+        let releasing_reader = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            drop(reader);
+        });
+
+        assert!(open_locked(&path, &options(false, Duration::from_secs(5)), Ok).unwrap());
+
+        releasing_reader.join().unwrap();
+        fs::remove_file(path).unwrap();
+    }
+}
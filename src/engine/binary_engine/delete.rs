@@ -0,0 +1,213 @@
+use std::io;
+
+use super::record_id::RecordId;
+use crate::dynamic_record::DynamicRecord;
+use crate::engine::delete::Delete;
+use crate::metadata;
+use crate::sql_parser::query::Condition;
+
+impl Delete for super::BinaryEngine {
+    fn delete(&self, table_name: String, where_clauses: Vec<Condition>) -> super::EngineResult {
+        let _guard = self.lock_for_write(&table_name)?;
+
+        let Ok(mut table) = self.table_manager.load_meta_data(&table_name) else {
+            return Err(format!("Table '{}' does not exist.", table_name));
+        };
+
+        self.collect_garbage(&mut table)
+            .map_err(|error| error.to_string())?;
+
+        let deleted = self.delete_matching(&mut table, &where_clauses)?.len();
+
+        if deleted > 0 {
+            table.last_writer = self
+                .pager
+                .transaction_id()
+                .map_err(|error| error.to_string())?;
+
+            self.table_manager
+                .store_meta_data(&table)
+                .map_err(|error| error.to_string())?;
+        }
+
+        Ok(super::EngineResponse {
+            table: None,
+            records: None,
+            message: Some(format!("Deleted {} records.", deleted)),
+        })
+    }
+}
+
+impl super::BinaryEngine {
+    /// Marks the rows matching the where clauses as deleted, finding them through an index if
+    /// one fits, and returns their record ids and records.
+    pub(super) fn delete_matching(
+        &self,
+        table: &mut metadata::Table,
+        where_clauses: &[Condition],
+    ) -> Result<Vec<(RecordId, DynamicRecord)>, String> {
+        let record_ids = match table.index_lookup(where_clauses) {
+            Some((index, key_range)) => Some(
+                self.index_manager
+                    .scan(&table.name, index, &key_range)
+                    .map_err(|error| error.to_string())?,
+            ),
+            None => None,
+        };
+
+        let deleted = self
+            .data_manager
+            .delete_records(&table.name, where_clauses, record_ids)
+            .map_err(|error| error.to_string())?;
+
+        table.dead_versions += deleted.len();
+
+        Ok(deleted)
+    }
+
+    /// Removes the versions no snapshot can see anymore from the data pages and indices of the table.
+    pub(super) fn collect_garbage(&self, table: &mut metadata::Table) -> io::Result<()> {
+        if table.dead_versions == 0 {
+            return Ok(());
+        }
+
+        let collected = self
+            .data_manager
+            .collect_garbage(&table.name, self.pager.horizon())?;
+
+        for (record_id, record) in &collected {
+            for index in &table.indices {
+                if let Some(key) = index.key_of(record) {
+                    self.index_manager
+                        .remove(&table.name, index, key, *record_id)?;
+                }
+            }
+        }
+
+        table.dead_versions -= collected.len();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{binary_engine::BinaryEngine, Engine, EngineResult};
+    use crate::io_test_context::{FileTestContext, TestDatabase};
+    use crate::sql_parser::SqlParser;
+
+    fn execute(engine: &BinaryEngine, query: &str) -> EngineResult {
+        engine.execute(SqlParser().parse_query(query.to_string()).unwrap())
+    }
+
+    fn names(engine: &BinaryEngine, query: &str) -> Vec<String> {
+        let mut names: Vec<String> = execute(engine, query)
+            .unwrap()
+            .records
+            .unwrap()
+            .iter()
+            .map(|record| record.fields.get("name").unwrap().to_string())
+            .collect();
+
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_deletes_the_rows_matching_the_where_clauses() {
+        let context = FileTestContext::new();
+        let table_name = context.table_name();
+        let engine = BinaryEngine::new();
+
+        for query in [
+            format!("CREATE TABLE {} (name VARCHAR, age INTEGER);", table_name),
+            format!("CREATE INDEX age_index ON {} (age);", table_name),
+            format!(
+                "INSERT INTO {} (name, age) VALUES ('a', 20), ('b', 30), ('c', 40);",
+                table_name
+            ),
+            format!("DELETE FROM {} WHERE age >= 30 AND name = 'b';", table_name),
+        ] {
+            execute(&engine, &query).unwrap();
+        }
+
+        assert_eq!(
+            names(&engine, &format!("SELECT * FROM {};", table_name)),
+            vec!["a", "c"]
+        );
+        assert_eq!(
+            names(
+                &engine,
+                &format!("SELECT * FROM {} WHERE age = 30;", table_name)
+            ),
+            Vec::<String>::new()
+        );
+
+        execute(&engine, &format!("DELETE FROM {};", table_name)).unwrap();
+
+        assert!(names(&engine, &format!("SELECT * FROM {};", table_name)).is_empty());
+    }
+
+    #[test]
+    fn test_collects_deleted_versions_once_no_snapshot_sees_them() {
+        // a database of its own, so that no transaction of a concurrent test holds the horizon back
+        let database = TestDatabase::new();
+        let table_name = "people";
+        let engine = BinaryEngine::open(&database.options()).unwrap();
+        let reader = BinaryEngine::open(&database.options()).unwrap();
+        let dead_versions = || {
+            engine
+                .table_manager
+                .load_meta_data(table_name)
+                .unwrap()
+                .dead_versions
+        };
+
+        for query in [
+            format!("CREATE TABLE {} (name VARCHAR);", table_name),
+            format!("CREATE UNIQUE INDEX name_index ON {} (name);", table_name),
+            format!("INSERT INTO {} (name) VALUES ('a'), ('b');", table_name),
+        ] {
+            execute(&engine, &query).unwrap();
+        }
+
+        execute(&reader, "BEGIN;").unwrap();
+        execute(&reader, "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ;").unwrap();
+        execute(&reader, &format!("SELECT * FROM {};", table_name)).unwrap();
+
+        execute(
+            &engine,
+            &format!("DELETE FROM {} WHERE name = 'a';", table_name),
+        )
+        .unwrap();
+        // the deleted key is free again, although its version is still stored
+        execute(
+            &engine,
+            &format!("INSERT INTO {} (name) VALUES ('a');", table_name),
+        )
+        .unwrap();
+
+        assert_eq!(dead_versions(), 1);
+        assert_eq!(
+            names(&reader, &format!("SELECT * FROM {};", table_name)),
+            vec!["a", "b"]
+        );
+
+        execute(&reader, "COMMIT;").unwrap();
+
+        execute(
+            &engine,
+            &format!("INSERT INTO {} (name) VALUES ('c');", table_name),
+        )
+        .unwrap();
+
+        assert_eq!(dead_versions(), 0);
+        assert_eq!(
+            names(
+                &engine,
+                &format!("SELECT name FROM {} WHERE name = 'a';", table_name)
+            ),
+            vec!["a"]
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::{hash_map, HashMap};
+use std::io;
+use std::mem;
+
+use serde::{Deserialize, Serialize};
+
+use super::spill::{Merge, Run, WorkMemory};
+use super::Operator;
+use crate::dynamic_record::{DynamicRecord, Value};
+use crate::sql_parser::query::{Aggregate as AggregateExpression, AggregateFunction};
+
+type Group = (Vec<Option<Value>>, Vec<Accumulator>);
+
+/// Groups the rows of its input by the values of the GROUP BY columns in a hash table and
+/// computes the aggregates of every group, yielding the groups in no particular order.
+/// Without GROUP BY columns, all rows form a single group, which also yields a row if there
+/// are none. Once the groups outgrow the work memory, they are sorted and written to
+/// temporary files in runs, which are merged in the end, combining the partial aggregates
+/// of groups found in more than one run.
+pub struct Aggregate<'a> {
+    input: Box<dyn Operator + 'a>,
+    group_by: Vec<String>,
+    aggregates: Vec<AggregateExpression>,
+    work_memory: WorkMemory,
+    groups: Option<Groups<'a>>,
+}
+
+enum Groups<'a> {
+    InMemory(hash_map::IntoIter<Vec<Option<Value>>, Vec<Accumulator>>),
+    Merged {
+        merge: Merge<'a, Group>,
+        next: Option<Group>,
+    },
+}
+
+impl<'a> Aggregate<'a> {
+    pub fn new(
+        input: Box<dyn Operator + 'a>,
+        group_by: Vec<String>,
+        aggregates: Vec<AggregateExpression>,
+        work_memory: WorkMemory,
+    ) -> Self {
+        Aggregate {
+            input,
+            group_by,
+            aggregates,
+            work_memory,
+            groups: None,
+        }
+    }
+
+    fn accumulate(&mut self) -> io::Result<Groups<'a>> {
+        let mut groups = HashMap::new();
+        let mut size = 0;
+        let mut runs = vec![];
+
+        if self.group_by.is_empty() {
+            groups.insert(vec![], self.accumulators());
+        }
+
+        while let Some(record) = self.input.next()? {
+            let key: Vec<Option<Value>> = self
+                .group_by
+                .iter()
+                .map(|column_name| record.fields.get(column_name).cloned())
+                .collect();
+
+            let accumulators = match groups.entry(key) {
+                hash_map::Entry::Occupied(entry) => entry.into_mut(),
+                hash_map::Entry::Vacant(entry) => {
+                    let accumulators = self.accumulators();
+                    size += WorkMemory::size_of(entry.key()) + WorkMemory::size_of(&accumulators);
+
+                    entry.insert(accumulators)
+                }
+            };
+
+            for (accumulator, aggregate) in accumulators.iter_mut().zip(&self.aggregates) {
+                let value = match &aggregate.column_name {
+                    Some(column_name) => record.fields.get(column_name),
+                    None => Some(&Value::Int(0)),
+                };
+
+                if let Some(value) = value {
+                    accumulator.add(value)?;
+                }
+            }
+
+            if size > self.work_memory.limit {
+                runs.push(self.write_run(mem::take(&mut groups))?);
+                size = 0;
+            }
+        }
+
+        if runs.is_empty() {
+            return Ok(Groups::InMemory(groups.into_iter()));
+        }
+
+        if !groups.is_empty() {
+            runs.push(self.write_run(groups)?);
+        }
+
+        Ok(Groups::Merged {
+            merge: Merge::new(
+                &self.work_memory.directory,
+                runs,
+                |(left, _): &Group, (right, _): &Group| left.cmp(right),
+            )?,
+            next: None,
+        })
+    }
+
+    /// Writes the groups sorted by their keys, so that the runs can be merged.
+    fn write_run(
+        &self,
+        groups: HashMap<Vec<Option<Value>>, Vec<Accumulator>>,
+    ) -> io::Result<Run<Group>> {
+        let mut groups: Vec<Group> = groups.into_iter().collect();
+        groups.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+        Run::write(&self.work_memory.directory, groups)
+    }
+
+    fn accumulators(&self) -> Vec<Accumulator> {
+        self.aggregates
+            .iter()
+            .map(|aggregate| Accumulator::new(aggregate.function))
+            .collect()
+    }
+}
+
+impl Operator for Aggregate<'_> {
+    fn next(&mut self) -> io::Result<Option<DynamicRecord>> {
+        if self.groups.is_none() {
+            self.groups = Some(self.accumulate()?);
+        }
+
+        let Some((key, accumulators)) = self.groups.as_mut().unwrap().next()? else {
+            return Ok(None);
+        };
+
+        let mut fields: HashMap<String, Value> = self
+            .group_by
+            .iter()
+            .cloned()
+            .zip(key)
+            .filter_map(|(column_name, value)| Some((column_name, value?)))
+            .collect();
+
+        for (accumulator, aggregate) in accumulators.into_iter().zip(&self.aggregates) {
+            if let Some(value) = accumulator.finish() {
+                fields.insert(aggregate.to_string(), value);
+            }
+        }
+
+        Ok(Some(DynamicRecord::new(fields)))
+    }
+}
+
+impl Groups<'_> {
+    fn next(&mut self) -> io::Result<Option<Group>> {
+        let (merge, next) = match self {
+            Groups::InMemory(groups) => return Ok(groups.next()),
+            Groups::Merged { merge, next } => (merge, next),
+        };
+
+        let Some((key, mut accumulators)) = (match next.take() {
+            Some(group) => Some(group),
+            None => merge.next()?,
+        }) else {
+            return Ok(None);
+        };
+
+        loop {
+            match merge.next()? {
+                Some((other_key, other_accumulators)) if other_key == key => {
+                    for (accumulator, other) in accumulators.iter_mut().zip(other_accumulators) {
+                        accumulator.merge(other)?;
+                    }
+                }
+                group => {
+                    *next = group;
+                    return Ok(Some((key, accumulators)));
+                }
+            }
+        }
+    }
+}
+
+/// The running result of an aggregate over the values of a group. Rows without a value are
+/// not counted, and SUM only adds up integers, failing once the sum leaves the range of i64.
+#[derive(Serialize, Deserialize)]
+enum Accumulator {
+    Count(i64),
+    Sum(Option<i64>),
+    Min(Option<Value>),
+    Max(Option<Value>),
+}
+
+impl Accumulator {
+    fn new(function: AggregateFunction) -> Self {
+        match function {
+            AggregateFunction::Count => Accumulator::Count(0),
+            AggregateFunction::Sum => Accumulator::Sum(None),
+            AggregateFunction::Min => Accumulator::Min(None),
+            AggregateFunction::Max => Accumulator::Max(None),
+        }
+    }
+
+    fn add(&mut self, value: &Value) -> io::Result<()> {
+        match self {
+            Accumulator::Count(count) => *count += 1,
+            Accumulator::Sum(sum) => {
+                if let Value::Int(value) = value {
+                    *sum = Some(checked_sum(*sum, *value)?);
+                }
+            }
+            Accumulator::Min(min) => {
+                if min.as_ref().is_none_or(|min| value < min) {
+                    *min = Some(value.clone());
+                }
+            }
+            Accumulator::Max(max) => {
+                if max.as_ref().is_none_or(|max| value > max) {
+                    *max = Some(value.clone());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds the values another accumulator of the same aggregate has seen.
+    fn merge(&mut self, other: Accumulator) -> io::Result<()> {
+        match (self, other) {
+            (Accumulator::Count(count), Accumulator::Count(other)) => *count += other,
+            (Accumulator::Sum(sum), Accumulator::Sum(Some(other))) => {
+                *sum = Some(checked_sum(*sum, other)?);
+            }
+            (Accumulator::Min(min), Accumulator::Min(Some(other)))
+                if min.as_ref().is_none_or(|min| other < *min) =>
+            {
+                *min = Some(other);
+            }
+            (Accumulator::Max(max), Accumulator::Max(Some(other)))
+                if max.as_ref().is_none_or(|max| other > *max) =>
+            {
+                *max = Some(other);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn finish(self) -> Option<Value> {
+        match self {
+            Accumulator::Count(count) => Some(Value::Int(count)),
+            Accumulator::Sum(sum) => sum.map(Value::Int),
+            Accumulator::Min(value) | Accumulator::Max(value) => value,
+        }
+    }
+}
+
+fn checked_sum(sum: Option<i64>, value: i64) -> io::Result<i64> {
+    sum.unwrap_or(0)
+        .checked_add(value)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Integer overflow in SUM."))
+}
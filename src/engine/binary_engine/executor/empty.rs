@@ -0,0 +1,13 @@
+use std::io;
+
+use super::Operator;
+use crate::dynamic_record::DynamicRecord;
+
+/// Produces no rows, for plans that are known not to match any.
+pub struct Empty;
+
+impl Operator for Empty {
+    fn next(&mut self) -> io::Result<Option<DynamicRecord>> {
+        Ok(None)
+    }
+}
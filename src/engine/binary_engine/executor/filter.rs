@@ -0,0 +1,32 @@
+use std::io;
+
+use super::Operator;
+use crate::dynamic_record::DynamicRecord;
+use crate::sql_parser::query::Condition;
+
+/// Only lets the rows matching all where clauses through.
+pub struct Filter<'a> {
+    input: Box<dyn Operator + 'a>,
+    where_clauses: Vec<Condition>,
+}
+
+impl<'a> Filter<'a> {
+    pub fn new(input: Box<dyn Operator + 'a>, where_clauses: Vec<Condition>) -> Self {
+        Filter {
+            input,
+            where_clauses,
+        }
+    }
+}
+
+impl Operator for Filter<'_> {
+    fn next(&mut self) -> io::Result<Option<DynamicRecord>> {
+        while let Some(record) = self.input.next()? {
+            if record.entry_should_be_included(&self.where_clauses) {
+                return Ok(Some(record));
+            }
+        }
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::vec;
+
+use super::join::merge;
+use super::spill::{Run, RunWriter, WorkMemory};
+use super::Operator;
+use crate::dynamic_record::{DynamicRecord, Value};
+
+/// How many partitions the rows are split into once the build input outgrows the work memory.
+const PARTITION_COUNT: u64 = 16;
+
+/// Joins the rows of two inputs with the same value in their join columns by keeping the rows
+/// of the build input in a hash table and looking up every row of the probe input in it. Once
+/// the build input outgrows the work memory, the rows of both inputs are split into partitions
+/// by the hash of their join value and written to temporary files, to be joined partition by
+/// partition. A partition that still does not fit is loaded as a whole regardless.
+pub struct HashJoin<'a> {
+    probe: Box<dyn Operator + 'a>,
+    build: Box<dyn Operator + 'a>,
+    probe_column: String,
+    build_column: String,
+    work_memory: WorkMemory,
+    state: Option<State>,
+}
+
+struct State {
+    table: HashMap<Value, Vec<DynamicRecord>>,
+    /// Whether the rows were split into partitions, rather than probed straight from the probe input.
+    partitioned: bool,
+    probe_run: Option<Run<DynamicRecord>>,
+    partitions: vec::IntoIter<(Run<DynamicRecord>, Run<DynamicRecord>)>,
+    /// The probe row being joined, the value it is looked up with and the next match to join it with.
+    current: Option<(DynamicRecord, Value, usize)>,
+}
+
+impl<'a> HashJoin<'a> {
+    pub fn new(
+        probe: Box<dyn Operator + 'a>,
+        build: Box<dyn Operator + 'a>,
+        probe_column: String,
+        build_column: String,
+        work_memory: WorkMemory,
+    ) -> Self {
+        HashJoin {
+            probe,
+            build,
+            probe_column,
+            build_column,
+            work_memory,
+            state: None,
+        }
+    }
+
+    fn build(&mut self) -> io::Result<State> {
+        let mut table: HashMap<Value, Vec<DynamicRecord>> = HashMap::new();
+        let mut size = 0;
+
+        while let Some(row) = self.build.next()? {
+            let Some(key) = row.fields.get(&self.build_column).cloned() else {
+                continue;
+            };
+
+            size += WorkMemory::size_of(&row);
+            table.entry(key).or_default().push(row);
+
+            if size > self.work_memory.limit {
+                return self.partition(table);
+            }
+        }
+
+        Ok(State {
+            table,
+            partitioned: false,
+            probe_run: None,
+            partitions: vec![].into_iter(),
+            current: None,
+        })
+    }
+
+    fn partition(&mut self, table: HashMap<Value, Vec<DynamicRecord>>) -> io::Result<State> {
+        let writers = || {
+            (0..PARTITION_COUNT)
+                .map(|_| RunWriter::create(&self.work_memory.directory))
+                .collect::<io::Result<Vec<RunWriter<DynamicRecord>>>>()
+        };
+
+        let (mut build_writers, mut probe_writers) = (writers()?, writers()?);
+
+        for (key, rows) in table {
+            for row in rows {
+                build_writers[partition_of(&key)].push(&row)?;
+            }
+        }
+
+        for (input, column, writers) in [
+            (&mut self.build, &self.build_column, &mut build_writers),
+            (&mut self.probe, &self.probe_column, &mut probe_writers),
+        ] {
+            while let Some(row) = input.next()? {
+                if let Some(key) = row.fields.get(column) {
+                    writers[partition_of(key)].push(&row)?;
+                }
+            }
+        }
+
+        let partitions = build_writers
+            .into_iter()
+            .zip(probe_writers)
+            .map(|(build, probe)| Ok((build.finish()?, probe.finish()?)))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(State {
+            table: HashMap::new(),
+            partitioned: true,
+            probe_run: None,
+            partitions: partitions.into_iter(),
+            current: None,
+        })
+    }
+}
+
+impl Operator for HashJoin<'_> {
+    fn next(&mut self) -> io::Result<Option<DynamicRecord>> {
+        if self.state.is_none() {
+            self.state = Some(self.build()?);
+        }
+
+        let state = self.state.as_mut().unwrap();
+
+        loop {
+            if let Some((probe_row, key, position)) = &mut state.current {
+                if let Some(build_row) = state.table[key].get(*position) {
+                    *position += 1;
+
+                    return Ok(Some(merge(probe_row, build_row)));
+                }
+
+                state.current = None;
+            }
+
+            let probe_row = match (&mut state.probe_run, state.partitioned) {
+                (Some(probe_run), _) => probe_run.next()?,
+                // no partition is loaded yet
+                (None, true) => None,
+                (None, false) => self.probe.next()?,
+            };
+
+            let Some(probe_row) = probe_row else {
+                let Some((mut build_run, probe_run)) = state.partitions.next() else {
+                    return Ok(None);
+                };
+
+                state.table = HashMap::new();
+
+                while let Some(row) = build_run.next()? {
+                    let key = row.fields[&self.build_column].clone();
+                    state.table.entry(key).or_default().push(row);
+                }
+
+                state.probe_run = Some(probe_run);
+                continue;
+            };
+
+            if let Some(key) = probe_row.fields.get(&self.probe_column) {
+                if state.table.contains_key(key) {
+                    let key = key.clone();
+                    state.current = Some((probe_row, key, 0));
+                }
+            }
+        }
+    }
+}
+
+fn partition_of(key: &Value) -> usize {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+
+    (hasher.finish() % PARTITION_COUNT) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::binary_engine::executor::Empty;
+
+    /// Produces the given rows.
+    struct Rows(vec::IntoIter<DynamicRecord>);
+
+    impl Operator for Rows {
+        fn next(&mut self) -> io::Result<Option<DynamicRecord>> {
+            Ok(self.0.next())
+        }
+    }
+
+    fn rows(column_name: &str, values: impl Iterator<Item = i64>) -> Box<dyn Operator> {
+        Box::new(Rows(
+            values
+                .enumerate()
+                .map(|(position, value)| {
+                    DynamicRecord::new(HashMap::from([
+                        (column_name.to_string(), Value::Int(value)),
+                        (format!("{}_position", column_name), Value::Int(position as i64)),
+                    ]))
+                })
+                .collect::<Vec<_>>()
+                .into_iter(),
+        ))
+    }
+
+    fn join(limit: usize) -> Vec<(i64, i64)> {
+        let mut join = HashJoin::new(
+            rows("probe", (0..100).map(|value| value % 30)),
+            rows("build", (0..60).map(|value| value % 20)),
+            String::from("probe"),
+            String::from("build"),
+            WorkMemory {
+                limit,
+                directory: std::env::temp_dir()
+                    .join("r_sql_hash_join")
+                    .to_string_lossy()
+                    .to_string(),
+            },
+        );
+
+        let mut joined = vec![];
+
+        while let Some(row) = join.next().unwrap() {
+            let position = |column_name: &str| match row.fields[column_name] {
+                Value::Int(position) => position,
+                _ => unreachable!(),
+            };
+
+            joined.push((position("probe_position"), position("build_position")));
+        }
+
+        joined.sort();
+        joined
+    }
+
+    #[test]
+    fn test_joins_the_same_rows_in_memory_and_in_partitions() {
+        let in_memory = join(usize::MAX);
+
+        // every probe value below 20 matches three build rows
+        assert_eq!(in_memory.len(), (0..100).filter(|value| value % 30 < 20).count() * 3);
+        assert_eq!(join(256), in_memory);
+
+        let mut empty = HashJoin::new(
+            Box::new(Empty),
+            rows("build", 0..3),
+            String::from("probe"),
+            String::from("build"),
+            WorkMemory {
+                limit: 0,
+                directory: std::env::temp_dir().to_string_lossy().to_string(),
+            },
+        );
+        assert!(empty.next().unwrap().is_none());
+    }
+}
@@ -0,0 +1,71 @@
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use super::Operator;
+use crate::dynamic_record::{DynamicRecord, Value};
+use crate::engine::binary_engine::pager::Pager;
+
+/// What an operator actually did while it ran, including the work of its inputs.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct OperatorStatistics {
+    pub rows: u64,
+    /// How often the operator was started, zero if it never ran. Rescanning starts it again.
+    pub loops: u64,
+    pub time: Duration,
+    pub disk_reads: u64,
+    pub pending_write_hits: u64,
+}
+
+/// Passes the rows of an operator through, recording its statistics along the way.
+pub struct Instrumented<'a> {
+    input: Box<dyn Operator + 'a>,
+    pager: &'a Pager,
+    statistics: Rc<RefCell<OperatorStatistics>>,
+}
+
+impl<'a> Instrumented<'a> {
+    pub fn new(
+        input: Box<dyn Operator + 'a>,
+        pager: &'a Pager,
+        statistics: Rc<RefCell<OperatorStatistics>>,
+    ) -> Self {
+        Instrumented {
+            input,
+            pager,
+            statistics,
+        }
+    }
+}
+
+impl Operator for Instrumented<'_> {
+    fn next(&mut self) -> io::Result<Option<DynamicRecord>> {
+        let page_accesses = self.pager.page_accesses();
+        let start = Instant::now();
+
+        let record = self.input.next();
+
+        let elapsed = start.elapsed();
+        let since = self.pager.page_accesses();
+        let mut statistics = self.statistics.borrow_mut();
+
+        statistics.loops = statistics.loops.max(1);
+        statistics.time += elapsed;
+        statistics.disk_reads += since.disk_reads - page_accesses.disk_reads;
+        statistics.pending_write_hits +=
+            since.pending_write_hits - page_accesses.pending_write_hits;
+
+        if let Ok(Some(_)) = record {
+            statistics.rows += 1;
+        }
+
+        record
+    }
+
+    fn rescan(&mut self, key: &Value) -> io::Result<()> {
+        self.statistics.borrow_mut().loops += 1;
+
+        self.input.rescan(key)
+    }
+}
@@ -0,0 +1,81 @@
+use std::io;
+
+use super::Operator;
+use crate::dynamic_record::DynamicRecord;
+
+/// Joins every row of the outer input with the rows of the inner input that have the same
+/// value in the join column. The inner input is rescanned with that value for every outer row,
+/// so it only reads the rows that join.
+pub struct NestedLoopJoin<'a> {
+    outer: Box<dyn Operator + 'a>,
+    inner: Box<dyn Operator + 'a>,
+    outer_column: String,
+    inner_column: String,
+    outer_row: Option<DynamicRecord>,
+}
+
+impl<'a> NestedLoopJoin<'a> {
+    pub fn new(
+        outer: Box<dyn Operator + 'a>,
+        inner: Box<dyn Operator + 'a>,
+        outer_column: String,
+        inner_column: String,
+    ) -> Self {
+        NestedLoopJoin {
+            outer,
+            inner,
+            outer_column,
+            inner_column,
+            outer_row: None,
+        }
+    }
+}
+
+impl Operator for NestedLoopJoin<'_> {
+    fn next(&mut self) -> io::Result<Option<DynamicRecord>> {
+        loop {
+            if let Some(outer_row) = &self.outer_row {
+                while let Some(inner_row) = self.inner.next()? {
+                    if joins(outer_row, &self.outer_column, &inner_row, &self.inner_column) {
+                        return Ok(Some(merge(outer_row, &inner_row)));
+                    }
+                }
+            }
+
+            let Some(outer_row) = self.outer.next()? else {
+                return Ok(None);
+            };
+
+            self.outer_row = match outer_row.fields.get(&self.outer_column) {
+                Some(key) => {
+                    self.inner.rescan(key)?;
+                    Some(outer_row)
+                }
+                None => None,
+            };
+        }
+    }
+}
+
+/// Whether both rows have the same value in their join columns. Rows without a value never join.
+pub fn joins(
+    outer_row: &DynamicRecord,
+    outer_column: &str,
+    inner_row: &DynamicRecord,
+    inner_column: &str,
+) -> bool {
+    match (
+        outer_row.fields.get(outer_column),
+        inner_row.fields.get(inner_column),
+    ) {
+        (Some(outer_value), Some(inner_value)) => outer_value == inner_value,
+        _ => false,
+    }
+}
+
+pub fn merge(outer_row: &DynamicRecord, inner_row: &DynamicRecord) -> DynamicRecord {
+    let mut fields = outer_row.fields.clone();
+    fields.extend(inner_row.fields.clone());
+
+    DynamicRecord::new(fields)
+}
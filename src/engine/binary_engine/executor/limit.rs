@@ -0,0 +1,46 @@
+use std::io;
+
+use super::Operator;
+use crate::dynamic_record::DynamicRecord;
+
+/// Skips the first rows up to the offset and stops after the limit,
+/// without pulling any more rows from its input.
+pub struct Limit<'a> {
+    input: Box<dyn Operator + 'a>,
+    limit: Option<usize>,
+    offset: usize,
+}
+
+impl<'a> Limit<'a> {
+    pub fn new(input: Box<dyn Operator + 'a>, limit: Option<usize>, offset: usize) -> Self {
+        Limit {
+            input,
+            limit,
+            offset,
+        }
+    }
+}
+
+impl Operator for Limit<'_> {
+    fn next(&mut self) -> io::Result<Option<DynamicRecord>> {
+        if self.limit == Some(0) {
+            return Ok(None);
+        }
+
+        while self.offset > 0 {
+            if self.input.next()?.is_none() {
+                return Ok(None);
+            }
+
+            self.offset -= 1;
+        }
+
+        let record = self.input.next()?;
+
+        if record.is_some() {
+            self.limit = self.limit.map(|limit| limit - 1);
+        }
+
+        Ok(record)
+    }
+}
@@ -0,0 +1,99 @@
+use std::io;
+
+use super::join::merge;
+use super::Operator;
+use crate::dynamic_record::{DynamicRecord, Value};
+
+/// Joins two inputs sorted in ascending order of their join columns by reading both side by side.
+/// The right rows sharing a value are kept, to be joined with every left row with that value.
+pub struct MergeJoin<'a> {
+    left: Box<dyn Operator + 'a>,
+    right: Box<dyn Operator + 'a>,
+    left_column: String,
+    right_column: String,
+    left_row: Option<DynamicRecord>,
+    /// The right rows with the value of the last left row, and the next one to join.
+    group: Vec<DynamicRecord>,
+    group_key: Option<Value>,
+    position: usize,
+    /// The first right row with a larger value than the group.
+    next_right_row: Option<DynamicRecord>,
+}
+
+impl<'a> MergeJoin<'a> {
+    pub fn new(
+        left: Box<dyn Operator + 'a>,
+        right: Box<dyn Operator + 'a>,
+        left_column: String,
+        right_column: String,
+    ) -> Self {
+        MergeJoin {
+            left,
+            right,
+            left_column,
+            right_column,
+            left_row: None,
+            group: vec![],
+            group_key: None,
+            position: 0,
+            next_right_row: None,
+        }
+    }
+
+    /// Skips the right rows with smaller values and collects those with the key.
+    fn fill_group(&mut self, key: Value) -> io::Result<()> {
+        self.group.clear();
+
+        loop {
+            let right_row = match self.next_right_row.take() {
+                Some(right_row) => right_row,
+                None => match self.right.next()? {
+                    Some(right_row) => right_row,
+                    None => break,
+                },
+            };
+
+            match right_row.fields.get(&self.right_column) {
+                Some(value) if *value == key => self.group.push(right_row),
+                Some(value) if *value > key => {
+                    self.next_right_row = Some(right_row);
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        self.group_key = Some(key);
+
+        Ok(())
+    }
+}
+
+impl Operator for MergeJoin<'_> {
+    fn next(&mut self) -> io::Result<Option<DynamicRecord>> {
+        loop {
+            if let Some(left_row) = &self.left_row {
+                if let Some(right_row) = self.group.get(self.position) {
+                    self.position += 1;
+
+                    return Ok(Some(merge(left_row, right_row)));
+                }
+            }
+
+            let Some(left_row) = self.left.next()? else {
+                return Ok(None);
+            };
+
+            let key = left_row.fields.get(&self.left_column).cloned();
+            self.left_row = Some(left_row);
+            self.position = 0;
+
+            match key {
+                // rows without a value never join
+                None => self.position = self.group.len(),
+                Some(key) if self.group_key.as_ref() == Some(&key) => {}
+                Some(key) => self.fill_group(key)?,
+            }
+        }
+    }
+}
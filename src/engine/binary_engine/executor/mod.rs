@@ -0,0 +1,55 @@
+mod aggregate;
+mod empty;
+mod filter;
+mod hash_join;
+mod instrument;
+mod join;
+mod limit;
+mod merge_join;
+mod project;
+mod scan;
+mod sort;
+mod spill;
+
+use std::io;
+
+use crate::dynamic_record::{DynamicRecord, Value};
+
+pub use self::aggregate::Aggregate;
+pub use self::empty::Empty;
+pub use self::filter::Filter;
+pub use self::hash_join::HashJoin;
+pub use self::instrument::{Instrumented, OperatorStatistics};
+pub use self::join::NestedLoopJoin;
+pub use self::limit::Limit;
+pub use self::merge_join::MergeJoin;
+pub use self::project::Project;
+pub use self::scan::{IndexOnlyScan, IndexScan, SeqScan, TableScan};
+pub use self::sort::Sort;
+pub use self::spill::WorkMemory;
+
+/// A node of a physical plan. Rows are pulled from the root one at a time,
+/// and every operator pulls the rows it needs from its inputs in turn.
+pub trait Operator {
+    /// Produces the next row, or None once there are no more.
+    fn next(&mut self) -> io::Result<Option<DynamicRecord>>;
+
+    /// Starts over, producing only the rows whose value in the looked up column is the key.
+    /// Only index scans providing the inner rows of a nested loop join can do so.
+    fn rescan(&mut self, _key: &Value) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "The operator cannot be rescanned.",
+        ))
+    }
+}
+
+impl<T: Operator + ?Sized> Operator for Box<T> {
+    fn next(&mut self) -> io::Result<Option<DynamicRecord>> {
+        (**self).next()
+    }
+
+    fn rescan(&mut self, key: &Value) -> io::Result<()> {
+        (**self).rescan(key)
+    }
+}
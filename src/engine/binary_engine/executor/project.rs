@@ -0,0 +1,31 @@
+use std::io;
+
+use super::Operator;
+use crate::dynamic_record::DynamicRecord;
+
+/// Keeps only the selected columns of every row.
+pub struct Project<'a> {
+    input: Box<dyn Operator + 'a>,
+    column_names: Vec<String>,
+}
+
+impl<'a> Project<'a> {
+    pub fn new(input: Box<dyn Operator + 'a>, column_names: Vec<String>) -> Self {
+        Project {
+            input,
+            column_names,
+        }
+    }
+}
+
+impl Operator for Project<'_> {
+    fn next(&mut self) -> io::Result<Option<DynamicRecord>> {
+        let Some(mut record) = self.input.next()? else {
+            return Ok(None);
+        };
+
+        record.filter_columns(&self.column_names);
+
+        Ok(Some(record))
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::{btree_map, BTreeMap};
+use std::io;
+use std::vec;
+
+use super::Operator;
+use crate::dynamic_record::{DynamicRecord, Value};
+use crate::engine::binary_engine::b_plus_tree::Entry;
+use crate::engine::binary_engine::data_manager::DataManager;
+use crate::engine::binary_engine::index_manager::IndexManager;
+use crate::engine::binary_engine::mvcc::Snapshot;
+use crate::engine::binary_engine::record_id::RecordId;
+use crate::metadata;
+use crate::sql_parser::query::Condition;
+
+/// Reads the rows of a table one data page at a time, optionally only those in given slots.
+struct PageScan<'a> {
+    data_manager: &'a DataManager,
+    table: TableScan,
+    pages: btree_map::IntoIter<usize, Option<Vec<usize>>>,
+    buffer: vec::IntoIter<DynamicRecord>,
+}
+
+impl<'a> PageScan<'a> {
+    fn new(
+        data_manager: &'a DataManager,
+        table: &TableScan,
+        pages: BTreeMap<usize, Option<Vec<usize>>>,
+    ) -> Self {
+        PageScan {
+            data_manager,
+            table: table.clone(),
+            pages: pages.into_iter(),
+            buffer: vec![].into_iter(),
+        }
+    }
+}
+
+impl Operator for PageScan<'_> {
+    fn next(&mut self) -> io::Result<Option<DynamicRecord>> {
+        loop {
+            if let Some(record) = self.buffer.next() {
+                return Ok(Some(self.table.output(record)));
+            }
+
+            let Some((data_page_index, slots)) = self.pages.next() else {
+                return Ok(None);
+            };
+
+            self.buffer = self
+                .data_manager
+                .load_records(
+                    &self.table.table_name,
+                    data_page_index,
+                    slots.as_deref(),
+                    &self.table.where_clauses,
+                    &self.table.snapshot,
+                )?
+                .into_iter();
+        }
+    }
+}
+
+/// What every scan of a table needs to know: the table, the where clauses its rows
+/// are filtered with while they are read, and the snapshot they are read with.
+#[derive(Clone)]
+pub struct TableScan {
+    pub table_name: String,
+    pub where_clauses: Vec<Condition>,
+    pub snapshot: Snapshot,
+    /// The columns to keep, all of them if there is no list.
+    pub columns: Option<Vec<String>>,
+    /// The name the columns are prefixed with once the rows are joined with other tables.
+    pub qualifier: Option<String>,
+}
+
+impl TableScan {
+    /// Keeps the columns of a row that are needed and qualifies their names.
+    fn output(&self, mut record: DynamicRecord) -> DynamicRecord {
+        if let Some(columns) = &self.columns {
+            record.filter_columns(columns);
+        }
+
+        match &self.qualifier {
+            Some(qualifier) => qualify(record, qualifier),
+            None => record,
+        }
+    }
+}
+
+/// Reads every data page of the table.
+pub struct SeqScan<'a> {
+    data_manager: &'a DataManager,
+    table: TableScan,
+    pages: Option<PageScan<'a>>,
+}
+
+impl<'a> SeqScan<'a> {
+    pub fn new(data_manager: &'a DataManager, table: TableScan) -> Self {
+        SeqScan {
+            data_manager,
+            table,
+            pages: None,
+        }
+    }
+}
+
+impl Operator for SeqScan<'_> {
+    fn next(&mut self) -> io::Result<Option<DynamicRecord>> {
+        let data_manager = self.data_manager;
+        let table = &self.table;
+
+        self.pages
+            .get_or_insert_with(|| {
+                let pages = data_manager.slots_per_data_page(&table.table_name, None);
+
+                PageScan::new(data_manager, table, pages)
+            })
+            .next()
+    }
+}
+
+/// Looks up the record ids in a range of an index and reads only the data pages holding them.
+pub struct IndexScan<'a> {
+    data_manager: &'a DataManager,
+    index_manager: &'a IndexManager,
+    table: TableScan,
+    index: metadata::Index,
+    key_range: metadata::KeyRange,
+    pages: Option<PageScan<'a>>,
+}
+
+impl<'a> IndexScan<'a> {
+    pub fn new(
+        data_manager: &'a DataManager,
+        index_manager: &'a IndexManager,
+        table: TableScan,
+        index: metadata::Index,
+        key_range: metadata::KeyRange,
+    ) -> Self {
+        IndexScan {
+            data_manager,
+            index_manager,
+            table,
+            index,
+            key_range,
+            pages: None,
+        }
+    }
+}
+
+impl Operator for IndexScan<'_> {
+    fn next(&mut self) -> io::Result<Option<DynamicRecord>> {
+        if self.pages.is_none() {
+            let record_ids =
+                self.index_manager
+                    .scan(&self.table.table_name, &self.index, &self.key_range)?;
+            let pages = self
+                .data_manager
+                .slots_per_data_page(&self.table.table_name, Some(record_ids));
+
+            self.pages = Some(PageScan::new(self.data_manager, &self.table, pages));
+        }
+
+        self.pages.as_mut().unwrap().next()
+    }
+
+    fn rescan(&mut self, key: &Value) -> io::Result<()> {
+        self.key_range = lookup(key);
+        self.pages = None;
+
+        Ok(())
+    }
+}
+
+/// Answers a query from the index entries alone. Only records whose included values
+/// were too large to be stored in the index are read from their data pages.
+pub struct IndexOnlyScan<'a> {
+    data_manager: &'a DataManager,
+    index_manager: &'a IndexManager,
+    table: TableScan,
+    index: metadata::Index,
+    key_range: metadata::KeyRange,
+    entries: Option<vec::IntoIter<Entry>>,
+    incomplete_record_ids: Vec<RecordId>,
+    pages: Option<PageScan<'a>>,
+}
+
+impl<'a> IndexOnlyScan<'a> {
+    pub fn new(
+        data_manager: &'a DataManager,
+        index_manager: &'a IndexManager,
+        table: TableScan,
+        index: metadata::Index,
+        key_range: metadata::KeyRange,
+    ) -> Self {
+        IndexOnlyScan {
+            data_manager,
+            index_manager,
+            table,
+            index,
+            key_range,
+            entries: None,
+            incomplete_record_ids: vec![],
+            pages: None,
+        }
+    }
+}
+
+impl Operator for IndexOnlyScan<'_> {
+    fn next(&mut self) -> io::Result<Option<DynamicRecord>> {
+        if self.entries.is_none() {
+            self.entries = Some(
+                self.index_manager
+                    .scan_entries(&self.table.table_name, &self.index, &self.key_range)?
+                    .into_iter(),
+            );
+        }
+
+        for entry in self.entries.as_mut().unwrap().by_ref() {
+            let Some(record) = self.index.record_of(&entry.key, &entry.included) else {
+                self.incomplete_record_ids.push(entry.record_id);
+                continue;
+            };
+
+            if record.entry_should_be_included(&self.table.where_clauses) {
+                return Ok(Some(self.table.output(record)));
+            }
+        }
+
+        if self.pages.is_none() {
+            let pages = self.data_manager.slots_per_data_page(
+                &self.table.table_name,
+                Some(std::mem::take(&mut self.incomplete_record_ids)),
+            );
+
+            self.pages = Some(PageScan::new(self.data_manager, &self.table, pages));
+        }
+
+        self.pages.as_mut().unwrap().next()
+    }
+
+    fn rescan(&mut self, key: &Value) -> io::Result<()> {
+        self.key_range = lookup(key);
+        self.entries = None;
+        self.incomplete_record_ids.clear();
+        self.pages = None;
+
+        Ok(())
+    }
+}
+
+/// The entries of an index whose first column holds the key.
+fn lookup(key: &Value) -> metadata::KeyRange {
+    metadata::KeyRange {
+        prefix: vec![metadata::IndexKey::from_value(key)],
+        last: None,
+    }
+}
+
+/// Prefixes every column with the name of its table, as in `users.name`.
+fn qualify(record: DynamicRecord, qualifier: &str) -> DynamicRecord {
+    DynamicRecord::new(
+        record
+            .fields
+            .into_iter()
+            .map(|(column_name, value)| (format!("{}.{}", qualifier, column_name), value))
+            .collect(),
+    )
+}
@@ -0,0 +1,111 @@
+use std::cmp::Ordering;
+use std::io;
+use std::mem;
+use std::vec;
+
+use super::spill::{Merge, Run, WorkMemory};
+use super::Operator;
+use crate::dynamic_record::DynamicRecord;
+use crate::sql_parser::query::OrderBy;
+
+/// Reads all rows of its input before it returns the first one in order.
+/// Rows missing a column sort before all others. Once the rows outgrow the work memory,
+/// they are sorted in runs written to temporary files, which are merged in the end.
+pub struct Sort<'a> {
+    input: Box<dyn Operator + 'a>,
+    order_by: Vec<OrderBy>,
+    work_memory: WorkMemory,
+    sorted: Option<Sorted<'a>>,
+}
+
+enum Sorted<'a> {
+    InMemory(vec::IntoIter<DynamicRecord>),
+    Merged(Merge<'a, DynamicRecord>),
+}
+
+impl<'a> Sort<'a> {
+    pub fn new(
+        input: Box<dyn Operator + 'a>,
+        order_by: Vec<OrderBy>,
+        work_memory: WorkMemory,
+    ) -> Self {
+        Sort {
+            input,
+            order_by,
+            work_memory,
+            sorted: None,
+        }
+    }
+
+    fn sort(&mut self) -> io::Result<Sorted<'a>> {
+        let mut records = vec![];
+        let mut size = 0;
+        let mut runs = vec![];
+
+        while let Some(record) = self.input.next()? {
+            size += WorkMemory::size_of(&record);
+            records.push(record);
+
+            if size > self.work_memory.limit {
+                runs.push(self.write_run(mem::take(&mut records))?);
+                size = 0;
+            }
+        }
+
+        if runs.is_empty() {
+            records.sort_by(|left, right| compare(left, right, &self.order_by));
+
+            return Ok(Sorted::InMemory(records.into_iter()));
+        }
+
+        if !records.is_empty() {
+            runs.push(self.write_run(records)?);
+        }
+
+        let order_by = self.order_by.clone();
+
+        Ok(Sorted::Merged(Merge::new(
+            &self.work_memory.directory,
+            runs,
+            move |left, right| compare(left, right, &order_by),
+        )?))
+    }
+
+    fn write_run(&self, mut records: Vec<DynamicRecord>) -> io::Result<Run<DynamicRecord>> {
+        records.sort_by(|left, right| compare(left, right, &self.order_by));
+
+        Run::write(&self.work_memory.directory, records)
+    }
+}
+
+impl Operator for Sort<'_> {
+    fn next(&mut self) -> io::Result<Option<DynamicRecord>> {
+        if self.sorted.is_none() {
+            self.sorted = Some(self.sort()?);
+        }
+
+        match self.sorted.as_mut().unwrap() {
+            Sorted::InMemory(records) => Ok(records.next()),
+            Sorted::Merged(merge) => merge.next(),
+        }
+    }
+}
+
+pub fn compare(left: &DynamicRecord, right: &DynamicRecord, order_by: &[OrderBy]) -> Ordering {
+    order_by
+        .iter()
+        .map(|column| {
+            let ordering = left
+                .fields
+                .get(&column.column_name)
+                .cmp(&right.fields.get(&column.column_name));
+
+            if column.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
@@ -0,0 +1,273 @@
+use std::cmp::Ordering;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::marker::PhantomData;
+use std::mem;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+static RUN_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// How many runs are merged at once, each of them keeping a file open.
+const MERGE_FAN_IN: usize = 16;
+
+type Compare<'a, T> = Box<dyn Fn(&T, &T) -> Ordering + 'a>;
+
+/// How much an operator may keep in memory, and where it writes the rest.
+#[derive(Clone, Debug)]
+pub struct WorkMemory {
+    /// In bytes, as the rows are serialized.
+    pub limit: usize,
+    pub directory: String,
+}
+
+impl WorkMemory {
+    pub fn size_of(item: &impl Serialize) -> usize {
+        bincode::serialized_size(item).unwrap() as usize
+    }
+}
+
+/// Writes items to a temporary file, one after the other.
+pub struct RunWriter<T> {
+    path: String,
+    writer: BufWriter<File>,
+    items: PhantomData<T>,
+}
+
+impl<T: Serialize + DeserializeOwned> RunWriter<T> {
+    pub fn create(directory: &str) -> io::Result<Self> {
+        fs::create_dir_all(directory)?;
+
+        let path = format!(
+            "{}/run_{}_{}.bin",
+            directory,
+            process::id(),
+            RUN_COUNT.fetch_add(1, AtomicOrdering::Relaxed)
+        );
+
+        Ok(RunWriter {
+            writer: BufWriter::new(File::create(&path)?),
+            path,
+            items: PhantomData,
+        })
+    }
+
+    pub fn push(&mut self, item: &T) -> io::Result<()> {
+        bincode::serialize_into(&mut self.writer, item).map_err(io::Error::other)
+    }
+
+    /// Ends the run, to be read back from the start. Its file is closed until then.
+    pub fn finish(mut self) -> io::Result<Run<T>> {
+        self.writer.flush()?;
+
+        Ok(Run {
+            reader: None,
+            path: mem::take(&mut self.path),
+            items: PhantomData,
+        })
+    }
+}
+
+impl<T> Drop for RunWriter<T> {
+    fn drop(&mut self) {
+        if !self.path.is_empty() {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Items written to a temporary file in order, to be read back once. The file is opened
+/// on the first read and removed once the run is dropped.
+pub struct Run<T> {
+    path: String,
+    reader: Option<BufReader<File>>,
+    items: PhantomData<T>,
+}
+
+impl<T: Serialize + DeserializeOwned> Run<T> {
+    pub fn write(directory: &str, items: impl IntoIterator<Item = T>) -> io::Result<Self> {
+        let mut writer = RunWriter::create(directory)?;
+
+        for item in items {
+            writer.push(&item)?;
+        }
+
+        writer.finish()
+    }
+
+    pub fn next(&mut self) -> io::Result<Option<T>> {
+        if self.reader.is_none() {
+            self.reader = Some(BufReader::new(File::open(&self.path)?));
+        }
+
+        let reader = self.reader.as_mut().unwrap();
+
+        if reader.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+
+        bincode::deserialize_from(reader)
+            .map(Some)
+            .map_err(io::Error::other)
+    }
+}
+
+impl<T> Drop for Run<T> {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Merges sorted runs into a single sorted sequence. Of equal items, the one from the
+/// earlier run comes first. With more runs than can be merged at once, neighbouring runs
+/// are merged into longer ones in the directory first, until few enough are left.
+pub struct Merge<'a, T> {
+    runs: Vec<Run<T>>,
+    heads: Option<Vec<Option<T>>>,
+    compare: Compare<'a, T>,
+}
+
+impl<'a, T: Serialize + DeserializeOwned> Merge<'a, T> {
+    pub fn new(
+        directory: &str,
+        mut runs: Vec<Run<T>>,
+        compare: impl Fn(&T, &T) -> Ordering + 'a,
+    ) -> io::Result<Self> {
+        while runs.len() > MERGE_FAN_IN {
+            let mut remaining = runs.into_iter().peekable();
+            runs = vec![];
+
+            while remaining.peek().is_some() {
+                let mut pass = Merge {
+                    runs: remaining.by_ref().take(MERGE_FAN_IN).collect(),
+                    heads: None,
+                    compare: Box::new(&compare),
+                };
+                let mut writer = RunWriter::create(directory)?;
+
+                while let Some(item) = pass.next()? {
+                    writer.push(&item)?;
+                }
+
+                runs.push(writer.finish()?);
+            }
+        }
+
+        Ok(Merge {
+            runs,
+            heads: None,
+            compare: Box::new(compare),
+        })
+    }
+
+    pub fn next(&mut self) -> io::Result<Option<T>> {
+        if self.heads.is_none() {
+            self.heads = Some(
+                self.runs
+                    .iter_mut()
+                    .map(Run::next)
+                    .collect::<io::Result<_>>()?,
+            );
+        }
+
+        let heads = self.heads.as_mut().unwrap();
+
+        // the number of runs is small, so finding the smallest head one by one is cheap
+        let mut smallest: Option<usize> = None;
+
+        for (position, head) in heads.iter().enumerate() {
+            let Some(head) = head else {
+                continue;
+            };
+
+            if smallest.is_none_or(|smallest| {
+                (self.compare)(head, heads[smallest].as_ref().unwrap()).is_lt()
+            }) {
+                smallest = Some(position);
+            }
+        }
+
+        let Some(position) = smallest else {
+            return Ok(None);
+        };
+
+        let next_head = self.runs[position].next()?;
+
+        Ok(mem::replace(&mut heads[position], next_head))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merges_runs_in_order_and_removes_their_files() {
+        let directory = std::env::temp_dir()
+            .join("r_sql_runs")
+            .to_string_lossy()
+            .to_string();
+
+        let runs = vec![
+            Run::write(&directory, [1, 4, 7]).unwrap(),
+            Run::write(&directory, [2, 3, 9]).unwrap(),
+            Run::write(&directory, Vec::<i64>::new()).unwrap(),
+        ];
+        let paths: Vec<String> = runs.iter().map(|run| run.path.clone()).collect();
+
+        let mut merge =
+            Merge::new(&directory, runs, |left: &i64, right: &i64| left.cmp(right)).unwrap();
+        let mut merged = vec![];
+
+        while let Some(item) = merge.next().unwrap() {
+            merged.push(item);
+        }
+
+        assert_eq!(merged, vec![1, 2, 3, 4, 7, 9]);
+
+        drop(merge);
+        assert!(paths
+            .iter()
+            .all(|path| !std::path::Path::new(path).exists()));
+    }
+
+    #[test]
+    fn test_merges_more_runs_than_it_merges_at_once_in_passes() {
+        let directory = std::env::temp_dir()
+            .join("r_sql_runs")
+            .to_string_lossy()
+            .to_string();
+
+        let run_count = MERGE_FAN_IN * MERGE_FAN_IN + 3;
+        // every run holds its number and the numbers of the runs after it, tagged with the run
+        let runs: Vec<Run<(usize, usize)>> = (0..run_count)
+            .map(|run| {
+                Run::write(&directory, [(run, run), (run + 1, run), (run + 2, run)]).unwrap()
+            })
+            .collect();
+
+        let mut merge = Merge::new(&directory, runs, |left: &(usize, usize), right| {
+            left.0.cmp(&right.0)
+        })
+        .unwrap();
+
+        assert!(merge.runs.len() <= MERGE_FAN_IN);
+
+        let mut merged = vec![];
+
+        while let Some(item) = merge.next().unwrap() {
+            merged.push(item);
+        }
+
+        let mut expected: Vec<(usize, usize)> = (0..run_count)
+            .flat_map(|run| [(run, run), (run + 1, run), (run + 2, run)])
+            .collect();
+        expected.sort();
+
+        // equal items keep the order of their runs
+        assert_eq!(merged, expected);
+    }
+}
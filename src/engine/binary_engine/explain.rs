@@ -0,0 +1,359 @@
+use std::collections::HashMap;
+
+use crate::dynamic_record::{DynamicRecord, Value};
+use crate::engine::explain::Explain;
+use crate::engine::SelectQuery;
+
+use super::executor::OperatorStatistics;
+
+impl Explain for super::BinaryEngine {
+    fn explain(&self, query: SelectQuery, analyze: bool) -> super::EngineResult {
+        let (mut plan, _guards) = self.plan_query(query)?;
+
+        let mut lines: Vec<String> = plan.to_string().lines().map(String::from).collect();
+
+        if analyze {
+            let statistics = plan.instrument();
+            let mut operator = plan.build(self);
+
+            while operator
+                .next()
+                .map_err(|error| error.to_string())?
+                .is_some()
+            {}
+
+            for (line, statistics) in lines.iter_mut().zip(statistics) {
+                line.push_str(&format_statistics(&statistics.borrow()));
+            }
+        }
+
+        let records = lines
+            .into_iter()
+            .map(|line| {
+                DynamicRecord::new(HashMap::from([(String::from("plan"), Value::Text(line))]))
+            })
+            .collect();
+
+        Ok(super::EngineResponse {
+            table: None,
+            records: Some(records),
+            message: None,
+        })
+    }
+}
+
+fn format_statistics(statistics: &OperatorStatistics) -> String {
+    if statistics.loops == 0 {
+        return String::from(" (never executed)");
+    }
+
+    format!(
+        " (actual rows: {}, loops: {}, time: {:.3} ms, disk reads: {}, pending-write hits: {})",
+        statistics.rows,
+        statistics.loops,
+        statistics.time.as_secs_f64() * 1000.0,
+        statistics.disk_reads,
+        statistics.pending_write_hits
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{binary_engine::BinaryEngine, Engine};
+    use crate::io_test_context::FileTestContext;
+    use crate::sql_parser::SqlParser;
+
+    fn explain(engine: &BinaryEngine, query: &str) -> Vec<String> {
+        engine
+            .execute(
+                SqlParser()
+                    .parse_query(format!("EXPLAIN {}", query))
+                    .unwrap(),
+            )
+            .unwrap()
+            .records
+            .unwrap()
+            .iter()
+            .map(|record| record.fields["plan"].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_explains_the_chosen_plan_with_estimated_rows() {
+        let context = FileTestContext::new();
+        let table_name = context.table_name();
+        let engine = BinaryEngine::new();
+
+        let values: Vec<String> = (0..300)
+            .map(|number| format!("('user{}', {})", number, number % 50))
+            .collect();
+
+        for query in [
+            format!("CREATE TABLE {} (name VARCHAR, age INTEGER);", table_name),
+            format!("CREATE INDEX age_index ON {} (age);", table_name),
+            format!(
+                "INSERT INTO {} (name, age) VALUES {};",
+                table_name,
+                values.join(", ")
+            ),
+        ] {
+            engine
+                .execute(SqlParser().parse_query(query).unwrap())
+                .unwrap();
+        }
+
+        assert_eq!(
+            explain(
+                &engine,
+                &format!("SELECT name FROM {} WHERE age = 7;", table_name)
+            ),
+            vec![
+                String::from("Project name (estimated rows: 30)"),
+                format!(
+                    "  -> Index Scan on {} using age_index where age = 7 (estimated rows: 30)",
+                    table_name
+                ),
+            ]
+        );
+        assert_eq!(
+            explain(
+                &engine,
+                &format!("SELECT * FROM {} WHERE id = 4 AND age >= 0;", table_name)
+            ),
+            vec![format!(
+                "Index Scan on {} using id_index where id = 4 AND age >= 0 (estimated rows: 1)",
+                table_name
+            )]
+        );
+        assert_eq!(
+            explain(
+                &engine,
+                &format!("SELECT COUNT(*) FROM {} WHERE age > 40;", table_name)
+            ),
+            vec![
+                String::from("Project COUNT(*) (estimated rows: 1)"),
+                String::from("  -> Aggregate COUNT(*) (estimated rows: 1)"),
+                format!(
+                    "      -> Index Only Scan on {} using age_index where age > 40 (estimated rows: 100)",
+                    table_name
+                ),
+            ]
+        );
+        assert_eq!(
+            explain(
+                &engine,
+                &format!(
+                    "SELECT name FROM {} WHERE age = 7 AND age > 10 ORDER BY name;",
+                    table_name
+                )
+            ),
+            vec![String::from("Empty (estimated rows: 0)")]
+        );
+        assert_eq!(
+            explain(&engine, &format!("SELECT name FROM {};", table_name))[1],
+            format!("  -> Seq Scan on {} (estimated rows: 300)", table_name)
+        );
+    }
+
+    #[test]
+    fn test_chooses_how_to_join_by_the_estimated_cost() {
+        let (customers, orders, offices) = (
+            FileTestContext::new(),
+            FileTestContext::new(),
+            FileTestContext::new(),
+        );
+        let (customers, orders, offices) = (
+            customers.table_name(),
+            orders.table_name(),
+            offices.table_name(),
+        );
+        let engine = BinaryEngine::new();
+
+        let values = |count: usize, value: &dyn Fn(usize) -> String| {
+            (0..count).map(value).collect::<Vec<String>>().join(", ")
+        };
+
+        for query in [
+            format!("CREATE TABLE {} (name VARCHAR, city INTEGER);", customers),
+            format!(
+                "CREATE TABLE {} (customer_id INTEGER, total INTEGER);",
+                orders
+            ),
+            format!("CREATE TABLE {} (city INTEGER);", offices),
+            format!("CREATE INDEX customer_index ON {} (customer_id);", orders),
+            format!(
+                "INSERT INTO {} (name, city) VALUES {};",
+                customers,
+                values(300, &|number| format!("('c{}', {})", number, number % 5))
+            ),
+            format!(
+                "INSERT INTO {} (customer_id, total) VALUES {};",
+                orders,
+                values(600, &|number| format!("({}, {})", number % 300 + 1, number))
+            ),
+            format!(
+                "INSERT INTO {} (city) VALUES {};",
+                offices,
+                values(100, &|number| format!("({})", number % 5))
+            ),
+        ] {
+            engine
+                .execute(SqlParser().parse_query(query).unwrap())
+                .unwrap();
+        }
+
+        let rows = |query: &str| {
+            engine
+                .execute(SqlParser().parse_query(query.to_string()).unwrap())
+                .unwrap()
+                .records
+                .unwrap()
+        };
+
+        let looked_up = format!(
+            "SELECT name, total FROM {customers} JOIN {orders} ON customer_id = {customers}.id WHERE {customers}.id = 7;"
+        );
+        assert_eq!(
+            explain(&engine, &looked_up)[1..],
+            [
+                format!("  -> Nested Loop Join on {customers}.id = {orders}.customer_id (estimated rows: 1)"),
+                format!("      -> Index Scan on {customers} using id_index where id = 7 (estimated rows: 1)"),
+                format!("      -> Index Scan on {orders} using customer_index where customer_id = {customers}.id (estimated rows: 60)"),
+            ]
+        );
+        assert_eq!(rows(&looked_up).len(), 2);
+
+        let counted = format!(
+            "SELECT COUNT(*) FROM {customers} JOIN {orders} ON customer_id = {customers}.id;"
+        );
+        // the smaller input is built into the hash table
+        assert_eq!(
+            explain(&engine, &counted)[2..],
+            [
+                format!("      -> Hash Join on {orders}.customer_id = {customers}.id (estimated rows: 300)"),
+                format!("          -> Seq Scan on {orders} (estimated rows: 600)"),
+                format!("          -> Seq Scan on {customers} (estimated rows: 300)"),
+            ]
+        );
+
+        let sorted = format!(
+            "SELECT {customers}.city FROM {customers} JOIN {offices} ON {customers}.city = {offices}.city ORDER BY {customers}.city;"
+        );
+        assert!(explain(&engine, &sorted)[2].contains("Hash Join"));
+
+        // with statistics every customer is known to join with many offices, so that sorting
+        // both inputs is cheaper than sorting the joined rows
+        for table_name in [customers, offices] {
+            engine
+                .execute(
+                    SqlParser()
+                        .parse_query(format!("ANALYZE {};", table_name))
+                        .unwrap(),
+                )
+                .unwrap();
+        }
+
+        assert_eq!(
+            explain(&engine, &sorted)[1..],
+            [
+                format!(
+                    "  -> Merge Join on {customers}.city = {offices}.city (estimated rows: 6000)"
+                ),
+                format!("      -> Sort by {customers}.city (estimated rows: 300)"),
+                format!("          -> Seq Scan on {customers} (estimated rows: 300)"),
+                format!("      -> Sort by {offices}.city (estimated rows: 100)"),
+                format!("          -> Seq Scan on {offices} (estimated rows: 100)"),
+            ]
+        );
+        let cities: Vec<String> = rows(&sorted)
+            .iter()
+            .map(|row| row.fields[&format!("{}.city", customers)].to_string())
+            .collect();
+
+        assert_eq!(cities.len(), 6000);
+        assert!(cities.is_sorted());
+    }
+
+    /// The actual statistics at the end of a line of an analyzed plan, by name.
+    fn actual(line: &str, name: &str) -> u64 {
+        let (_, statistics) = line.split_once("(actual ").unwrap();
+
+        statistics
+            .trim_end_matches(')')
+            .split(", ")
+            .find_map(|statistic| statistic.strip_prefix(&format!("{}: ", name)))
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_explain_analyze_runs_the_query_and_shows_what_every_operator_did() {
+        let context = FileTestContext::new();
+        let table_name = context.table_name();
+        let engine = BinaryEngine::new();
+
+        let values: Vec<String> = (0..300)
+            .map(|number| format!("('user{}', {})", number, number % 50))
+            .collect();
+
+        for query in [
+            format!("CREATE TABLE {} (name VARCHAR, age INTEGER);", table_name),
+            format!("CREATE INDEX age_index ON {} (age);", table_name),
+            format!(
+                "INSERT INTO {} (name, age) VALUES {};",
+                table_name,
+                values.join(", ")
+            ),
+        ] {
+            engine
+                .execute(SqlParser().parse_query(query).unwrap())
+                .unwrap();
+        }
+
+        let plan = explain(
+            &engine,
+            &format!(
+                "ANALYZE SELECT name FROM {} WHERE age = 7 LIMIT 2;",
+                table_name
+            ),
+        );
+
+        assert_eq!(plan.len(), 3);
+        assert!(
+            plan[0].starts_with("Limit 2 (estimated rows: 2) (actual rows: 2, loops: 1, time: ")
+        );
+        assert!(plan[2].starts_with(&format!(
+            "      -> Index Scan on {} using age_index where age = 7 (estimated rows: 30) (actual rows: 2,",
+            table_name
+        )));
+        assert!(actual(&plan[2], "disk reads") > 0);
+        assert_eq!(
+            actual(&plan[0], "disk reads"),
+            actual(&plan[2], "disk reads")
+        );
+
+        engine
+            .execute(SqlParser().parse_query(String::from("BEGIN;")).unwrap())
+            .unwrap();
+        engine
+            .execute(
+                SqlParser()
+                    .parse_query(format!(
+                        "INSERT INTO {} (name, age) VALUES ('new', 1);",
+                        table_name
+                    ))
+                    .unwrap(),
+            )
+            .unwrap();
+
+        let plan = explain(&engine, &format!("ANALYZE SELECT * FROM {};", table_name));
+
+        assert_eq!(actual(&plan[0], "rows"), 301);
+        assert!(actual(&plan[0], "pending-write hits") > 0);
+
+        engine
+            .execute(SqlParser().parse_query(String::from("ROLLBACK;")).unwrap())
+            .unwrap();
+    }
+}
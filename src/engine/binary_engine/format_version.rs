@@ -0,0 +1,122 @@
+use std::fs;
+use std::io::{self, Write};
+
+use crate::engine::file_paths::FilePaths;
+
+/// The version of the format of the files this build reads and writes: the encoding of the
+/// records, the headers of the pages and the frames of the write-ahead log. Databases written
+/// before the version was recorded have version 0.
+pub const FORMAT_VERSION: u32 = 1;
+
+// the files a database holds before anything was written to it
+const FILES_OF_EMPTY_DATABASES: [&str; 2] = ["database.lock", "temp"];
+
+/// Records the format version of a new database, or refuses to open an existing one
+/// written in another format, before any of its files is read.
+pub fn check(file_paths: &FilePaths, writable: bool) -> io::Result<()> {
+    let path = file_paths.format_version_path();
+
+    let version = match fs::read(&path) {
+        Ok(contents) => match <[u8; 4]>::try_from(contents) {
+            Ok(bytes) => u32::from_le_bytes(bytes),
+            Err(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("The format version in '{}' is corrupted.", path),
+                ))
+            }
+        },
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            if !is_empty(file_paths.base_path())? {
+                0
+            } else if writable {
+                return write(&path);
+            } else {
+                return Ok(());
+            }
+        }
+        Err(error) => return Err(error),
+    };
+
+    if version != FORMAT_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "The database in '{}' has format version {}, but this build only opens format version {}. \
+                 Export its data with the build that wrote it and import it into a new database.",
+                file_paths.base_path(),
+                version,
+                FORMAT_VERSION
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn is_empty(base_path: &str) -> io::Result<bool> {
+    let entries = match fs::read_dir(base_path) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(true),
+        Err(error) => return Err(error),
+    };
+
+    for entry in entries {
+        if !FILES_OF_EMPTY_DATABASES.contains(&entry?.file_name().to_string_lossy().as_ref()) {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Writes the version to a temporary file first, so that a crash cannot leave a partial one.
+fn write(path: &str) -> io::Result<()> {
+    let temporary_path = format!("{}.tmp", path);
+
+    let mut file = fs::File::create(&temporary_path)?;
+    file.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    file.sync_all()?;
+
+    fs::rename(&temporary_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::FORMAT_VERSION;
+    use crate::engine::binary_engine::BinaryEngine;
+    use crate::io_test_context::TestDatabase;
+
+    #[test]
+    fn test_refuses_to_open_databases_of_another_format_version() {
+        let database = TestDatabase::new();
+        let options = database.options();
+        let directory = options.directory.clone().unwrap();
+        let version_path = format!("{}/format_version.bin", directory);
+
+        BinaryEngine::open(&options).unwrap();
+        assert_eq!(
+            fs::read(&version_path).unwrap(),
+            FORMAT_VERSION.to_le_bytes()
+        );
+        BinaryEngine::open(&options).unwrap();
+
+        let refused = |version: u32| {
+            format!(
+                "Failed to open the database: The database in '{}' has format version {}, \
+                 but this build only opens format version {}. \
+                 Export its data with the build that wrote it and import it into a new database.",
+                directory, version, FORMAT_VERSION
+            )
+        };
+
+        fs::write(&version_path, 7u32.to_le_bytes()).unwrap();
+        assert_eq!(BinaryEngine::open(&options).err().unwrap(), refused(7));
+
+        // written before the format version was recorded
+        fs::remove_file(&version_path).unwrap();
+        assert_eq!(BinaryEngine::open(&options).err().unwrap(), refused(0));
+    }
+}
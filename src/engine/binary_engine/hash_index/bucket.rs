@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+use super::super::b_plus_tree::Entry;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Block {
+    /// Entries whose hashes share the lowest `local_depth` bits. A full bucket that cannot be
+    /// split any further continues in an overflow bucket.
+    Bucket {
+        local_depth: usize,
+        entries: Vec<Entry>,
+        overflow: Option<usize>,
+    },
+    Free {
+        next: Option<usize>,
+    },
+}
+
+/// The directory maps the lowest `global_depth` bits of a hash to a bucket and is stored
+/// in `directory_blocks` consecutive blocks starting at `directory_start`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Header {
+    pub global_depth: usize,
+    pub directory_start: usize,
+    pub directory_blocks: usize,
+    pub block_count: usize,
+    pub free_list: Option<usize>,
+}
@@ -0,0 +1,582 @@
+mod bucket;
+
+use std::collections::BTreeSet;
+use std::io;
+use std::sync::Arc;
+
+use self::bucket::{Block, Header};
+use super::b_plus_tree::{
+    check_blocks, read_block, serialized_size, write_block, Entry, MAX_ENTRY_SIZE, NODE_CAPACITY,
+};
+use super::pager::{PagedFile, Pager};
+use crate::metadata::{IndexKey, KeyRange};
+
+// directories beyond this depth would outgrow their use, so buckets overflow instead
+const MAX_GLOBAL_DEPTH: usize = 20;
+const HEADER_BLOCK: usize = 0;
+
+/// A bucket as read from disk, together with the block it is stored in.
+#[derive(Clone)]
+struct Bucket {
+    block_id: usize,
+    local_depth: usize,
+    entries: Vec<Entry>,
+    overflow: Option<usize>,
+}
+
+/// An extendible hash index stored in fixed-size blocks of a single file. Block 0 holds the
+/// header, the directory is kept in memory while the index is open and buckets are read on demand.
+/// It only answers lookups of complete keys.
+pub struct HashIndex {
+    file: PagedFile,
+    header: Header,
+    directory: Vec<usize>,
+}
+
+impl HashIndex {
+    pub fn create(pager: &Arc<Pager>, path: &str) -> io::Result<()> {
+        pager.write(path, vec![]);
+
+        let mut index = HashIndex {
+            file: pager.file(path),
+            header: Header {
+                global_depth: 0,
+                directory_start: 1,
+                directory_blocks: 1,
+                block_count: 3,
+                free_list: None,
+            },
+            directory: vec![2],
+        };
+
+        index.write_bucket(Bucket {
+            block_id: 2,
+            local_depth: 0,
+            entries: vec![],
+            overflow: None,
+        })?;
+        index.write_directory()?;
+        index.write_header()
+    }
+
+    pub fn open(pager: &Arc<Pager>, path: &str) -> io::Result<Self> {
+        let file = pager.file(path);
+
+        let buffer = read_block(&file, HEADER_BLOCK)?;
+        let header: Header =
+            bincode::deserialize(&buffer[..]).map_err(|_| corrupted(HEADER_BLOCK))?;
+
+        let mut buffer = vec![];
+        for block_id in header.directory_start..header.directory_start + header.directory_blocks {
+            buffer.extend(read_block(&file, block_id)?);
+        }
+
+        let directory =
+            bincode::deserialize(&buffer[..]).map_err(|_| corrupted(header.directory_start))?;
+
+        Ok(HashIndex {
+            file,
+            header,
+            directory,
+        })
+    }
+
+    pub fn insert(&mut self, entry: Entry) -> io::Result<()> {
+        if serialized_size(&entry) > MAX_ENTRY_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Index key is too large.",
+            ));
+        }
+
+        let hash = hash(&entry.key);
+
+        loop {
+            let bucket_id = self.bucket_id(hash);
+            let chain = self.read_chain(bucket_id)?;
+
+            if chain.iter().any(|bucket| bucket.entries.contains(&entry)) {
+                return Ok(());
+            }
+
+            if let Some(mut bucket) = chain
+                .iter()
+                .find(|bucket| fits(&bucket.entries, &entry))
+                .cloned()
+            {
+                bucket.entries.push(entry);
+
+                return self.write_bucket(bucket);
+            }
+
+            let mut last = chain.last().cloned().unwrap();
+
+            if last.local_depth == MAX_GLOBAL_DEPTH
+                || chain
+                    .iter()
+                    .flat_map(|bucket| &bucket.entries)
+                    .all(|other| hash_of(other) == hash)
+            {
+                let overflow_id = self.allocate()?;
+
+                self.write_bucket(Bucket {
+                    block_id: overflow_id,
+                    local_depth: last.local_depth,
+                    entries: vec![entry],
+                    overflow: None,
+                })?;
+
+                last.overflow = Some(overflow_id);
+                self.write_bucket(last)?;
+
+                return self.write_header();
+            }
+
+            self.split(bucket_id, chain)?;
+        }
+    }
+
+    pub fn remove(&mut self, entry: &Entry) -> io::Result<bool> {
+        let bucket_id = self.bucket_id(hash(&entry.key));
+        let mut previous: Option<Bucket> = None;
+
+        for mut bucket in self.read_chain(bucket_id)? {
+            let Some(position) = bucket.entries.iter().position(|other| other == entry) else {
+                previous = Some(bucket);
+                continue;
+            };
+
+            bucket.entries.remove(position);
+
+            match previous {
+                // empty overflow buckets are unlinked from their chain and reused later on
+                Some(mut previous) if bucket.entries.is_empty() => {
+                    previous.overflow = bucket.overflow;
+
+                    self.write_bucket(previous)?;
+                    self.free(bucket.block_id)?;
+                    self.write_header()?;
+                }
+                _ => self.write_bucket(bucket)?,
+            }
+
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    /// Collects all entries whose key equals the key range's prefix.
+    pub fn scan(&mut self, key_range: &KeyRange) -> io::Result<Vec<Entry>> {
+        if key_range.last.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Hash indexes only support equality lookups.",
+            ));
+        }
+
+        let key = &key_range.prefix;
+        let mut found = vec![];
+
+        for bucket in self.read_chain(self.bucket_id(hash(key)))? {
+            found.extend(bucket.entries.into_iter().filter(|entry| &entry.key == key));
+        }
+
+        found.sort();
+
+        Ok(found)
+    }
+
+    /// Collects the entries of all buckets.
+    pub fn entries(&mut self) -> io::Result<Vec<Entry>> {
+        let bucket_ids: BTreeSet<usize> = self.directory.iter().copied().collect();
+        let mut entries = vec![];
+
+        for bucket_id in bucket_ids {
+            for bucket in self.read_chain(bucket_id)? {
+                entries.extend(bucket.entries);
+            }
+        }
+
+        entries.sort();
+
+        Ok(entries)
+    }
+
+    /// Splits the chain of a full bucket into two buckets distinguished by one more bit of the hash,
+    /// doubling the directory first if the bucket already uses all of its bits.
+    fn split(&mut self, bucket_id: usize, chain: Vec<Bucket>) -> io::Result<()> {
+        let local_depth = chain[0].local_depth;
+
+        if local_depth == self.header.global_depth {
+            self.directory.extend(self.directory.clone());
+            self.header.global_depth += 1;
+        }
+
+        for bucket in chain.iter().skip(1) {
+            self.free(bucket.block_id)?;
+        }
+
+        let (right_entries, left_entries): (Vec<Entry>, Vec<Entry>) = chain
+            .into_iter()
+            .flat_map(|bucket| bucket.entries)
+            .partition(|entry| hash_of(entry) >> local_depth & 1 == 1);
+
+        let right_id = self.allocate()?;
+
+        self.write_chain(bucket_id, local_depth + 1, left_entries)?;
+        self.write_chain(right_id, local_depth + 1, right_entries)?;
+
+        for (position, block_id) in self.directory.iter_mut().enumerate() {
+            if *block_id == bucket_id && position >> local_depth & 1 == 1 {
+                *block_id = right_id;
+            }
+        }
+
+        self.write_directory()?;
+        self.write_header()
+    }
+
+    /// Writes the entries into the given bucket, continuing in overflow buckets if they don't fit.
+    fn write_chain(
+        &mut self,
+        block_id: usize,
+        local_depth: usize,
+        entries: Vec<Entry>,
+    ) -> io::Result<()> {
+        let mut bucket = Bucket {
+            block_id,
+            local_depth,
+            entries: vec![],
+            overflow: None,
+        };
+
+        for entry in entries {
+            if !fits(&bucket.entries, &entry) {
+                let overflow_id = self.allocate()?;
+                bucket.overflow = Some(overflow_id);
+
+                self.write_bucket(bucket)?;
+
+                bucket = Bucket {
+                    block_id: overflow_id,
+                    local_depth,
+                    entries: vec![],
+                    overflow: None,
+                };
+            }
+
+            bucket.entries.push(entry);
+        }
+
+        self.write_bucket(bucket)
+    }
+
+    fn bucket_id(&self, hash: u64) -> usize {
+        self.directory[(hash & ((1 << self.header.global_depth) - 1)) as usize]
+    }
+
+    /// Reads a bucket and all of its overflow buckets.
+    fn read_chain(&mut self, bucket_id: usize) -> io::Result<Vec<Bucket>> {
+        let mut chain = vec![];
+        let mut next_block = Some(bucket_id);
+
+        while let Some(block_id) = next_block {
+            let Block::Bucket {
+                local_depth,
+                entries,
+                overflow,
+            } = self.read_block(block_id)?
+            else {
+                return Err(corrupted(block_id));
+            };
+
+            chain.push(Bucket {
+                block_id,
+                local_depth,
+                entries,
+                overflow,
+            });
+            next_block = overflow;
+        }
+
+        Ok(chain)
+    }
+
+    fn allocate(&mut self) -> io::Result<usize> {
+        match self.header.free_list {
+            Some(block_id) => {
+                let Block::Free { next } = self.read_block(block_id)? else {
+                    return Err(corrupted(block_id));
+                };
+
+                self.header.free_list = next;
+
+                Ok(block_id)
+            }
+            None => {
+                let block_id = self.header.block_count;
+                self.header.block_count += 1;
+
+                Ok(block_id)
+            }
+        }
+    }
+
+    fn free(&mut self, block_id: usize) -> io::Result<()> {
+        self.write_block(
+            block_id,
+            &Block::Free {
+                next: self.header.free_list,
+            },
+        )?;
+        self.header.free_list = Some(block_id);
+
+        Ok(())
+    }
+
+    /// Describes every block of the file that is corrupted, whether it is in use or free.
+    pub fn check_blocks(&self) -> Vec<String> {
+        check_blocks(&self.file, self.header.block_count)
+    }
+
+    fn read_block(&mut self, block_id: usize) -> io::Result<Block> {
+        let buffer = read_block(&self.file, block_id)?;
+
+        bincode::deserialize(&buffer[..]).map_err(|_| corrupted(block_id))
+    }
+
+    fn write_block(&mut self, block_id: usize, block: &Block) -> io::Result<()> {
+        write_block(&self.file, block_id, &bincode::serialize(block).unwrap())
+    }
+
+    fn write_bucket(&mut self, bucket: Bucket) -> io::Result<()> {
+        self.write_block(
+            bucket.block_id,
+            &Block::Bucket {
+                local_depth: bucket.local_depth,
+                entries: bucket.entries,
+                overflow: bucket.overflow,
+            },
+        )
+    }
+
+    /// Writes the directory into its blocks, moving it to the end of the file once it outgrows them.
+    fn write_directory(&mut self) -> io::Result<()> {
+        let serialized = bincode::serialize(&self.directory).unwrap();
+        let directory_blocks = serialized.len().div_ceil(NODE_CAPACITY);
+
+        if directory_blocks > self.header.directory_blocks {
+            for block_id in self.header.directory_start
+                ..self.header.directory_start + self.header.directory_blocks
+            {
+                self.free(block_id)?;
+            }
+
+            self.header.directory_start = self.header.block_count;
+            self.header.directory_blocks = directory_blocks;
+            self.header.block_count += directory_blocks;
+        }
+
+        for (offset, chunk) in serialized.chunks(NODE_CAPACITY).enumerate() {
+            write_block(&self.file, self.header.directory_start + offset, chunk)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        let serialized = bincode::serialize(&self.header).unwrap();
+
+        write_block(&self.file, HEADER_BLOCK, &serialized)
+    }
+}
+
+fn fits(entries: &[Entry], entry: &Entry) -> bool {
+    let block = Block::Bucket {
+        local_depth: 0,
+        entries: entries.to_vec(),
+        overflow: Some(0),
+    };
+
+    serialized_size(&block) + serialized_size(entry) <= NODE_CAPACITY
+}
+
+fn hash_of(entry: &Entry) -> u64 {
+    hash(&entry.key)
+}
+
+/// FNV-1a over the serialized key, which unlike the std hashers is guaranteed to stay the same
+/// across builds, as the hashes decide where entries are stored on disk.
+fn hash(key: &[IndexKey]) -> u64 {
+    bincode::serialize(key)
+        .unwrap()
+        .iter()
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+fn corrupted(block_id: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Index block {} is corrupted.", block_id),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::record_id::RecordId;
+    use super::*;
+    use crate::engine::binary_engine::pager::tests::TempPager;
+
+    struct IndexFile(TempPager);
+
+    impl IndexFile {
+        fn new() -> Self {
+            let temp = TempPager::new();
+            HashIndex::create(&temp.pager, &temp.path("index.bin")).unwrap();
+
+            IndexFile(temp)
+        }
+
+        fn open(&self) -> HashIndex {
+            HashIndex::open(&self.0.pager, &self.0.path("index.bin")).unwrap()
+        }
+    }
+
+    fn entry(key: usize, data_page: usize) -> Entry {
+        Entry::new(
+            vec![IndexKey::Int(key as i64)],
+            RecordId::new(data_page, key),
+        )
+    }
+
+    fn find(index: &mut HashIndex, key: IndexKey) -> Vec<RecordId> {
+        index
+            .scan(&KeyRange {
+                prefix: vec![key],
+                last: None,
+            })
+            .unwrap()
+            .iter()
+            .map(|entry| entry.record_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_can_find_entries_after_splitting_buckets() {
+        let index_file = IndexFile::new();
+        let mut index = index_file.open();
+
+        for key in 0..5000 {
+            index.insert(entry(key, 1)).unwrap();
+        }
+
+        let mut index = index_file.open();
+
+        assert!(index.header.global_depth > 4);
+        for key in [0, 1, 2500, 4999] {
+            assert_eq!(
+                find(&mut index, IndexKey::Int(key as i64)),
+                vec![RecordId::new(1, key)]
+            );
+        }
+        assert!(find(&mut index, IndexKey::Int(5000)).is_empty());
+    }
+
+    #[test]
+    fn test_moves_the_directory_once_it_outgrows_its_blocks() {
+        let index_file = IndexFile::new();
+        let mut index = index_file.open();
+
+        for _ in 0..10 {
+            index.directory.extend(index.directory.clone());
+            index.header.global_depth += 1;
+        }
+
+        index.write_directory().unwrap();
+        index.write_header().unwrap();
+        index.insert(entry(1, 1)).unwrap();
+
+        let mut index = index_file.open();
+
+        assert!(index.header.directory_blocks > 1);
+        assert_eq!(index.directory.len(), 1024);
+        assert_eq!(
+            find(&mut index, IndexKey::Int(1)),
+            vec![RecordId::new(1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_keeps_duplicate_keys_in_overflow_buckets() {
+        let index_file = IndexFile::new();
+        let mut index = index_file.open();
+
+        for data_page in 0..500 {
+            index
+                .insert(Entry::new(
+                    vec![IndexKey::Text(String::from("open"))],
+                    RecordId::new(data_page, 0),
+                ))
+                .unwrap();
+        }
+        index.insert(entry(7, 1)).unwrap();
+
+        let mut index = index_file.open();
+
+        assert_eq!(
+            find(&mut index, IndexKey::Text(String::from("open"))).len(),
+            500
+        );
+        assert_eq!(
+            find(&mut index, IndexKey::Int(7)),
+            vec![RecordId::new(1, 7)]
+        );
+    }
+
+    #[test]
+    fn test_reuses_emptied_overflow_buckets_after_removals() {
+        let index_file = IndexFile::new();
+        let mut index = index_file.open();
+
+        let duplicate = |data_page| {
+            Entry::new(
+                vec![IndexKey::Text(String::from("open"))],
+                RecordId::new(data_page, 0),
+            )
+        };
+
+        for data_page in 0..500 {
+            index.insert(duplicate(data_page)).unwrap();
+        }
+
+        let block_count = index.header.block_count;
+
+        for data_page in 0..500 {
+            assert!(index.remove(&duplicate(data_page)).unwrap());
+        }
+        assert!(!index.remove(&duplicate(0)).unwrap());
+        assert!(index.header.free_list.is_some());
+
+        for data_page in 0..500 {
+            index.insert(duplicate(data_page)).unwrap();
+        }
+
+        assert_eq!(index.header.block_count, block_count);
+    }
+
+    #[test]
+    fn test_only_answers_equality_lookups() {
+        let index_file = IndexFile::new();
+        let mut index = index_file.open();
+
+        assert!(index
+            .scan(&KeyRange {
+                prefix: vec![],
+                last: Some(crate::metadata::ColumnRange::Prefix(String::from("a"))),
+            })
+            .is_err());
+    }
+}
@@ -0,0 +1,196 @@
+use std::io;
+use std::sync::Arc;
+
+use crate::{dynamic_record::DynamicRecord, engine::file_paths::FilePaths, metadata};
+
+use super::b_plus_tree::{serialized_size, BPlusTree, Entry, MAX_ENTRY_SIZE};
+use super::hash_index::HashIndex;
+use super::pager::Pager;
+use super::record_id::RecordId;
+
+/// The operations the engine needs from the structure an index file holds.
+pub trait IndexStructure {
+    fn insert(&mut self, entry: Entry) -> io::Result<()>;
+
+    fn remove(&mut self, entry: &Entry) -> io::Result<bool>;
+
+    fn scan(&mut self, key_range: &metadata::KeyRange) -> io::Result<Vec<Entry>>;
+
+    fn entries(&mut self) -> io::Result<Vec<Entry>>;
+
+    /// Describes every block of the file whose page header or checksum is wrong.
+    fn check_blocks(&self) -> Vec<String>;
+}
+
+impl IndexStructure for BPlusTree {
+    fn insert(&mut self, entry: Entry) -> io::Result<()> {
+        BPlusTree::insert(self, entry)
+    }
+
+    fn remove(&mut self, entry: &Entry) -> io::Result<bool> {
+        BPlusTree::remove(self, entry)
+    }
+
+    fn scan(&mut self, key_range: &metadata::KeyRange) -> io::Result<Vec<Entry>> {
+        BPlusTree::scan(self, key_range)
+    }
+
+    fn entries(&mut self) -> io::Result<Vec<Entry>> {
+        BPlusTree::scan(
+            self,
+            &metadata::KeyRange {
+                prefix: vec![],
+                last: None,
+            },
+        )
+    }
+
+    fn check_blocks(&self) -> Vec<String> {
+        BPlusTree::check_nodes(self)
+    }
+}
+
+impl IndexStructure for HashIndex {
+    fn insert(&mut self, entry: Entry) -> io::Result<()> {
+        HashIndex::insert(self, entry)
+    }
+
+    fn remove(&mut self, entry: &Entry) -> io::Result<bool> {
+        HashIndex::remove(self, entry)
+    }
+
+    fn scan(&mut self, key_range: &metadata::KeyRange) -> io::Result<Vec<Entry>> {
+        HashIndex::scan(self, key_range)
+    }
+
+    fn entries(&mut self) -> io::Result<Vec<Entry>> {
+        HashIndex::entries(self)
+    }
+
+    fn check_blocks(&self) -> Vec<String> {
+        HashIndex::check_blocks(self)
+    }
+}
+
+pub struct IndexManager {
+    file_paths: Arc<FilePaths>,
+    pager: Arc<Pager>,
+}
+
+impl IndexManager {
+    pub fn new(file_paths: Arc<FilePaths>, pager: Arc<Pager>) -> Self {
+        IndexManager { file_paths, pager }
+    }
+
+    pub fn create_index_file(&self, table_name: &str, index: &metadata::Index) -> io::Result<()> {
+        let path = self.file_paths.index_path(table_name, &index.name);
+
+        self.pager.atomically(|| match index.index_type {
+            metadata::IndexType::BTree => BPlusTree::create(&self.pager, &path),
+            metadata::IndexType::Hash => HashIndex::create(&self.pager, &path),
+        })
+    }
+
+    pub fn delete_index_file(&self, table_name: &str, index: &metadata::Index) -> io::Result<()> {
+        self.pager.atomically(|| {
+            self.pager
+                .remove(&self.file_paths.index_path(table_name, &index.name))
+        })
+    }
+
+    /// Adds the record to the index and returns its key, or None if the index does not hold the record.
+    pub fn insert(
+        &self,
+        table_name: &str,
+        index: &metadata::Index,
+        record: &DynamicRecord,
+        record_id: RecordId,
+    ) -> io::Result<Option<Vec<metadata::IndexKey>>> {
+        let Some(key) = index.key_of(record) else {
+            return Ok(None);
+        };
+
+        let mut entry =
+            Entry::with_included(key.clone(), record_id, index.included_values_of(record));
+
+        // included values too large for an entry are read from the data pages instead
+        if serialized_size(&entry) > MAX_ENTRY_SIZE {
+            entry.included.clear();
+        }
+
+        self.pager
+            .atomically(|| self.open(table_name, index)?.insert(entry))?;
+
+        Ok(Some(key))
+    }
+
+    pub fn remove(
+        &self,
+        table_name: &str,
+        index: &metadata::Index,
+        key: Vec<metadata::IndexKey>,
+        record_id: RecordId,
+    ) -> io::Result<bool> {
+        self.pager.atomically(|| {
+            self.open(table_name, index)?
+                .remove(&Entry::new(key, record_id))
+        })
+    }
+
+    pub fn scan(
+        &self,
+        table_name: &str,
+        index: &metadata::Index,
+        key_range: &metadata::KeyRange,
+    ) -> io::Result<Vec<RecordId>> {
+        Ok(self
+            .scan_entries(table_name, index, key_range)?
+            .iter()
+            .map(|entry| entry.record_id)
+            .collect())
+    }
+
+    pub fn scan_entries(
+        &self,
+        table_name: &str,
+        index: &metadata::Index,
+        key_range: &metadata::KeyRange,
+    ) -> io::Result<Vec<Entry>> {
+        // commits must not change the nodes between the reads of one traversal
+        let _guard = self
+            .pager
+            .lock_for_read(&self.file_paths.table_path(table_name));
+
+        self.open(table_name, index)?.scan(key_range)
+    }
+
+    /// Lists every entry of the index, in key order for B+trees.
+    pub fn entries(&self, table_name: &str, index: &metadata::Index) -> io::Result<Vec<Entry>> {
+        let _guard = self
+            .pager
+            .lock_for_read(&self.file_paths.table_path(table_name));
+
+        self.open(table_name, index)?.entries()
+    }
+
+    pub fn check_blocks(
+        &self,
+        table_name: &str,
+        index: &metadata::Index,
+    ) -> io::Result<Vec<String>> {
+        Ok(self.open(table_name, index)?.check_blocks())
+    }
+
+    fn open(
+        &self,
+        table_name: &str,
+        index: &metadata::Index,
+    ) -> io::Result<Box<dyn IndexStructure>> {
+        let path = self.file_paths.index_path(table_name, &index.name);
+
+        Ok(match index.index_type {
+            metadata::IndexType::BTree => Box::new(BPlusTree::open(&self.pager, &path)?),
+            metadata::IndexType::Hash => Box::new(HashIndex::open(&self.pager, &path)?),
+        })
+    }
+}
@@ -1,6 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 use crate::engine::{dynamic_record, insert::Insert};
+use crate::metadata;
+use crate::utils::unquote;
 
 impl Insert for super::BinaryEngine {
     fn insert(
@@ -9,6 +11,7 @@ impl Insert for super::BinaryEngine {
         column_names: Vec<String>,
         values: Vec<Vec<String>>,
     ) -> super::EngineResult {
+        let _guard = self.lock_for_write(&table_name)?;
         let metadata = self.table_manager.load_meta_data(&table_name);
 
         if metadata.is_err() {
@@ -17,66 +20,155 @@ impl Insert for super::BinaryEngine {
 
         let mut metadata = metadata.unwrap();
 
+        self.collect_garbage(&mut metadata)
+            .map_err(|error| error.to_string())?;
+
+        let mut records = Vec::with_capacity(values.len());
+
         for value_vec in values {
             let mut dynamic_data = HashMap::new();
 
             for (index, column_name) in column_names.iter().enumerate() {
                 for metadata_column in &metadata.columns {
                     if metadata_column.name == column_name.as_str() {
-                        if metadata_column
+                        match metadata_column
                             .data_type
-                            .allows_value(value_vec[index].clone())
+                            .to_value(unquote(&value_vec[index]))
                         {
-                            dynamic_data.insert(
-                                column_name.to_string(),
-                                dynamic_record::Value::Text(value_vec[index].clone()),
-                            );
-                        } else {
-                            return Err(format!("Type does not allow {} value", value_vec[index]));
+                            Some(value) => {
+                                dynamic_data.insert(column_name.to_string(), value);
+                            }
+                            None => {
+                                return Err(format!(
+                                    "Type does not allow {} value",
+                                    value_vec[index]
+                                ))
+                            }
                         }
                     }
                 }
             }
 
             if !dynamic_data.contains_key(&metadata.primary_key.name) {
+                let primary_key = metadata.new_primary_key().to_string();
+
                 dynamic_data.insert(
                     metadata.primary_key.clone().name,
-                    dynamic_record::Value::Text(metadata.new_primary_key().to_string()),
+                    metadata
+                        .primary_key
+                        .data_type
+                        .to_value(&primary_key)
+                        .unwrap(),
                 );
             }
 
-            let record = dynamic_record::DynamicRecord::new(dynamic_data);
+            records.push(dynamic_record::DynamicRecord::new(dynamic_data));
+        }
 
-            let result = self.data_manager.save_record(record.clone(), &table_name);
+        self.check_unique_indices(&table_name, &metadata, &records)?;
 
-            if let Err(err) = &result {
-                return Err(err.to_string());
-            }
+        self.store_records(&metadata, records)?;
 
-            for index in metadata.indices.iter_mut() {
-                index.update_tree((
-                    record
-                        .fields
-                        .get(index.column_name.as_str())
-                        .unwrap()
-                        .to_string(),
-                    result.as_ref().unwrap().clone(),
-                ));
-            }
-        }
+        metadata.last_writer = self
+            .pager
+            .transaction_id()
+            .map_err(|error| error.to_string())?;
 
-        self.table_manager.store_meta_data(&metadata).unwrap();
+        self.table_manager
+            .store_meta_data(&metadata)
+            .map_err(|error| error.to_string())?;
 
         Ok(super::EngineResponse {
             table: None,
             records: None,
+            message: None,
         })
     }
 }
 
+impl super::BinaryEngine {
+    /// Stores the records as new versions and adds them to the indices of the table.
+    pub(super) fn store_records(
+        &self,
+        table: &metadata::Table,
+        records: Vec<dynamic_record::DynamicRecord>,
+    ) -> Result<(), String> {
+        for record in records {
+            let record_id = match self.data_manager.save_record(record.clone(), &table.name) {
+                Ok(record_id) => record_id,
+                Err(err) => return Err(err.to_string()),
+            };
+
+            let mut updated_indices = vec![];
+
+            for index in table.indices.iter() {
+                match self
+                    .index_manager
+                    .insert(&table.name, index, &record, record_id)
+                {
+                    Ok(Some(key)) => updated_indices.push((index, key)),
+                    Ok(None) => {}
+                    Err(err) => {
+                        for (index, key) in updated_indices {
+                            self.index_manager
+                                .remove(&table.name, index, key, record_id)
+                                .ok();
+                        }
+
+                        return Err(err.to_string());
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rejects the whole insert or update if a record would duplicate a key of a unique index,
+    /// either one of a stored version that is not deleted or one of another record of the same
+    /// statement. An update deletes the old versions of its rows first, so they do not count.
+    pub(super) fn check_unique_indices(
+        &self,
+        table_name: &str,
+        table: &metadata::Table,
+        records: &[dynamic_record::DynamicRecord],
+    ) -> Result<(), String> {
+        for index in table.indices.iter().filter(|index| index.unique) {
+            let mut new_keys = BTreeSet::new();
+
+            for key in records.iter().filter_map(|record| index.key_of(record)) {
+                let key_range = metadata::KeyRange {
+                    prefix: key.clone(),
+                    last: None,
+                };
+
+                let exists = self
+                    .index_manager
+                    .scan(table_name, index, &key_range)
+                    .and_then(|record_ids| {
+                        self.data_manager
+                            .contains_undeleted_version(table_name, record_ids)
+                    })
+                    .map_err(|err| err.to_string())?;
+
+                if exists || !new_keys.insert(key.clone()) {
+                    return Err(format!(
+                        "Duplicate key {} violates unique index '{}'.",
+                        metadata::IndexKey::format_key(&key),
+                        index.name
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::engine::{CreateTable, Insert};
+    use crate::engine::{CreateIndex, CreateTable, Insert, Select};
+    use crate::metadata::{Index, IndexColumn};
     use crate::{engine::binary_engine::BinaryEngine, io_test_context::FileTestContext};
 
     #[test]
@@ -131,4 +223,69 @@ mod tests {
             panic!()
         }
     }
+
+    #[test]
+    fn test_cannot_insert_duplicate_keys_into_a_unique_index() {
+        let context = FileTestContext::new();
+
+        let engine = BinaryEngine::new();
+
+        engine
+            .create_table(
+                context.table_name().to_string(),
+                vec![vec!["email".to_string(), "VARCHAR".to_string()]],
+            )
+            .unwrap();
+
+        engine
+            .create_index(
+                context.table_name().to_string(),
+                Index {
+                    unique: true,
+                    ..Index::new(String::from("email_index"), vec![IndexColumn::new("email")])
+                },
+            )
+            .unwrap();
+
+        engine
+            .insert(
+                context.table_name().to_string(),
+                vec!["email".to_string()],
+                vec![vec!["'john@mail.com'".to_string()]],
+            )
+            .unwrap();
+
+        assert_eq!(
+            engine
+                .insert(
+                    context.table_name().to_string(),
+                    vec!["email".to_string()],
+                    vec![
+                        vec!["'doe@mail.com'".to_string()],
+                        vec!["'john@mail.com'".to_string()],
+                    ],
+                )
+                .unwrap_err(),
+            "Duplicate key ('john@mail.com') violates unique index 'email_index'."
+        );
+
+        assert!(engine
+            .insert(
+                context.table_name().to_string(),
+                vec!["email".to_string()],
+                vec![
+                    vec!["'jane@mail.com'".to_string()],
+                    vec!["'jane@mail.com'".to_string()],
+                ],
+            )
+            .is_err());
+
+        let records = engine
+            .select(context.table_name().to_string(), vec![], vec![])
+            .unwrap()
+            .records
+            .unwrap();
+
+        assert_eq!(records.len(), 1);
+    }
 }
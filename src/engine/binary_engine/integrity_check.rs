@@ -0,0 +1,326 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::mvcc::RowVersion;
+use super::record_id::RecordId;
+use crate::dynamic_record::{DynamicRecord, Value};
+use crate::engine::integrity_check::IntegrityCheck;
+use crate::metadata::{self, IndexKey};
+
+impl IntegrityCheck for super::BinaryEngine {
+    fn integrity_check(&self, table_name: Option<String>) -> super::EngineResult {
+        let table_names = match table_name {
+            Some(table_name) => vec![table_name],
+            None => self
+                .table_manager
+                .table_names()
+                .map_err(|error| format!("Failed to list the tables: {}", error))?,
+        };
+
+        let mut records = vec![];
+
+        for table_name in table_names {
+            let _guard = self.lock_for_read(&table_name);
+
+            for problem in self.check_table(&table_name) {
+                records.push(DynamicRecord::new(HashMap::from([
+                    (String::from("table"), Value::Text(table_name.clone())),
+                    (String::from("problem"), Value::Text(problem)),
+                ])));
+            }
+        }
+
+        let message = match records.len() {
+            0 => String::from("No problems found."),
+            problems => format!("Found {} problems.", problems),
+        };
+
+        Ok(super::EngineResponse {
+            table: None,
+            records: Some(records),
+            message: Some(message),
+        })
+    }
+}
+
+impl super::BinaryEngine {
+    fn check_table(&self, table_name: &str) -> Vec<String> {
+        let table = match self.table_manager.load_meta_data(table_name) {
+            Ok(table) => table,
+            Err(error) => return vec![format!("Its metadata cannot be read: {}.", error)],
+        };
+
+        let mut problems = vec![];
+        let mut versions = BTreeMap::new();
+        let mut corrupted_pages = BTreeSet::new();
+
+        for data_page_index in 1..=self.data_manager.data_page_count(table_name) {
+            match self
+                .data_manager
+                .load_data_page(table_name, data_page_index)
+            {
+                Ok(page) => versions.extend(page),
+                Err(error) => {
+                    problems.push(error.to_string());
+                    corrupted_pages.insert(data_page_index);
+                }
+            }
+        }
+
+        problems.extend(check_primary_key(&table, &versions));
+
+        for index in &table.indices {
+            problems.extend(self.check_index(&table, index, &versions, &corrupted_pages));
+        }
+
+        problems
+    }
+
+    /// Checks the checksums of all blocks of the index, that every entry of the index points
+    /// at a version holding its key, and that every version belonging to the index has an entry.
+    fn check_index(
+        &self,
+        table: &metadata::Table,
+        index: &metadata::Index,
+        versions: &BTreeMap<RecordId, RowVersion>,
+        corrupted_pages: &BTreeSet<usize>,
+    ) -> Vec<String> {
+        let cannot_be_read =
+            |error| vec![format!("Index '{}' cannot be read: {}.", index.name, error)];
+
+        let corrupted_blocks = match self.index_manager.check_blocks(&table.name, index) {
+            Ok(corrupted_blocks) => corrupted_blocks,
+            Err(error) => return cannot_be_read(error),
+        };
+
+        if !corrupted_blocks.is_empty() {
+            return corrupted_blocks
+                .into_iter()
+                .map(|problem| format!("In index '{}': {}", index.name, problem))
+                .collect();
+        }
+
+        let entries = match self.index_manager.entries(&table.name, index) {
+            Ok(entries) => entries,
+            Err(error) => return cannot_be_read(error),
+        };
+
+        let mut problems = vec![];
+        let mut indexed = BTreeSet::new();
+
+        for entry in entries {
+            if corrupted_pages.contains(&entry.record_id.data_page) {
+                continue;
+            }
+
+            match versions.get(&entry.record_id) {
+                None => problems.push(format!(
+                    "Index '{}' points at record {} for key {}, which does not exist.",
+                    index.name,
+                    entry.record_id,
+                    IndexKey::format_key(&entry.key)
+                )),
+                Some(version) if index.key_of(&version.record).as_ref() != Some(&entry.key) => {
+                    problems.push(format!(
+                        "Index '{}' points at record {} for key {}, which the record does not hold.",
+                        index.name,
+                        entry.record_id,
+                        IndexKey::format_key(&entry.key)
+                    ))
+                }
+                Some(_) => {
+                    indexed.insert(entry.record_id);
+                }
+            }
+        }
+
+        for (record_id, version) in versions {
+            if index.key_of(&version.record).is_some() && !indexed.contains(record_id) {
+                problems.push(format!(
+                    "Record {} is missing from index '{}'.",
+                    record_id, index.name
+                ));
+            }
+        }
+
+        problems
+    }
+}
+
+/// Checks that newly generated primary keys cannot collide with stored ones.
+fn check_primary_key(
+    table: &metadata::Table,
+    versions: &BTreeMap<RecordId, RowVersion>,
+) -> Option<String> {
+    let largest_primary_key = versions
+        .values()
+        .filter_map(
+            |version| match version.record.fields.get(&table.primary_key.name) {
+                Some(Value::Int(primary_key)) => Some(*primary_key),
+                _ => None,
+            },
+        )
+        .max()?;
+
+    (largest_primary_key > table.latest_primary_key() as i64).then(|| {
+        format!(
+            "The latest primary key {} is below the largest stored primary key {}.",
+            table.latest_primary_key(),
+            largest_primary_key
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::engine::binary_engine::data_manager::DATA_PAGE_SIZE;
+    use crate::engine::binary_engine::{record_id::RecordId, BinaryEngine};
+    use crate::engine::{CreateTable, Insert, IntegrityCheck};
+    use crate::io_test_context::FileTestContext;
+    use crate::metadata::{IndexKey, Table};
+
+    fn problems(engine: &BinaryEngine, table_name: &str) -> Vec<String> {
+        engine
+            .integrity_check(Some(table_name.to_string()))
+            .unwrap()
+            .records
+            .unwrap()
+            .into_iter()
+            .map(|record| record.fields["problem"].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_reports_inconsistent_indices_and_primary_keys() {
+        let context = FileTestContext::new();
+        let engine = BinaryEngine::new();
+
+        engine
+            .create_table(
+                context.table_name().to_string(),
+                vec![vec!["name".to_string(), "VARCHAR".to_string()]],
+            )
+            .unwrap();
+
+        engine
+            .insert(
+                context.table_name().to_string(),
+                vec!["name".to_string()],
+                vec![vec!["'a'".to_string()], vec!["'b'".to_string()]],
+            )
+            .unwrap();
+
+        assert!(problems(&engine, context.table_name()).is_empty());
+
+        let table = engine
+            .table_manager
+            .load_meta_data(context.table_name())
+            .unwrap();
+        let primary_key_index = &table.indices[0];
+
+        engine
+            .index_manager
+            .remove(
+                context.table_name(),
+                primary_key_index,
+                vec![IndexKey::Int(1)],
+                RecordId::new(1, 0),
+            )
+            .unwrap();
+
+        // metadata as it was before any rows were inserted
+        engine
+            .table_manager
+            .store_meta_data(&Table::new(
+                context.table_name().to_string(),
+                vec![vec!["name".to_string(), "VARCHAR".to_string()]],
+            ))
+            .unwrap();
+
+        assert_eq!(
+            problems(&engine, context.table_name()),
+            vec![
+                "The latest primary key 0 is below the largest stored primary key 2.",
+                "Record (1, 0) is missing from index 'id_index'.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reports_corrupted_data_pages() {
+        let context = FileTestContext::new();
+        let engine = BinaryEngine::new();
+
+        engine
+            .create_table(
+                context.table_name().to_string(),
+                vec![vec!["name".to_string(), "VARCHAR".to_string()]],
+            )
+            .unwrap();
+
+        engine
+            .insert(
+                context.table_name().to_string(),
+                vec!["name".to_string()],
+                vec![vec!["'a'".to_string()]],
+            )
+            .unwrap();
+
+        fs::write(engine.file_paths.data_page(context.table_name(), 1), b"").unwrap();
+
+        assert_eq!(
+            problems(&engine, context.table_name()),
+            vec![format!(
+                "Data page 1 of table '{}' is corrupted: it is truncated to 0 bytes.",
+                context.table_name()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_reports_corrupted_overflow_pages_and_index_nodes() {
+        let context = FileTestContext::new();
+        let engine = BinaryEngine::new();
+        let flip_last_byte = |path: &str| {
+            let mut contents = fs::read(path).unwrap();
+            *contents.last_mut().unwrap() ^= 1;
+            fs::write(path, contents).unwrap();
+        };
+
+        engine
+            .create_table(
+                context.table_name().to_string(),
+                vec![vec!["name".to_string(), "VARCHAR".to_string()]],
+            )
+            .unwrap();
+
+        engine
+            .insert(
+                context.table_name().to_string(),
+                vec!["name".to_string()],
+                vec![vec!["a".repeat(DATA_PAGE_SIZE)]],
+            )
+            .unwrap();
+
+        flip_last_byte(&engine.file_paths.overflow_page(context.table_name(), 1));
+        flip_last_byte(
+            &engine
+                .file_paths
+                .index_path(context.table_name(), "id_index"),
+        );
+
+        assert_eq!(
+            problems(&engine, context.table_name()),
+            vec![
+                format!(
+                    "Overflow page 1 of table '{}' is corrupted: its checksum does not match.",
+                    context.table_name()
+                ),
+                String::from(
+                    "In index 'id_index': Index node 1 is corrupted: its checksum does not match."
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,499 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::ThreadId;
+
+/// A session is one thread using one pager, as several pagers of the same database may
+/// be open on the same thread.
+pub type SessionId = (u64, ThreadId);
+
+/// The locks held on the directory of one table.
+#[derive(Default)]
+struct TableLocks {
+    // the session whose transaction writes to the table
+    writer: Option<SessionId>,
+    // whether the writer also keeps new scans away and waits for the running ones
+    exclusive: bool,
+    // the sessions with queries scanning the table, counting the queries of each session
+    scanners: HashMap<SessionId, usize>,
+    // the sessions reading files of the table, counting nested reads of the same session
+    readers: HashMap<SessionId, usize>,
+    // the session applying a commit to the files of the table
+    applying: Option<SessionId>,
+}
+
+impl TableLocks {
+    fn is_unused(&self) -> bool {
+        self.writer.is_none()
+            && self.scanners.is_empty()
+            && self.readers.is_empty()
+            && self.applying.is_none()
+    }
+}
+
+/// A lock a session waits for.
+#[derive(Clone)]
+enum Wait {
+    Write(String),
+    Exclusive(String),
+    Scan(String),
+}
+
+#[derive(Default)]
+struct LockTable {
+    tables: HashMap<String, TableLocks>,
+    // the lock every blocked session waits for, whose holders are looked up when detecting
+    // deadlocks, so that a released lock never counts as waited for
+    waits_for: HashMap<SessionId, Wait>,
+}
+
+// the lock manager of every database opened by this process, keyed by the path of its log
+static LOCK_MANAGERS: Mutex<BTreeMap<String, Arc<LockManager>>> = Mutex::new(BTreeMap::new());
+
+/// Table-level locks shared by all sessions of all engines using the same database.
+///
+/// A write lock is held by at most one transaction per table and only released when the
+/// transaction ends. Reads never wait for writers, since uncommitted writes are private to
+/// their session, but a read of the files of a table waits while a commit is applied to them,
+/// and applying a commit waits for the reads in progress. Queries only keep scan locks while
+/// their cursors live, which nothing but writers that move rows, like VACUUM, wait for.
+pub struct LockManager {
+    lock_table: Mutex<LockTable>,
+    released: Condvar,
+}
+
+impl LockManager {
+    /// Opens the lock manager of the database whose log is at the path.
+    /// All engines of this process using the same database share it.
+    pub fn open(log_path: &str) -> Arc<Self> {
+        let mut lock_managers = LOCK_MANAGERS
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+
+        Arc::clone(
+            lock_managers
+                .entry(log_path.to_string())
+                .or_insert_with(|| Arc::new(LockManager::new())),
+        )
+    }
+
+    fn new() -> Self {
+        LockManager {
+            lock_table: Mutex::new(LockTable::default()),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Waits until the session holds the write lock of the table, failing instead of
+    /// waiting for a session that itself waits for this one.
+    pub fn lock_for_write(&self, session: SessionId, table_path: &str) -> io::Result<()> {
+        let mut lock_table = self.acquire(session, Wait::Write(table_path.to_string()))?;
+
+        lock_table
+            .tables
+            .entry(table_path.to_string())
+            .or_default()
+            .writer = Some(session);
+
+        Ok(())
+    }
+
+    /// Waits until the session holds the write lock of the table and no other session scans it,
+    /// keeping new scans away until the transaction ends.
+    pub fn lock_exclusively(&self, session: SessionId, table_path: &str) -> io::Result<()> {
+        self.lock_for_write(session, table_path)?;
+
+        self.lock()
+            .tables
+            .entry(table_path.to_string())
+            .or_default()
+            .exclusive = true;
+
+        self.acquire(session, Wait::Exclusive(table_path.to_string()))
+            .map(|_| ())
+    }
+
+    /// Releases all write locks of the session.
+    pub fn release_writes(&self, session: SessionId) {
+        let mut lock_table = self.lock();
+
+        for table in lock_table.tables.values_mut() {
+            if table.writer == Some(session) {
+                table.writer = None;
+                table.exclusive = false;
+            }
+        }
+
+        lock_table.prune();
+        self.released.notify_all();
+    }
+
+    /// Waits until no other session holds the table exclusively, keeping such sessions
+    /// away from it while the guard lives.
+    pub fn lock_for_scan(&self, session: SessionId, table_path: &str) -> io::Result<ScanGuard<'_>> {
+        let mut lock_table = self.acquire(session, Wait::Scan(table_path.to_string()))?;
+
+        *lock_table
+            .tables
+            .entry(table_path.to_string())
+            .or_default()
+            .scanners
+            .entry(session)
+            .or_default() += 1;
+
+        Ok(ScanGuard {
+            lock_manager: self,
+            session,
+            table_path: table_path.to_string(),
+        })
+    }
+
+    /// Waits until no other session applies a commit to the table, keeping commits
+    /// away from it while the guard lives.
+    pub fn lock_for_read(&self, session: SessionId, table_path: &str) -> ReadGuard<'_> {
+        let mut lock_table = self.lock();
+
+        // applying a commit only waits for reads in progress, which wait for nothing else,
+        // so this wait cannot close a cycle and is left out of deadlock detection
+        while lock_table
+            .tables
+            .get(table_path)
+            .and_then(|table| table.applying)
+            .is_some_and(|applying| applying != session)
+        {
+            lock_table = self.released.wait(lock_table).unwrap();
+        }
+
+        *lock_table
+            .tables
+            .entry(table_path.to_string())
+            .or_default()
+            .readers
+            .entry(session)
+            .or_default() += 1;
+
+        ReadGuard {
+            lock_manager: self,
+            session,
+            table_path: table_path.to_string(),
+        }
+    }
+
+    /// Waits until no other session reads or applies to any of the tables,
+    /// keeping them to this session while the guard lives.
+    pub fn lock_for_apply(
+        &self,
+        session: SessionId,
+        table_paths: &BTreeSet<String>,
+    ) -> ApplyGuard<'_> {
+        let mut lock_table = self.lock();
+
+        while table_paths.iter().any(|table_path| {
+            lock_table.tables.get(table_path).is_some_and(|table| {
+                table.applying.is_some() || table.readers.keys().any(|reader| *reader != session)
+            })
+        }) {
+            lock_table = self.released.wait(lock_table).unwrap();
+        }
+
+        for table_path in table_paths {
+            lock_table
+                .tables
+                .entry(table_path.to_string())
+                .or_default()
+                .applying = Some(session);
+        }
+
+        ApplyGuard {
+            lock_manager: self,
+            table_paths: table_paths.clone(),
+        }
+    }
+
+    /// Waits until no other session holds the lock, failing instead of waiting for
+    /// a session that itself waits for this one.
+    fn acquire(&self, session: SessionId, wait: Wait) -> io::Result<MutexGuard<'_, LockTable>> {
+        let mut lock_table = self.lock();
+
+        loop {
+            let holders = lock_table.holders(session, &wait);
+
+            if holders.is_empty() {
+                lock_table.waits_for.remove(&session);
+
+                return Ok(lock_table);
+            }
+
+            if lock_table.deadlocks(session, holders) {
+                lock_table.waits_for.remove(&session);
+
+                for table in lock_table.tables.values_mut() {
+                    if table.writer == Some(session) {
+                        table.exclusive = false;
+                    }
+                }
+
+                self.released.notify_all();
+
+                let action = match &wait {
+                    Wait::Write(table_path) => format!("write '{}'", table_path),
+                    Wait::Exclusive(table_path) => format!("write '{}' exclusively", table_path),
+                    Wait::Scan(table_path) => format!("read '{}'", table_path),
+                };
+
+                return Err(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    format!(
+                        "Deadlock detected while waiting to {}, the transaction was rolled back.",
+                        action
+                    ),
+                ));
+            }
+
+            lock_table.waits_for.insert(session, wait.clone());
+            lock_table = self.released.wait(lock_table).unwrap();
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LockTable> {
+        self.lock_table
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+impl LockTable {
+    /// The other sessions holding the lock, which the session has to wait for.
+    fn holders(&self, session: SessionId, wait: &Wait) -> Vec<SessionId> {
+        let (Wait::Write(table_path) | Wait::Exclusive(table_path) | Wait::Scan(table_path)) = wait;
+
+        let Some(table) = self.tables.get(table_path) else {
+            return vec![];
+        };
+
+        let holders: Vec<SessionId> = match wait {
+            Wait::Write(_) => table.writer.into_iter().collect(),
+            Wait::Exclusive(_) => table.scanners.keys().copied().collect(),
+            // the queries of a session never wait for each other
+            Wait::Scan(_) if table.exclusive && !table.scanners.contains_key(&session) => {
+                table.writer.into_iter().collect()
+            }
+            Wait::Scan(_) => vec![],
+        };
+
+        holders
+            .into_iter()
+            .filter(|holder| *holder != session)
+            .collect()
+    }
+
+    /// Whether the session would close a cycle by waiting for the holders.
+    fn deadlocks(&self, session: SessionId, holders: Vec<SessionId>) -> bool {
+        let mut waiting = holders;
+        let mut visited = HashSet::new();
+
+        while let Some(next) = waiting.pop() {
+            if next == session {
+                return true;
+            }
+
+            if visited.insert(next) {
+                if let Some(wait) = self.waits_for.get(&next) {
+                    waiting.extend(self.holders(next, wait));
+                }
+            }
+        }
+
+        false
+    }
+
+    fn prune(&mut self) {
+        self.tables.retain(|_, table| !table.is_unused());
+    }
+}
+
+pub struct ScanGuard<'a> {
+    lock_manager: &'a LockManager,
+    session: SessionId,
+    table_path: String,
+}
+
+impl Drop for ScanGuard<'_> {
+    fn drop(&mut self) {
+        let mut lock_table = self.lock_manager.lock();
+
+        if let Some(table) = lock_table.tables.get_mut(&self.table_path) {
+            if let Some(count) = table.scanners.get_mut(&self.session) {
+                *count -= 1;
+
+                if *count == 0 {
+                    table.scanners.remove(&self.session);
+                }
+            }
+        }
+
+        lock_table.prune();
+        self.lock_manager.released.notify_all();
+    }
+}
+
+pub struct ReadGuard<'a> {
+    lock_manager: &'a LockManager,
+    session: SessionId,
+    table_path: String,
+}
+
+impl Drop for ReadGuard<'_> {
+    fn drop(&mut self) {
+        let mut lock_table = self.lock_manager.lock();
+
+        if let Some(table) = lock_table.tables.get_mut(&self.table_path) {
+            if let Some(count) = table.readers.get_mut(&self.session) {
+                *count -= 1;
+
+                if *count == 0 {
+                    table.readers.remove(&self.session);
+                }
+            }
+        }
+
+        lock_table.prune();
+        self.lock_manager.released.notify_all();
+    }
+}
+
+pub struct ApplyGuard<'a> {
+    lock_manager: &'a LockManager,
+    table_paths: BTreeSet<String>,
+}
+
+impl Drop for ApplyGuard<'_> {
+    fn drop(&mut self) {
+        let mut lock_table = self.lock_manager.lock();
+
+        for table_path in &self.table_paths {
+            if let Some(table) = lock_table.tables.get_mut(table_path) {
+                table.applying = None;
+            }
+        }
+
+        lock_table.prune();
+        self.lock_manager.released.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    use super::*;
+
+    fn session() -> SessionId {
+        (0, thread::current().id())
+    }
+
+    #[test]
+    fn test_detects_deadlocks_between_writers() {
+        let locks = Arc::new(LockManager::new());
+        let barrier = Arc::new(Barrier::new(2));
+
+        let other = {
+            let locks = Arc::clone(&locks);
+            let barrier = Arc::clone(&barrier);
+
+            thread::spawn(move || {
+                locks.lock_for_write(session(), "b").unwrap();
+                barrier.wait();
+
+                let result = locks.lock_for_write(session(), "a");
+                locks.release_writes(session());
+
+                result
+            })
+        };
+
+        locks.lock_for_write(session(), "a").unwrap();
+        barrier.wait();
+
+        // one of both sessions has to give up, which lets the other one go on
+        let result = locks.lock_for_write(session(), "b");
+        locks.release_writes(session());
+
+        let other_result = other.join().unwrap();
+
+        assert!(result.is_err() != other_result.is_err());
+    }
+
+    #[test]
+    fn test_released_locks_do_not_count_as_waited_for() {
+        let locks = LockManager::new();
+        let (first, second) = ((0, thread::current().id()), (1, thread::current().id()));
+
+        locks.lock_for_write(first, "x").unwrap();
+        locks.lock_for_write(second, "y").unwrap();
+
+        // the second session waited for "x" and was woken up by the release, but did not run yet
+        locks
+            .lock()
+            .waits_for
+            .insert(second, Wait::Write(String::from("x")));
+        locks.release_writes(first);
+
+        assert!(!locks.lock().deadlocks(first, vec![second]));
+    }
+
+    #[test]
+    fn test_applying_waits_for_readers_of_other_sessions() {
+        let locks = Arc::new(LockManager::new());
+        let read = locks.lock_for_read(session(), "a");
+        let scan = locks.lock_for_scan(session(), "a").unwrap();
+
+        let applier = {
+            let locks = Arc::clone(&locks);
+
+            thread::spawn(move || {
+                let _guard = locks.lock_for_apply(session(), &BTreeSet::from([String::from("a")]));
+            })
+        };
+
+        thread::sleep(std::time::Duration::from_millis(50));
+        assert!(!applier.is_finished());
+
+        // scans do not keep commits away, only the reads in progress do
+        drop(read);
+        applier.join().unwrap();
+        drop(scan);
+    }
+
+    #[test]
+    fn test_detects_deadlocks_between_scanners_and_exclusive_writers() {
+        let locks = Arc::new(LockManager::new());
+        let barrier = Arc::new(Barrier::new(2));
+
+        let scanner = {
+            let locks = Arc::clone(&locks);
+            let barrier = Arc::clone(&barrier);
+
+            thread::spawn(move || {
+                let _scan = locks.lock_for_scan(session(), "a").unwrap();
+                barrier.wait();
+                barrier.wait();
+
+                locks.lock_for_scan(session(), "b").map(|_| ())
+            })
+        };
+
+        barrier.wait();
+        locks.lock_exclusively(session(), "b").unwrap();
+        barrier.wait();
+
+        // the scan of "b" waits for this session, which waits for the scan of "a" to end
+        let result = locks.lock_exclusively(session(), "a");
+        locks.release_writes(session());
+
+        let scanner_result = scanner.join().unwrap();
+
+        assert!(result.is_err() != scanner_result.is_err());
+    }
+}
@@ -1,38 +1,153 @@
+mod analyze;
+mod b_plus_tree;
+mod checkpoint;
 mod create_index;
 mod create_table;
 mod data_manager;
+mod database_lock;
+mod delete;
+mod executor;
+mod explain;
+mod format_version;
+mod hash_index;
+mod index_manager;
 mod insert;
+mod integrity_check;
+mod lock_manager;
+mod mvcc;
+mod overflow_manager;
+mod page;
+mod pager;
+mod planner;
+mod record_id;
 mod select;
 mod table_manager;
+mod transaction;
+mod update;
 pub mod utils;
+mod vacuum;
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use data_manager::DataManager;
+use executor::WorkMemory;
+use index_manager::IndexManager;
+use lock_manager::{ReadGuard, ScanGuard};
+use pager::Pager;
 use table_manager::TableManager;
 
 use super::Engine;
 use super::EngineResponse;
 use super::EngineResult;
+use super::OpenOptions;
 
 pub struct BinaryEngine {
-    file_paths: Rc<super::file_paths::FilePaths>,
+    file_paths: Arc<super::file_paths::FilePaths>,
+    read_only: bool,
+    work_memory: usize,
+    pager: Arc<Pager>,
     data_manager: DataManager,
     table_manager: TableManager,
+    index_manager: IndexManager,
 }
 
 impl BinaryEngine {
+    #[cfg(test)]
     pub fn new() -> Self {
-        let file_paths = Rc::new(FilePaths::new());
-        let table_manager = TableManager::new(Rc::clone(&file_paths));
-        let data_manager = DataManager::new(Rc::clone(&file_paths));
+        Self::open(&OpenOptions::from_env()).expect("\tFailed to open the database.")
+    }
+
+    /// Opens the database, holding a lock that keeps other processes from writing to it.
+    pub fn open(options: &OpenOptions) -> Result<Self, String> {
+        let file_paths = Arc::new(match &options.directory {
+            Some(directory) => FilePaths::at(directory),
+            None => FilePaths::new(),
+        });
+
+        let pager = database_lock::open_locked(&file_paths.lock_path(), options, |exclusive| {
+            format_version::check(&file_paths, !options.read_only)?;
+
+            let (log_path, transaction_ids_path) =
+                (file_paths.wal_path(), file_paths.transaction_ids_path());
 
-        BinaryEngine {
+            match (options.read_only, exclusive) {
+                (false, _) => Pager::open(&log_path, &transaction_ids_path),
+                (true, recover) => Pager::open_read_only(&log_path, &transaction_ids_path, recover),
+            }
+        })
+        .map_err(|error| format!("Failed to open the database: {}", error))?;
+
+        let pager = Arc::new(pager);
+        let table_manager = TableManager::new(Arc::clone(&file_paths), Arc::clone(&pager));
+        let data_manager = DataManager::new(Arc::clone(&file_paths), Arc::clone(&pager));
+        let index_manager = IndexManager::new(Arc::clone(&file_paths), Arc::clone(&pager));
+
+        Ok(BinaryEngine {
             file_paths,
+            read_only: options.read_only,
+            work_memory: options.work_memory,
+            pager,
             table_manager,
             data_manager,
+            index_manager,
+        })
+    }
+
+    fn check_writable(&self) -> Result<(), String> {
+        if self.read_only {
+            return Err(String::from("The database is opened read-only."));
+        }
+
+        Ok(())
+    }
+
+    /// How much memory every sort and aggregation of a query may use, and where it spills the rest.
+    fn work_memory(&self) -> WorkMemory {
+        WorkMemory {
+            limit: self.work_memory,
+            directory: self.file_paths.temp_path(),
         }
     }
+
+    /// Locks the table for writing until the transaction ends, and for reading while the guard lives.
+    fn lock_for_write(&self, table_name: &str) -> Result<ReadGuard<'_>, String> {
+        self.check_writable()?;
+
+        let table_path = self.file_paths.table_path(table_name);
+
+        self.pager
+            .lock_for_write(&table_path)
+            .map_err(|error| error.to_string())?;
+
+        Ok(self.pager.lock_for_read(&table_path))
+    }
+
+    /// Like `lock_for_write`, but also waits for the queries of other sessions reading the table
+    /// to end and keeps new ones away until the transaction ends.
+    fn lock_exclusively(&self, table_name: &str) -> Result<ReadGuard<'_>, String> {
+        self.check_writable()?;
+
+        let table_path = self.file_paths.table_path(table_name);
+
+        self.pager
+            .lock_exclusively(&table_path)
+            .map_err(|error| error.to_string())?;
+
+        Ok(self.pager.lock_for_read(&table_path))
+    }
+
+    /// Keeps commits of other sessions from changing the files of the table while the guard lives.
+    fn lock_for_read(&self, table_name: &str) -> ReadGuard<'_> {
+        self.pager
+            .lock_for_read(&self.file_paths.table_path(table_name))
+    }
+
+    /// Keeps other sessions from moving the rows of the table while the guard lives.
+    fn lock_for_scan(&self, table_name: &str) -> Result<ScanGuard<'_>, String> {
+        self.pager
+            .lock_for_scan(&self.file_paths.table_path(table_name))
+            .map_err(|error| error.to_string())
+    }
 }
 
 impl Engine for BinaryEngine {}
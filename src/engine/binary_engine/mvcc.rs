@@ -0,0 +1,308 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+
+use crate::dynamic_record::DynamicRecord;
+
+pub type TransactionId = u64;
+
+// the ids handed out are reserved in blocks, so that the file only changes every so often
+const RESERVED_IDS: TransactionId = 1024;
+
+/// A version of a row, stamped with the transaction that inserted it
+/// and, once it is deleted, with the transaction that deleted it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RowVersion<R = DynamicRecord> {
+    pub xmin: TransactionId,
+    pub xmax: Option<TransactionId>,
+    pub record: R,
+}
+
+impl<R> RowVersion<R> {
+    pub fn new(xmin: TransactionId, record: R) -> Self {
+        RowVersion {
+            xmin,
+            xmax: None,
+            record,
+        }
+    }
+}
+
+/// The transactions whose changes a reader sees: all that committed before the snapshot was
+/// taken, together with its own. Only committed versions ever reach the data pages, so any
+/// transaction that is neither in progress nor started after the snapshot has committed.
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    own: Option<TransactionId>,
+    // the first id that was not handed out yet
+    xmax: TransactionId,
+    active: BTreeSet<TransactionId>,
+}
+
+impl Snapshot {
+    /// Whether another transaction had committed when the snapshot was taken.
+    pub fn sees_commit_of(&self, id: TransactionId) -> bool {
+        id < self.xmax && !self.active.contains(&id)
+    }
+
+    /// Whether the changes of the transaction are visible, either as its own or as committed ones.
+    pub fn sees_changes_of(&self, id: TransactionId) -> bool {
+        Some(id) == self.own || self.sees_commit_of(id)
+    }
+
+    pub fn sees<R>(&self, version: &RowVersion<R>) -> bool {
+        self.sees_changes_of(version.xmin)
+            && !version.xmax.is_some_and(|xmax| self.sees_changes_of(xmax))
+    }
+}
+
+struct TransactionState {
+    // whether the reserved ids are written to the file, which read-only processes never do
+    persistent: bool,
+    next_id: TransactionId,
+    reserved_until: TransactionId,
+    // every transaction in progress, with the oldest transaction its snapshots may not see
+    active: BTreeMap<TransactionId, TransactionId>,
+    // the horizons held by cursors that outlive their statement, counting the holds of each
+    held: BTreeMap<TransactionId, usize>,
+}
+
+impl TransactionState {
+    fn horizon(&self) -> TransactionId {
+        self.active
+            .values()
+            .chain(self.held.keys())
+            .copied()
+            .min()
+            .unwrap_or(self.next_id)
+    }
+}
+
+// the transaction manager of every database opened by this process, keyed by the path of its id file
+static TRANSACTION_MANAGERS: Mutex<BTreeMap<String, Arc<TransactionManager>>> =
+    Mutex::new(BTreeMap::new());
+
+/// Hands out transaction ids and takes snapshots of the transactions in progress.
+pub struct TransactionManager {
+    path: String,
+    state: Mutex<TransactionState>,
+}
+
+impl TransactionManager {
+    /// Opens the transaction manager of the database whose ids are reserved in the file at the path.
+    /// All engines of this process using the same database share it.
+    pub fn open(path: &str, persistent: bool) -> io::Result<Arc<Self>> {
+        let mut transaction_managers = TRANSACTION_MANAGERS
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+
+        if let Some(transaction_manager) = transaction_managers.get(path) {
+            let mut state = transaction_manager.lock();
+
+            // the process writes to the database from now on, so the ids it hands out have to last
+            if persistent && !state.persistent {
+                state.persistent = true;
+                state.next_id = state.next_id.max(read_reserved_until(path)?);
+                state.reserved_until = state.next_id;
+            }
+
+            drop(state);
+
+            return Ok(Arc::clone(transaction_manager));
+        }
+
+        let reserved_until = read_reserved_until(path)?;
+
+        let transaction_manager = Arc::new(TransactionManager {
+            path: path.to_string(),
+            state: Mutex::new(TransactionState {
+                persistent,
+                next_id: reserved_until,
+                reserved_until,
+                active: BTreeMap::new(),
+                held: BTreeMap::new(),
+            }),
+        });
+
+        transaction_managers.insert(path.to_string(), Arc::clone(&transaction_manager));
+
+        Ok(transaction_manager)
+    }
+
+    pub fn start(&self) -> io::Result<TransactionId> {
+        let mut state = self.lock();
+
+        if state.next_id == state.reserved_until {
+            let reserved_until = state.reserved_until + RESERVED_IDS;
+
+            if state.persistent {
+                self.reserve(reserved_until)?;
+            }
+
+            state.reserved_until = reserved_until;
+        }
+
+        let id = state.next_id;
+        state.next_id += 1;
+        state.active.insert(id, id);
+
+        Ok(id)
+    }
+
+    pub fn snapshot(&self, own: Option<TransactionId>) -> Snapshot {
+        let mut state = self.lock();
+
+        let active: BTreeSet<TransactionId> = state.active.keys().copied().collect();
+        let oldest_unseen = active.first().copied().unwrap_or(state.next_id);
+
+        if let Some(horizon) = own.and_then(|own| state.active.get_mut(&own)) {
+            *horizon = (*horizon).min(oldest_unseen);
+        }
+
+        Snapshot {
+            own,
+            xmax: state.next_id,
+            active,
+        }
+    }
+
+    pub fn end(&self, id: TransactionId) {
+        self.lock().active.remove(&id);
+    }
+
+    /// The oldest transaction that some snapshot may still not see. Versions deleted by
+    /// transactions before it are invisible to every current and future snapshot.
+    pub fn horizon(&self) -> TransactionId {
+        self.lock().horizon()
+    }
+
+    /// Keeps the horizon from moving past where it is now while the guard lives. Every snapshot
+    /// taken meanwhile sees all transactions before it, so none of the versions it sees can be collected.
+    pub fn hold_horizon(self: &Arc<Self>) -> HorizonGuard {
+        let mut state = self.lock();
+
+        let horizon = state.horizon();
+        *state.held.entry(horizon).or_default() += 1;
+
+        HorizonGuard {
+            transaction_manager: Arc::clone(self),
+            horizon,
+        }
+    }
+
+    /// Durably records that all ids below the given one may be in use.
+    fn reserve(&self, reserved_until: TransactionId) -> io::Result<()> {
+        let temporary_path = format!("{}.tmp", self.path);
+
+        let mut file = fs::File::create(&temporary_path)?;
+        io::Write::write_all(&mut file, &bincode::serialize(&reserved_until).unwrap())?;
+        file.sync_all()?;
+
+        fs::rename(&temporary_path, &self.path)?;
+
+        match Path::new(&self.path).parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => fs::File::open(parent)?.sync_all(),
+            _ => Ok(()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TransactionState> {
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+pub struct HorizonGuard {
+    transaction_manager: Arc<TransactionManager>,
+    horizon: TransactionId,
+}
+
+impl Drop for HorizonGuard {
+    fn drop(&mut self) {
+        let mut state = self.transaction_manager.lock();
+
+        if let Some(count) = state.held.get_mut(&self.horizon) {
+            *count -= 1;
+
+            if *count == 0 {
+                state.held.remove(&self.horizon);
+            }
+        }
+    }
+}
+
+// ids up to the reserved one may have been handed out before a crash, so they are skipped
+fn read_reserved_until(path: &str) -> io::Result<TransactionId> {
+    match fs::read(path) {
+        Ok(contents) => bincode::deserialize(&contents).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("'{}' is corrupted.", path),
+            )
+        }),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(1),
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn version(xmin: TransactionId, xmax: Option<TransactionId>) -> RowVersion {
+        RowVersion {
+            xmin,
+            xmax,
+            record: DynamicRecord::new(HashMap::new()),
+        }
+    }
+
+    #[test]
+    fn test_snapshots_only_see_transactions_committed_before_them() {
+        let snapshot = Snapshot {
+            own: Some(7),
+            xmax: 9,
+            active: BTreeSet::from([5, 7]),
+        };
+
+        assert!(snapshot.sees(&version(3, None)));
+        assert!(snapshot.sees(&version(7, None)));
+        assert!(!snapshot.sees(&version(5, None)));
+        assert!(!snapshot.sees(&version(9, None)));
+
+        assert!(!snapshot.sees(&version(3, Some(4))));
+        assert!(!snapshot.sees(&version(3, Some(7))));
+        assert!(snapshot.sees(&version(3, Some(5))));
+        assert!(snapshot.sees(&version(3, Some(10))));
+    }
+
+    #[test]
+    fn test_the_horizon_waits_for_the_oldest_snapshot() {
+        let dir = std::env::temp_dir().join(format!("transaction_ids_{}.bin", std::process::id()));
+        let transactions = TransactionManager::open(dir.to_str().unwrap(), true).unwrap();
+
+        let reader = transactions.start().unwrap();
+        let writer = transactions.start().unwrap();
+        transactions.snapshot(Some(writer));
+
+        // the writer saw the reader in progress, so it holds the horizon back even after the reader ends
+        transactions.end(reader);
+        assert_eq!(transactions.horizon(), reader);
+
+        // a cursor outliving the writer keeps the horizon until it is dropped
+        let cursor = transactions.hold_horizon();
+
+        transactions.end(writer);
+        assert_eq!(transactions.horizon(), reader);
+
+        drop(cursor);
+        assert_eq!(transactions.horizon(), writer + 1);
+
+        fs::remove_file(dir).unwrap();
+    }
+}
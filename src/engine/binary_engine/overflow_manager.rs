@@ -0,0 +1,123 @@
+use std::io;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::page::{OVERFLOW_PAGE, PAGE_HEADER_SIZE};
+use super::pager::Pager;
+use crate::engine::file_paths::FilePaths;
+
+pub const OVERFLOW_PAGE_SIZE: usize = 16000;
+
+// the page header, followed by the bincode header of an overflow page:
+// Option<usize> (1 + 8 bytes) and the length of `data` (8 bytes)
+const OVERFLOW_PAGE_HEADER_SIZE: usize = PAGE_HEADER_SIZE + 17;
+const OVERFLOW_CHUNK_SIZE: usize = OVERFLOW_PAGE_SIZE - OVERFLOW_PAGE_HEADER_SIZE;
+
+#[derive(Serialize, Deserialize)]
+struct OverflowPage {
+    next: Option<usize>,
+    data: Vec<u8>,
+}
+
+pub struct OverflowManager {
+    file_paths: Arc<FilePaths>,
+    pager: Arc<Pager>,
+}
+
+impl OverflowManager {
+    pub fn new(file_paths: Arc<FilePaths>, pager: Arc<Pager>) -> Self {
+        OverflowManager { file_paths, pager }
+    }
+
+    /// Writes the payload into a chain of free overflow pages and returns the index of the first page.
+    pub fn store(&self, table_name: &str, payload: &[u8]) -> io::Result<usize> {
+        let chunks: Vec<&[u8]> = if payload.is_empty() {
+            vec![payload]
+        } else {
+            payload.chunks(OVERFLOW_CHUNK_SIZE).collect()
+        };
+
+        let page_indices = self.free_page_indices(table_name, chunks.len());
+
+        for (position, chunk) in chunks.iter().enumerate() {
+            let page = OverflowPage {
+                next: page_indices.get(position + 1).copied(),
+                data: chunk.to_vec(),
+            };
+
+            self.pager.write(
+                &self
+                    .file_paths
+                    .overflow_page(table_name, page_indices[position]),
+                OVERFLOW_PAGE.encode(&bincode::serialize(&page).unwrap()),
+            );
+        }
+
+        Ok(page_indices[0])
+    }
+
+    /// Follows the chain starting at `first_page_index` and reassembles the payload.
+    pub fn load(&self, table_name: &str, first_page_index: usize) -> io::Result<Vec<u8>> {
+        let mut payload = Vec::new();
+        let mut page_index = Some(first_page_index);
+
+        while let Some(index) = page_index {
+            let page = self.read_page(table_name, index)?;
+
+            payload.extend(page.data);
+            page_index = page.next;
+        }
+
+        Ok(payload)
+    }
+
+    /// Removes the pages of the chain starting at `first_page_index`, so that they can be reused.
+    pub fn free(&self, table_name: &str, first_page_index: usize) -> io::Result<()> {
+        let mut page_index = Some(first_page_index);
+
+        while let Some(index) = page_index {
+            page_index = self.read_page(table_name, index)?.next;
+
+            self.pager
+                .remove(&self.file_paths.overflow_page(table_name, index))?;
+        }
+
+        Ok(())
+    }
+
+    fn read_page(&self, table_name: &str, page_index: usize) -> io::Result<OverflowPage> {
+        let buffer = self
+            .pager
+            .read(&self.file_paths.overflow_page(table_name, page_index))?;
+
+        OVERFLOW_PAGE
+            .decode(&buffer)
+            .and_then(|payload| {
+                bincode::deserialize(payload)
+                    .map_err(|error| format!("its chunk is invalid ({})", error))
+            })
+            .map_err(|reason| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Overflow page {} of table '{}' is corrupted: {}.",
+                        page_index, table_name, reason
+                    ),
+                )
+            })
+    }
+
+    /// The smallest indices of pages that do not exist, either because they were never
+    /// written or because their chain was freed.
+    fn free_page_indices(&self, table_name: &str, count: usize) -> Vec<usize> {
+        (1..)
+            .filter(|page_index| {
+                !self
+                    .pager
+                    .exists(&self.file_paths.overflow_page(table_name, *page_index))
+            })
+            .take(count)
+            .collect()
+    }
+}
@@ -0,0 +1,93 @@
+use crate::utils::crc32c;
+
+/// Every page starts with the magic of its kind, the version of its format
+/// and the CRC-32C checksum of the rest of the page.
+pub const PAGE_HEADER_SIZE: usize = 12;
+const PAGE_FORMAT_VERSION: u32 = 2;
+
+pub struct PageKind {
+    magic: [u8; 4],
+    name: &'static str,
+}
+
+pub const DATA_PAGE: PageKind = PageKind {
+    magic: *b"RSDP",
+    name: "data page",
+};
+
+pub const OVERFLOW_PAGE: PageKind = PageKind {
+    magic: *b"RSOP",
+    name: "overflow page",
+};
+
+/// The nodes of B+trees as well as the blocks of hash indices.
+pub const INDEX_NODE: PageKind = PageKind {
+    magic: *b"RSIN",
+    name: "index node",
+};
+
+impl PageKind {
+    pub fn encode(&self, payload: &[u8]) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(PAGE_HEADER_SIZE + payload.len());
+        buffer.extend(self.magic);
+        buffer.extend(PAGE_FORMAT_VERSION.to_le_bytes());
+        buffer.extend(crc32c(payload).to_le_bytes());
+        buffer.extend(payload);
+
+        buffer
+    }
+
+    /// Checks the header of the page and returns the payload behind it,
+    /// or why the page is corrupted.
+    pub fn decode<'a>(&self, buffer: &'a [u8]) -> Result<&'a [u8], String> {
+        if buffer.len() < PAGE_HEADER_SIZE {
+            return Err(format!("it is truncated to {} bytes", buffer.len()));
+        }
+
+        let (header, payload) = buffer.split_at(PAGE_HEADER_SIZE);
+
+        if header[0..4] != self.magic {
+            return Err(format!("it does not start with the {} magic", self.name));
+        }
+
+        let version = u32::from_le_bytes(header[4..8].try_into().unwrap());
+
+        if version != PAGE_FORMAT_VERSION {
+            return Err(format!("its format version {} is not supported", version));
+        }
+
+        if crc32c(payload) != u32::from_le_bytes(header[8..12].try_into().unwrap()) {
+            return Err(String::from("its checksum does not match"));
+        }
+
+        Ok(payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_pages_of_another_kind_and_flipped_bits() {
+        let page = OVERFLOW_PAGE.encode(b"payload");
+
+        assert_eq!(OVERFLOW_PAGE.decode(&page), Ok(&b"payload"[..]));
+        assert_eq!(
+            DATA_PAGE.decode(&page),
+            Err(String::from("it does not start with the data page magic"))
+        );
+
+        let mut flipped = page.clone();
+        *flipped.last_mut().unwrap() ^= 1;
+
+        assert_eq!(
+            OVERFLOW_PAGE.decode(&flipped),
+            Err(String::from("its checksum does not match"))
+        );
+        assert_eq!(
+            OVERFLOW_PAGE.decode(&page[..5]),
+            Err(String::from("it is truncated to 5 bytes"))
+        );
+    }
+}
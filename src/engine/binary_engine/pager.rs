@@ -0,0 +1,819 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+
+use super::lock_manager::{LockManager, ReadGuard, ScanGuard, SessionId};
+use super::mvcc::{HorizonGuard, Snapshot, TransactionId, TransactionManager};
+use crate::sql_parser::query::IsolationLevel;
+use crate::write_ahead_log::{LogRecord, WriteAheadLog};
+
+/// A file as changed by writes that are not committed yet.
+#[derive(Clone)]
+enum PendingFile {
+    Removed,
+    /// The file is rewritten as a whole.
+    Replaced(Vec<u8>),
+    /// The committed file with the bytes written at some offsets, which never overlap or touch,
+    /// so that only the blocks written to are kept in memory.
+    Patched {
+        length: usize,
+        patches: BTreeMap<usize, Vec<u8>>,
+    },
+}
+
+/// A point inside a transaction that it can be rolled back to. Before a file changes for
+/// the first time after the savepoint, its pending state is kept so that it can be restored.
+struct Savepoint {
+    // statements are wrapped in savepoints without a name
+    name: Option<String>,
+    undo: BTreeMap<String, Option<PendingFile>>,
+}
+
+/// The transaction state of a session, i.e. of one thread using the engine.
+#[derive(Default)]
+struct Session {
+    pending: BTreeMap<String, PendingFile>,
+    in_transaction: bool,
+    savepoints: Vec<Savepoint>,
+    transaction_id: Option<TransactionId>,
+    isolation_level: IsolationLevel,
+    snapshot: Option<Snapshot>,
+    page_accesses: PageAccesses,
+}
+
+/// How often the session read a file or a block of one. There is no buffer cache, so every
+/// read either goes to disk or is answered from the pending writes the session keeps in memory.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct PageAccesses {
+    /// Reads of a whole data, overflow or metadata file, or of an index node, from disk.
+    pub disk_reads: u64,
+    /// Reads answered from the writes of the session that are not committed yet.
+    pub pending_write_hits: u64,
+}
+
+impl PageAccesses {
+    fn count(&mut self, cached: bool) {
+        match cached {
+            true => self.pending_write_hits += 1,
+            false => self.disk_reads += 1,
+        }
+    }
+}
+
+static NEXT_PAGER_ID: AtomicU64 = AtomicU64::new(0);
+
+/// All reads and writes of database files go through the pager. Writes are kept in memory
+/// until they are committed, which logs them to the write-ahead log before the files change.
+/// Every thread is a session of its own, whose writes no other session sees before they are committed.
+pub struct Pager {
+    // tells the sessions of pagers on the same thread apart in the shared locks
+    id: u64,
+    log: WriteAheadLog,
+    locks: Arc<LockManager>,
+    transactions: Arc<TransactionManager>,
+    sessions: Mutex<HashMap<ThreadId, Session>>,
+}
+
+impl Pager {
+    /// Opens the pager on top of the log at the path, recovering all commits it still holds.
+    pub fn open(log_path: &str, transaction_ids_path: &str) -> io::Result<Self> {
+        Ok(Pager {
+            id: NEXT_PAGER_ID.fetch_add(1, Ordering::Relaxed),
+            log: WriteAheadLog::open(log_path)?,
+            locks: LockManager::open(log_path),
+            transactions: TransactionManager::open(transaction_ids_path, true)?,
+            sessions: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Opens the pager without ever writing to the database files, recovering the
+    /// log only if no other process has the database open.
+    pub fn open_read_only(
+        log_path: &str,
+        transaction_ids_path: &str,
+        recover: bool,
+    ) -> io::Result<Self> {
+        let log = if recover {
+            WriteAheadLog::open(log_path)?
+        } else {
+            WriteAheadLog::open_without_recovery(log_path)?
+        };
+
+        Ok(Pager {
+            id: NEXT_PAGER_ID.fetch_add(1, Ordering::Relaxed),
+            log,
+            locks: LockManager::open(log_path),
+            transactions: TransactionManager::open(transaction_ids_path, false)?,
+            sessions: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn file(self: &Arc<Self>, path: &str) -> PagedFile {
+        PagedFile {
+            pager: Arc::clone(self),
+            path: path.to_string(),
+        }
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        match self.pending_file(path) {
+            Some(PendingFile::Removed) => false,
+            Some(_) => true,
+            None => Path::new(path).exists(),
+        }
+    }
+
+    pub fn len(&self, path: &str) -> io::Result<usize> {
+        let pending_length = self.with_session(|session| match session.pending.get(path) {
+            Some(PendingFile::Removed) => Some(Err(not_found(path))),
+            Some(PendingFile::Replaced(contents)) => Some(Ok(contents.len())),
+            Some(PendingFile::Patched { length, .. }) => Some(Ok(*length)),
+            None => None,
+        });
+
+        match pending_length {
+            Some(length) => length,
+            None => Ok(fs::metadata(path)?.len() as usize),
+        }
+    }
+
+    pub fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        let pending_file = self.with_session(|session| {
+            let pending_file = session.pending.get(path).cloned();
+            session
+                .page_accesses
+                .count(matches!(pending_file, Some(PendingFile::Replaced(_))));
+
+            pending_file
+        });
+
+        match pending_file {
+            Some(PendingFile::Removed) => Err(not_found(path)),
+            Some(PendingFile::Replaced(contents)) => Ok(contents),
+            Some(PendingFile::Patched { length, patches }) => {
+                let mut contents = self.read_committed(path)?;
+                contents.resize(length, 0);
+                overlay(&patches, 0, &mut contents);
+
+                Ok(contents)
+            }
+            None => self.read_committed(path),
+        }
+    }
+
+    pub fn read_at(&self, path: &str, offset: usize, length: usize) -> io::Result<Vec<u8>> {
+        let end = offset + length;
+
+        let pending_bytes = self.with_session(|session| {
+            let bytes = match session.pending.get(path) {
+                Some(PendingFile::Removed) => Some(Err(not_found(path))),
+                Some(PendingFile::Replaced(contents)) => Some(
+                    contents
+                        .get(offset..end)
+                        .map(|bytes| bytes.to_vec())
+                        .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof)),
+                ),
+                Some(PendingFile::Patched { length, .. }) if *length < end => {
+                    Some(Err(io::Error::from(io::ErrorKind::UnexpectedEof)))
+                }
+                // the block was written as a whole
+                Some(PendingFile::Patched { patches, .. }) => patches
+                    .range(..=offset)
+                    .next_back()
+                    .filter(|(start, bytes)| **start + bytes.len() >= end)
+                    .map(|(start, bytes)| Ok(bytes[offset - start..end - start].to_vec())),
+                None => None,
+            };
+
+            session.page_accesses.count(bytes.is_some());
+            bytes
+        });
+
+        if let Some(bytes) = pending_bytes {
+            return bytes;
+        }
+
+        let mut buffer = Vec::with_capacity(length);
+
+        {
+            let _guard = self.lock_for_read(&table_path_of(path));
+            let mut file = fs::File::open(path)?;
+
+            file.seek(SeekFrom::Start(offset as u64))?;
+            file.take(length as u64).read_to_end(&mut buffer)?;
+        }
+
+        match self.pending_file(path) {
+            // the file may have grown past what is committed
+            Some(PendingFile::Patched { patches, .. }) => {
+                buffer.resize(length, 0);
+                overlay(&patches, offset, &mut buffer);
+            }
+            _ if buffer.len() < length => {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+            }
+            _ => {}
+        }
+
+        Ok(buffer)
+    }
+
+    /// Replaces the contents of the file, creating it if necessary.
+    pub fn write(&self, path: &str, contents: Vec<u8>) {
+        self.with_session(|session| {
+            session.keep_undo(path);
+            session
+                .pending
+                .insert(path.to_string(), PendingFile::Replaced(contents));
+        });
+    }
+
+    /// Overwrites the bytes at the offset, growing the file with zeros if necessary.
+    /// Only the written bytes are kept, the rest of the file is read from disk when needed.
+    pub fn write_at(&self, path: &str, offset: usize, bytes: &[u8]) -> io::Result<()> {
+        let committed_length = match self.pending_file(path) {
+            None if Path::new(path).exists() => Some(fs::metadata(path)?.len() as usize),
+            _ => None,
+        };
+
+        self.with_session(|session| {
+            session.keep_undo(path);
+
+            let pending_file =
+                session
+                    .pending
+                    .entry(path.to_string())
+                    .or_insert_with(|| match committed_length {
+                        Some(length) => PendingFile::Patched {
+                            length,
+                            patches: BTreeMap::new(),
+                        },
+                        None => PendingFile::Removed,
+                    });
+
+            if let PendingFile::Removed = pending_file {
+                *pending_file = PendingFile::Replaced(vec![]);
+            }
+
+            match pending_file {
+                PendingFile::Replaced(contents) => {
+                    if contents.len() < offset + bytes.len() {
+                        contents.resize(offset + bytes.len(), 0);
+                    }
+                    contents[offset..offset + bytes.len()].copy_from_slice(bytes);
+                }
+                PendingFile::Patched { length, patches } => {
+                    *length = (*length).max(offset + bytes.len());
+                    patch(patches, offset, bytes);
+                }
+                PendingFile::Removed => unreachable!(),
+            }
+        });
+
+        Ok(())
+    }
+
+    pub fn remove(&self, path: &str) -> io::Result<()> {
+        if !self.exists(path) {
+            return Err(not_found(path));
+        }
+
+        self.with_session(|session| {
+            session.keep_undo(path);
+            session
+                .pending
+                .insert(path.to_string(), PendingFile::Removed);
+        });
+
+        Ok(())
+    }
+
+    /// Starts a transaction, deferring all commits until it ends.
+    pub fn begin(&self) -> io::Result<()> {
+        self.transaction_id()?;
+        self.with_session(|session| session.in_transaction = true);
+
+        Ok(())
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        self.with_session(|session| session.in_transaction)
+    }
+
+    /// The id of the transaction of the session, which every version it writes is stamped with.
+    /// Outside a transaction, one is started that ends with the next commit.
+    pub fn transaction_id(&self) -> io::Result<TransactionId> {
+        self.with_session(|session| match session.transaction_id {
+            Some(transaction_id) => Ok(transaction_id),
+            None => {
+                let transaction_id = self.transactions.start()?;
+                session.transaction_id = Some(transaction_id);
+
+                Ok(transaction_id)
+            }
+        })
+    }
+
+    /// The snapshot the session reads with. A transaction takes it on its first read and keeps
+    /// it until the end of the statement, or of the transaction if it repeats its reads.
+    pub fn snapshot(&self) -> Snapshot {
+        self.with_session(|session| {
+            if let Some(snapshot) = &session.snapshot {
+                return snapshot.clone();
+            }
+
+            let snapshot = self.transactions.snapshot(session.transaction_id);
+
+            if session.transaction_id.is_some() {
+                session.snapshot = Some(snapshot.clone());
+            }
+
+            snapshot
+        })
+    }
+
+    /// How often the session read files so far.
+    pub fn page_accesses(&self) -> PageAccesses {
+        self.with_session(|session| session.page_accesses)
+    }
+
+    /// Lets the next statement of a transaction that reads committed changes take a new snapshot.
+    pub fn start_statement(&self) {
+        self.with_session(|session| {
+            if session.isolation_level == IsolationLevel::ReadCommitted {
+                session.snapshot = None;
+            }
+        });
+    }
+
+    pub fn set_isolation_level(&self, isolation_level: IsolationLevel) -> Result<(), String> {
+        self.with_session(|session| {
+            if session.snapshot.is_some() {
+                return Err(String::from(
+                    "The isolation level has to be set before the first query of the transaction.",
+                ));
+            }
+
+            session.isolation_level = isolation_level;
+
+            Ok(())
+        })
+    }
+
+    /// The oldest transaction some snapshot may not see, see `TransactionManager::horizon`.
+    pub fn horizon(&self) -> TransactionId {
+        self.transactions.horizon()
+    }
+
+    /// Locks the table in the directory for writing until the transaction of the session ends.
+    /// If that would deadlock, the transaction is rolled back instead. Outside a transaction
+    /// every write commits on its own, so there is nothing to hold the lock for.
+    pub fn lock_for_write(&self, table_path: &str) -> io::Result<()> {
+        if !self.in_transaction() {
+            return Ok(());
+        }
+
+        self.locks
+            .lock_for_write(self.session_id(), table_path)
+            .inspect_err(|_| {
+                self.rollback();
+            })
+    }
+
+    /// Like `lock_for_write`, but also waits for the queries of other sessions scanning the table
+    /// to end and keeps new ones away, for writes that move rows to other record ids.
+    pub fn lock_exclusively(&self, table_path: &str) -> io::Result<()> {
+        if !self.in_transaction() {
+            return Ok(());
+        }
+
+        self.locks
+            .lock_exclusively(self.session_id(), table_path)
+            .inspect_err(|_| {
+                self.rollback();
+            })
+    }
+
+    /// Keeps commits of other sessions away from the table in the directory while the guard lives.
+    pub fn lock_for_read(&self, table_path: &str) -> ReadGuard<'_> {
+        self.locks.lock_for_read(self.session_id(), table_path)
+    }
+
+    /// Keeps other sessions from moving the rows of the table in the directory while the guard
+    /// lives, without keeping their commits away. If that would deadlock, the transaction is
+    /// rolled back instead.
+    pub fn lock_for_scan(&self, table_path: &str) -> io::Result<ScanGuard<'_>> {
+        self.locks
+            .lock_for_scan(self.session_id(), table_path)
+            .inspect_err(|_| {
+                self.rollback();
+            })
+    }
+
+    /// Keeps the horizon where it is while the guard lives, so that every snapshot taken
+    /// meanwhile keeps seeing its versions, also after its transaction ends.
+    pub fn hold_horizon(&self) -> HorizonGuard {
+        self.transactions.hold_horizon()
+    }
+
+    /// Makes all pending writes durable and applies them to the files, ending the transaction.
+    pub fn commit(&self) -> io::Result<()> {
+        let session = self.end_session();
+
+        let records: Vec<LogRecord> = session
+            .pending
+            .into_iter()
+            .map(|(path, pending_file)| match pending_file {
+                PendingFile::Removed => LogRecord::Remove { path },
+                PendingFile::Replaced(contents) => LogRecord::Write { path, contents },
+                PendingFile::Patched { patches, .. } => LogRecord::Patch {
+                    path,
+                    writes: patches
+                        .into_iter()
+                        .map(|(offset, bytes)| (offset as u64, bytes))
+                        .collect(),
+                },
+            })
+            .collect();
+
+        let table_paths: BTreeSet<String> = records
+            .iter()
+            .map(|record| table_path_of(record.path()))
+            .collect();
+
+        let _guard = self.locks.lock_for_apply(self.session_id(), &table_paths);
+        let result = self.log.commit(records);
+
+        // the transaction has to end before others may write the same tables
+        self.end_transaction(session.transaction_id);
+        self.locks.release_writes(self.session_id());
+
+        result
+    }
+
+    /// Syncs all committed changes to the files and truncates the log.
+    pub fn checkpoint(&self) -> io::Result<u64> {
+        self.log.checkpoint()
+    }
+
+    /// Discards all pending writes, ending the transaction.
+    pub fn rollback(&self) {
+        let session = self.end_session();
+
+        self.end_transaction(session.transaction_id);
+        self.locks.release_writes(self.session_id());
+    }
+
+    /// Marks the current state of the transaction, so that later writes can be rolled back.
+    pub fn savepoint(&self, name: Option<&str>) {
+        self.with_session(|session| {
+            session.savepoints.push(Savepoint {
+                name: name.map(str::to_string),
+                undo: BTreeMap::new(),
+            })
+        });
+    }
+
+    /// Restores the state of the innermost savepoint with the name, which stays in place
+    /// while all savepoints after it are dropped.
+    pub fn rollback_to_savepoint(&self, name: Option<&str>) -> io::Result<()> {
+        self.with_session(|session| {
+            let position = session.savepoint_position(name)?;
+
+            while session.savepoints.len() > position {
+                let savepoint = session.savepoints.pop().unwrap();
+
+                for (path, pending_file) in savepoint.undo {
+                    match pending_file {
+                        Some(pending_file) => session.pending.insert(path, pending_file),
+                        None => session.pending.remove(&path),
+                    };
+                }
+            }
+
+            session.savepoints.push(Savepoint {
+                name: name.map(str::to_string),
+                undo: BTreeMap::new(),
+            });
+
+            Ok(())
+        })
+    }
+
+    /// Drops the innermost savepoint with the name and all savepoints after it,
+    /// keeping their writes as part of the enclosing savepoint or transaction.
+    pub fn release_savepoint(&self, name: Option<&str>) -> io::Result<()> {
+        self.with_session(|session| {
+            let position = session.savepoint_position(name)?;
+            let released = session.savepoints.split_off(position);
+
+            if let Some(enclosing) = session.savepoints.last_mut() {
+                for (path, pending_file) in
+                    released.into_iter().flat_map(|savepoint| savepoint.undo)
+                {
+                    enclosing.undo.entry(path).or_insert(pending_file);
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Commits the writes of the operation, or discards them if it fails.
+    /// Inside a transaction, they are left to the end of the transaction instead.
+    pub fn atomically<T>(&self, operation: impl FnOnce() -> io::Result<T>) -> io::Result<T> {
+        if self.in_transaction() {
+            return operation();
+        }
+
+        match operation() {
+            Ok(value) => {
+                self.commit()?;
+                Ok(value)
+            }
+            Err(error) => {
+                self.rollback();
+                Err(error)
+            }
+        }
+    }
+
+    fn session_id(&self) -> SessionId {
+        (self.id, thread::current().id())
+    }
+
+    fn with_session<T>(&self, operation: impl FnOnce(&mut Session) -> T) -> T {
+        let mut sessions = self.sessions.lock().unwrap();
+
+        operation(sessions.entry(thread::current().id()).or_default())
+    }
+
+    fn end_session(&self) -> Session {
+        self.sessions
+            .lock()
+            .unwrap()
+            .remove(&thread::current().id())
+            .unwrap_or_default()
+    }
+
+    fn end_transaction(&self, transaction_id: Option<TransactionId>) {
+        if let Some(transaction_id) = transaction_id {
+            self.transactions.end(transaction_id);
+        }
+    }
+
+    /// Reads the committed file, which no commit is applied to meanwhile.
+    fn read_committed(&self, path: &str) -> io::Result<Vec<u8>> {
+        let _guard = self.lock_for_read(&table_path_of(path));
+
+        fs::read(path)
+    }
+
+    fn pending_file(&self, path: &str) -> Option<PendingFile> {
+        self.with_session(|session| session.pending.get(path).cloned())
+    }
+}
+
+impl Session {
+    fn savepoint_position(&self, name: Option<&str>) -> io::Result<usize> {
+        self.savepoints
+            .iter()
+            .rposition(|savepoint| savepoint.name.as_deref() == name)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Savepoint '{}' does not exist.", name.unwrap_or_default()),
+                )
+            })
+    }
+
+    /// Keeps the pending state of the file for the innermost savepoint, unless it already has it.
+    fn keep_undo(&mut self, path: &str) {
+        if let Some(savepoint) = self.savepoints.last_mut() {
+            if !savepoint.undo.contains_key(path) {
+                let pending_file = self.pending.get(path).cloned();
+
+                savepoint.undo.insert(path.to_string(), pending_file);
+            }
+        }
+    }
+}
+
+/// A single file of the pager, read and written in fixed-size blocks.
+pub struct PagedFile {
+    pager: Arc<Pager>,
+    path: String,
+}
+
+impl PagedFile {
+    pub fn read_at(&self, offset: usize, length: usize) -> io::Result<Vec<u8>> {
+        self.pager.read_at(&self.path, offset, length)
+    }
+
+    pub fn write_at(&self, offset: usize, bytes: &[u8]) -> io::Result<()> {
+        self.pager.write_at(&self.path, offset, bytes)
+    }
+}
+
+/// Adds the bytes at the offset to the patches, merging them with the patches they overlap or touch.
+fn patch(patches: &mut BTreeMap<usize, Vec<u8>>, offset: usize, bytes: &[u8]) {
+    let end = offset + bytes.len();
+
+    let merged: Vec<usize> = patches
+        .range(..=end)
+        .rev()
+        .take_while(|(start, patch)| **start + patch.len() >= offset)
+        .map(|(start, _)| *start)
+        .collect();
+
+    let start = merged.last().map_or(offset, |start| (*start).min(offset));
+    let mut merged_bytes = vec![];
+
+    for patch_start in merged.into_iter().rev() {
+        let patch = patches.remove(&patch_start).unwrap();
+
+        if merged_bytes.len() < patch_start - start + patch.len() {
+            merged_bytes.resize(patch_start - start + patch.len(), 0);
+        }
+        merged_bytes[patch_start - start..patch_start - start + patch.len()]
+            .copy_from_slice(&patch);
+    }
+
+    if merged_bytes.len() < end - start {
+        merged_bytes.resize(end - start, 0);
+    }
+    merged_bytes[offset - start..end - start].copy_from_slice(bytes);
+
+    patches.insert(start, merged_bytes);
+}
+
+/// Copies the patched bytes into the buffer, which holds the file from the offset on.
+fn overlay(patches: &BTreeMap<usize, Vec<u8>>, offset: usize, buffer: &mut [u8]) {
+    let end = offset + buffer.len();
+
+    for (start, patch) in patches.range(..end) {
+        let (from, to) = ((*start).max(offset), (start + patch.len()).min(end));
+
+        if from < to {
+            buffer[from - offset..to - offset].copy_from_slice(&patch[from - start..to - start]);
+        }
+    }
+}
+
+/// The directory of the table the file belongs to, which locks are taken on.
+fn table_path_of(path: &str) -> String {
+    Path::new(path)
+        .parent()
+        .map(|table_path| table_path.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn not_found(path: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("'{}' does not exist.", path),
+    )
+}
+
+#[cfg(test)]
+pub mod tests {
+    use rand::{distributions::Alphanumeric, Rng};
+
+    use super::*;
+
+    /// A pager whose log and files live in a fresh temporary directory.
+    pub struct TempPager {
+        pub pager: Arc<Pager>,
+        dir: String,
+    }
+
+    impl TempPager {
+        pub fn new() -> Self {
+            let dir_name: String = rand::thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(10)
+                .map(char::from)
+                .collect();
+
+            let dir = std::env::temp_dir().join(dir_name);
+            fs::create_dir(&dir).unwrap();
+            let dir = dir.to_str().unwrap().to_string();
+
+            let pager = Arc::new(
+                Pager::open(
+                    &format!("{}/wal.log", dir),
+                    &format!("{}/transaction_ids.bin", dir),
+                )
+                .unwrap(),
+            );
+
+            TempPager { pager, dir }
+        }
+
+        pub fn path(&self, file_name: &str) -> String {
+            format!("{}/{}", self.dir, file_name)
+        }
+    }
+
+    impl Drop for TempPager {
+        fn drop(&mut self) {
+            fs::remove_dir_all(&self.dir).ok();
+        }
+    }
+
+    #[test]
+    fn test_reads_see_pending_writes_until_they_are_rolled_back() {
+        let temp = TempPager::new();
+        let path = temp.path("page.bin");
+
+        temp.pager.write(&path, vec![1, 2, 3]);
+        temp.pager.write_at(&path, 4, &[5]).unwrap();
+
+        assert_eq!(temp.pager.read(&path).unwrap(), vec![1, 2, 3, 0, 5]);
+        assert!(!Path::new(&path).exists());
+
+        temp.pager.rollback();
+
+        assert!(!temp.pager.exists(&path));
+    }
+
+    #[test]
+    fn test_rolling_back_to_a_savepoint_restores_the_files_it_saw() {
+        let temp = TempPager::new();
+        let page = temp.path("page.bin");
+        let index = temp.path("index.bin");
+
+        temp.pager.begin().unwrap();
+        temp.pager.write(&page, vec![1]);
+        temp.pager.savepoint(Some("first"));
+        temp.pager.write(&page, vec![2]);
+        temp.pager.savepoint(Some("second"));
+        temp.pager.write_at(&index, 0, &[3]).unwrap();
+        temp.pager.remove(&page).unwrap();
+
+        temp.pager.rollback_to_savepoint(Some("second")).unwrap();
+
+        assert_eq!(temp.pager.read(&page).unwrap(), vec![2]);
+        assert!(!temp.pager.exists(&index));
+
+        temp.pager.write(&page, vec![4]);
+        temp.pager.write_at(&index, 0, &[5]).unwrap();
+        temp.pager.release_savepoint(Some("second")).unwrap();
+        temp.pager.rollback_to_savepoint(Some("first")).unwrap();
+
+        assert_eq!(temp.pager.read(&page).unwrap(), vec![1]);
+        assert!(!temp.pager.exists(&index));
+        assert!(temp.pager.rollback_to_savepoint(Some("second")).is_err());
+    }
+
+    #[test]
+    fn test_commit_only_patches_the_written_ranges() {
+        let temp = TempPager::new();
+        let path = temp.path("index.bin");
+
+        fs::write(&path, vec![0; 8]).unwrap();
+
+        temp.pager.write_at(&path, 2, &[7, 7]).unwrap();
+        temp.pager.commit().unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), vec![0, 0, 7, 7, 0, 0, 0, 0]);
+
+        temp.pager.write_at(&path, 6, &[1, 2, 3, 4]).unwrap();
+        temp.pager.write_at(&path, 1, &[5, 5]).unwrap();
+        temp.pager.write_at(&path, 3, &[6]).unwrap();
+
+        assert_eq!(temp.pager.len(&path).unwrap(), 10);
+        assert_eq!(
+            temp.pager.read_at(&path, 0, 5).unwrap(),
+            vec![0, 5, 5, 6, 0]
+        );
+        assert_eq!(
+            temp.pager.read(&path).unwrap(),
+            vec![0, 5, 5, 6, 0, 0, 1, 2, 3, 4]
+        );
+        assert!(temp.pager.read_at(&path, 8, 3).is_err());
+        // the overlapping writes are merged, the untouched bytes are never read into memory
+        assert_eq!(
+            temp.pager
+                .pending_file(&path)
+                .map(|pending_file| match pending_file {
+                    PendingFile::Patched { patches, .. } => patches.into_keys().collect(),
+                    _ => vec![],
+                }),
+            Some(vec![1, 6])
+        );
+
+        temp.pager.commit().unwrap();
+        assert_eq!(fs::read(&path).unwrap(), vec![0, 5, 5, 6, 0, 0, 1, 2, 3, 4]);
+
+        temp.pager.remove(&path).unwrap();
+        assert!(temp.pager.read_at(&path, 0, 1).is_err());
+
+        temp.pager.commit().unwrap();
+        assert!(!Path::new(&path).exists());
+    }
+}
@@ -0,0 +1,239 @@
+use crate::engine::binary_engine::data_manager::DATA_PAGE_SIZE;
+use crate::metadata::{ColumnRange, ColumnStatistics, Index, KeyRange, Table};
+use crate::sql_parser::query::{Condition, Predicate};
+
+/// The cost of processing a single row, relative to reading a page.
+const ROW_COST: f64 = 0.01;
+/// How many entries a page of an index is assumed to hold.
+const ENTRIES_PER_INDEX_PAGE: f64 = 64.0;
+/// How many bytes a row is assumed to take, to tell whether rows fit into the work memory.
+const ESTIMATED_ROW_SIZE: f64 = 100.0;
+
+const EQUALITY_SELECTIVITY: f64 = 0.1;
+const RANGE_SELECTIVITY: f64 = 1.0 / 3.0;
+const BOUNDED_RANGE_SELECTIVITY: f64 = 0.25;
+
+/// How many rows the table is estimated to hold: as many as ANALYZE counted, scaled by how
+/// its data pages grew or shrank since, or as many as were ever inserted without statistics.
+pub fn table_rows(table: &Table, data_pages: usize) -> f64 {
+    match &table.statistics {
+        Some(statistics) if statistics.data_page_count > 0 => {
+            statistics.row_count as f64 * data_pages as f64 / statistics.data_page_count as f64
+        }
+        Some(statistics) if data_pages == 0 => statistics.row_count as f64,
+        _ => table.latest_primary_key() as f64,
+    }
+}
+
+/// The estimated fraction of the rows of the table that match all where clauses,
+/// assuming they are independent of each other. The statistics of the columns are
+/// used where ANALYZE collected them, fixed guesses otherwise.
+pub fn selectivity<'a>(
+    table: Option<&Table>,
+    where_clauses: impl IntoIterator<Item = &'a Condition>,
+) -> f64 {
+    where_clauses
+        .into_iter()
+        .map(|clause| {
+            table
+                .and_then(|table| estimated_selectivity(table, clause))
+                .unwrap_or_else(|| default_selectivity(table, clause))
+        })
+        .product()
+}
+
+fn estimated_selectivity(table: &Table, clause: &Condition) -> Option<f64> {
+    if clause.function.is_some() {
+        return None;
+    }
+
+    let statistics = table
+        .statistics
+        .as_ref()?
+        .columns
+        .get(&clause.column_name)?;
+    let non_null = 1.0 - statistics.null_fraction;
+
+    if let Predicate::Equal(_) = clause.predicate {
+        return Some(non_null / statistics.distinct_count.max(1) as f64);
+    }
+
+    let data_type = table.column_type(&clause.column_name)?;
+
+    match ColumnRange::from_predicate(&clause.predicate, data_type)? {
+        ColumnRange::Range(lower, upper) => {
+            Some(non_null * statistics.range_fraction(lower.as_ref(), upper.as_ref()))
+        }
+        ColumnRange::Prefix(_) => None,
+    }
+}
+
+fn default_selectivity(table: Option<&Table>, clause: &Condition) -> f64 {
+    match &clause.predicate {
+        Predicate::Equal(_) => match table {
+            Some(table) if clause.function.is_none() && is_unique(table, &clause.column_name) => {
+                1.0 / table.latest_primary_key().max(1) as f64
+            }
+            _ => EQUALITY_SELECTIVITY,
+        },
+        Predicate::LessThan(_)
+        | Predicate::LessThanOrEqual(_)
+        | Predicate::GreaterThan(_)
+        | Predicate::GreaterThanOrEqual(_) => RANGE_SELECTIVITY,
+        Predicate::Between(_, _) | Predicate::Like(_) => BOUNDED_RANGE_SELECTIVITY,
+    }
+}
+
+/// The estimated fraction of the rows of the table holding any single value of the column,
+/// as a lookup of the values of another table in it matches.
+pub fn lookup_selectivity(table: &Table, column_name: &str) -> f64 {
+    match table
+        .statistics
+        .as_ref()
+        .and_then(|statistics| statistics.columns.get(column_name))
+    {
+        Some(statistics) => {
+            (1.0 - statistics.null_fraction) / statistics.distinct_count.max(1) as f64
+        }
+        None if is_unique(table, column_name) => 1.0 / table.latest_primary_key().max(1) as f64,
+        None => EQUALITY_SELECTIVITY,
+    }
+}
+
+/// The statistics of a column, named after its table if the tables are joined.
+pub fn column_statistics<'a>(
+    tables: &'a [Table],
+    column_name: &str,
+) -> Option<&'a ColumnStatistics> {
+    let (table, column_name) = match tables {
+        [table] => (table, column_name),
+        _ => {
+            let (table_name, column_name) = column_name.split_once('.')?;
+
+            (
+                tables.iter().find(|table| table.name == table_name)?,
+                column_name,
+            )
+        }
+    };
+
+    table.statistics.as_ref()?.columns.get(column_name)
+}
+
+/// Whether no two rows of the table share a value in the column.
+fn is_unique(table: &Table, column_name: &str) -> bool {
+    table.indices.iter().any(|index| {
+        index.unique
+            && index.where_clauses.is_empty()
+            && matches!(index.columns.as_slice(), [column] if column.function.is_none() && column.name == column_name)
+    })
+}
+
+/// The where clauses that restrict the part of the index that is scanned.
+pub fn key_range_clauses<'a>(
+    index: &Index,
+    key_range: &KeyRange,
+    where_clauses: &'a [Condition],
+) -> Vec<&'a Condition> {
+    let matched_columns = &index.columns[..key_range.matched_columns()];
+
+    where_clauses
+        .iter()
+        .filter(|clause| matched_columns.iter().any(|column| column.matches(clause)))
+        .collect()
+}
+
+/// Reading every data page of a table and checking each of its rows.
+pub fn seq_scan_cost(data_pages: usize, rows: f64) -> f64 {
+    data_pages.max(1) as f64 + rows * ROW_COST
+}
+
+/// Looking up the matching entries of an index and reading the data pages holding them.
+pub fn index_scan_cost(matched_rows: f64, data_pages: usize) -> f64 {
+    index_only_scan_cost(matched_rows) + matched_rows.ceil().min(data_pages.max(1) as f64)
+}
+
+/// Looking up the matching entries of an index, without reading any data pages.
+pub fn index_only_scan_cost(matched_rows: f64) -> f64 {
+    (matched_rows / ENTRIES_PER_INDEX_PAGE).ceil().max(1.0) + matched_rows * ROW_COST
+}
+
+/// Sorting the rows, writing them to temporary files and reading them back if they do not fit.
+pub fn sort_cost(rows: f64, work_memory: usize) -> f64 {
+    rows * rows.max(2.0).log2() * ROW_COST + spill_cost(rows, work_memory)
+}
+
+/// Putting the rows of the build input into a hash table and looking up the rows of the probe
+/// input in it. If the build input does not fit, both inputs are written to temporary files.
+pub fn hash_join_cost(build_rows: f64, probe_rows: f64, work_memory: usize) -> f64 {
+    let spilled_rows = match fits(build_rows, work_memory) {
+        true => 0.0,
+        false => build_rows + probe_rows,
+    };
+
+    (2.0 * build_rows + probe_rows) * ROW_COST + spill_cost(spilled_rows, 0)
+}
+
+/// Reading both sorted inputs side by side.
+pub fn merge_join_cost(left_rows: f64, right_rows: f64) -> f64 {
+    (left_rows + right_rows) * ROW_COST
+}
+
+/// Handling every row of the input once, as filters, aggregates and nested loop joins do.
+pub fn processing_cost(rows: f64) -> f64 {
+    rows * ROW_COST
+}
+
+fn fits(rows: f64, work_memory: usize) -> bool {
+    rows * ESTIMATED_ROW_SIZE <= work_memory as f64
+}
+
+/// Writing the rows that do not fit into the work memory to pages of temporary files and reading them back.
+fn spill_cost(rows: f64, work_memory: usize) -> f64 {
+    match fits(rows, work_memory) {
+        true => 0.0,
+        false => 2.0 * (rows * ESTIMATED_ROW_SIZE / DATA_PAGE_SIZE as f64).ceil(),
+    }
+}
+
+/// Every row is assumed to join with the rows of the other input sharing its value, out of as
+/// many distinct values as the input with more of them has. Without statistics, every row is
+/// assumed to join with at most one row of the other input, as it does on keys.
+pub fn join_rows(
+    left_rows: f64,
+    right_rows: f64,
+    left_distinct: Option<&ColumnStatistics>,
+    right_distinct: Option<&ColumnStatistics>,
+) -> f64 {
+    let distinct = |statistics: Option<&ColumnStatistics>, rows: f64| {
+        statistics.map(|statistics| (statistics.distinct_count as f64).min(rows))
+    };
+
+    match (
+        distinct(left_distinct, left_rows),
+        distinct(right_distinct, right_rows),
+    ) {
+        (None, None) => left_rows.min(right_rows),
+        (left, right) => left_rows * right_rows / left.into_iter().chain(right).fold(1.0, f64::max),
+    }
+}
+
+/// How many groups the rows are estimated to fall into: as many as there are combinations
+/// of the distinct values of the grouped columns, if all of them are known.
+pub fn group_count(rows: f64, group_by: &[String], tables: &[Table]) -> f64 {
+    if group_by.is_empty() {
+        return 1.0;
+    }
+
+    let distinct: Option<f64> = group_by
+        .iter()
+        .map(|column_name| {
+            column_statistics(tables, column_name)
+                .map(|statistics| statistics.distinct_count as f64)
+        })
+        .product();
+
+    distinct
+        .unwrap_or((rows * EQUALITY_SELECTIVITY).ceil())
+        .clamp(1.0, rows.max(1.0))
+}
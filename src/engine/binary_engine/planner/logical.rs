@@ -0,0 +1,208 @@
+use crate::engine::utils::selected_all_columns;
+use crate::engine::SelectQuery;
+use crate::sql_parser::query::{Aggregate, Condition, OrderBy};
+
+/// What a query computes, before it is decided how. The where clauses of scans are
+/// evaluated while the rows are read, and only the listed columns are kept.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogicalPlan {
+    Scan {
+        table_name: String,
+        /// Whether the columns are named after the table, as they are when tables are joined.
+        qualified: bool,
+        where_clauses: Vec<Condition>,
+        columns: Option<Vec<String>>,
+    },
+    /// No rows at all, for queries that cannot match any.
+    Empty,
+    Filter {
+        input: Box<LogicalPlan>,
+        where_clauses: Vec<Condition>,
+    },
+    /// An inner join, where the left column belongs to the left input.
+    Join {
+        left: Box<LogicalPlan>,
+        right: Box<LogicalPlan>,
+        left_column: String,
+        right_column: String,
+    },
+    Aggregate {
+        input: Box<LogicalPlan>,
+        group_by: Vec<String>,
+        aggregates: Vec<Aggregate>,
+    },
+    Sort {
+        input: Box<LogicalPlan>,
+        order_by: Vec<OrderBy>,
+    },
+    Project {
+        input: Box<LogicalPlan>,
+        columns: Vec<String>,
+    },
+    Limit {
+        input: Box<LogicalPlan>,
+        limit: Option<usize>,
+        offset: usize,
+    },
+}
+
+impl LogicalPlan {
+    /// Translates the query clause by clause: the tables are joined from left to right,
+    /// then filtered, grouped, sorted, projected and limited.
+    pub fn from_query(query: &SelectQuery) -> Self {
+        let scan = |table_name: &str| LogicalPlan::Scan {
+            table_name: table_name.to_string(),
+            qualified: !query.joins.is_empty(),
+            where_clauses: vec![],
+            columns: None,
+        };
+
+        let mut plan = scan(&query.table_name);
+
+        for join in &query.joins {
+            let (left_column, right_column) = if join
+                .left_column
+                .starts_with(&format!("{}.", join.table_name))
+            {
+                (join.right_column.clone(), join.left_column.clone())
+            } else {
+                (join.left_column.clone(), join.right_column.clone())
+            };
+
+            plan = LogicalPlan::Join {
+                left: Box::new(plan),
+                right: Box::new(scan(&join.table_name)),
+                left_column,
+                right_column,
+            };
+        }
+
+        if !query.where_clauses.is_empty() {
+            plan = LogicalPlan::Filter {
+                input: Box::new(plan),
+                where_clauses: query.where_clauses.clone(),
+            };
+        }
+
+        let aggregates: Vec<Aggregate> = query
+            .selection
+            .iter()
+            .filter_map(|item| Aggregate::from_selection(item))
+            .collect();
+
+        if !aggregates.is_empty() || !query.group_by.is_empty() {
+            plan = LogicalPlan::Aggregate {
+                input: Box::new(plan),
+                group_by: query.group_by.clone(),
+                aggregates,
+            };
+        }
+
+        if !query.order_by.is_empty() {
+            plan = LogicalPlan::Sort {
+                input: Box::new(plan),
+                order_by: query.order_by.clone(),
+            };
+        }
+
+        if !query.selection.is_empty() && !selected_all_columns(&query.selection) {
+            plan = LogicalPlan::Project {
+                input: Box::new(plan),
+                columns: query.selection.clone(),
+            };
+        }
+
+        if query.limit.is_some() || query.offset.is_some() {
+            plan = LogicalPlan::Limit {
+                input: Box::new(plan),
+                limit: query.limit,
+                offset: query.offset.unwrap_or(0),
+            };
+        }
+
+        plan
+    }
+
+    /// The tables whose rows the plan reads.
+    pub fn table_names(&self) -> Vec<&str> {
+        match self {
+            LogicalPlan::Scan { table_name, .. } => vec![table_name.as_str()],
+            LogicalPlan::Empty => vec![],
+            LogicalPlan::Join { left, right, .. } => left
+                .table_names()
+                .into_iter()
+                .chain(right.table_names())
+                .collect(),
+            LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Aggregate { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Project { input, .. }
+            | LogicalPlan::Limit { input, .. } => input.table_names(),
+        }
+    }
+
+    /// Applies the function to the inputs of the plan.
+    pub fn map_inputs(self, mut function: impl FnMut(LogicalPlan) -> LogicalPlan) -> Self {
+        let mut map = |input: Box<LogicalPlan>| Box::new(function(*input));
+
+        match self {
+            LogicalPlan::Scan { .. } | LogicalPlan::Empty => self,
+            LogicalPlan::Filter {
+                input,
+                where_clauses,
+            } => LogicalPlan::Filter {
+                input: map(input),
+                where_clauses,
+            },
+            LogicalPlan::Join {
+                left,
+                right,
+                left_column,
+                right_column,
+            } => LogicalPlan::Join {
+                left: map(left),
+                right: map(right),
+                left_column,
+                right_column,
+            },
+            LogicalPlan::Aggregate {
+                input,
+                group_by,
+                aggregates,
+            } => LogicalPlan::Aggregate {
+                input: map(input),
+                group_by,
+                aggregates,
+            },
+            LogicalPlan::Sort { input, order_by } => LogicalPlan::Sort {
+                input: map(input),
+                order_by,
+            },
+            LogicalPlan::Project { input, columns } => LogicalPlan::Project {
+                input: map(input),
+                columns,
+            },
+            LogicalPlan::Limit {
+                input,
+                limit,
+                offset,
+            } => LogicalPlan::Limit {
+                input: map(input),
+                limit,
+                offset,
+            },
+        }
+    }
+
+    /// Whether the plan is known to produce no rows.
+    pub fn is_empty(&self) -> bool {
+        matches!(self, LogicalPlan::Empty)
+    }
+}
+
+/// The columns the aggregates of a query read.
+pub fn aggregated_columns(aggregates: &[Aggregate]) -> impl Iterator<Item = &String> {
+    aggregates
+        .iter()
+        .filter_map(|aggregate| aggregate.column_name.as_ref())
+}
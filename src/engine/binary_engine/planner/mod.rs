@@ -0,0 +1,28 @@
+mod cost;
+mod logical;
+mod physical;
+mod rewrite;
+
+use crate::engine::SelectQuery;
+use crate::metadata::Table;
+
+use self::logical::LogicalPlan;
+pub use self::physical::PhysicalPlan;
+use super::mvcc::Snapshot;
+use super::BinaryEngine;
+
+/// Plans a query whose columns were checked against the tables it reads. Its logical plan
+/// is rewritten by rules first, then every scan gets the cheapest way to read its table.
+pub fn plan(
+    engine: &BinaryEngine,
+    tables: &[Table],
+    query: &SelectQuery,
+    snapshot: &Snapshot,
+) -> PhysicalPlan {
+    let plan = LogicalPlan::from_query(query);
+    let plan = rewrite::push_down_predicates(plan);
+    let plan = rewrite::fold_constants(plan, tables);
+    let plan = rewrite::prune_projections(plan, None);
+
+    physical::choose(engine, tables, plan, snapshot)
+}
@@ -0,0 +1,866 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::metadata::IndexType;
+use crate::metadata::{Index, KeyRange, Table};
+use crate::sql_parser::query::{Aggregate, Condition, OrderBy};
+
+use super::super::executor::{
+    self, Filter, HashJoin, IndexOnlyScan, IndexScan, Instrumented, Limit, MergeJoin,
+    NestedLoopJoin, Operator, OperatorStatistics, Project, SeqScan, Sort, TableScan,
+};
+use super::super::mvcc::Snapshot;
+use super::super::BinaryEngine;
+use super::cost;
+use super::logical::LogicalPlan;
+
+/// How a query is executed: a tree of operators, each with the number of rows it is estimated to produce.
+#[derive(Clone)]
+pub struct PhysicalPlan {
+    pub node: PhysicalNode,
+    pub inputs: Vec<PhysicalPlan>,
+    pub estimated_rows: f64,
+    /// The estimated cost of the operator and its inputs, in pages read.
+    pub cost: f64,
+    /// The columns the rows come out sorted by in ascending order, most significant first.
+    pub sorted_by: Vec<String>,
+    /// Where the operator records what it did while it runs, if the plan is instrumented.
+    pub statistics: Option<Rc<RefCell<OperatorStatistics>>>,
+}
+
+#[derive(Clone)]
+pub enum PhysicalNode {
+    Empty,
+    SeqScan(TableScan),
+    /// Looks up the outer column of a nested loop join instead if it is set, rescanned for every outer row.
+    IndexScan {
+        scan: TableScan,
+        index: Index,
+        key_range: KeyRange,
+        lookup: Option<String>,
+    },
+    IndexOnlyScan {
+        scan: TableScan,
+        index: Index,
+        key_range: KeyRange,
+        lookup: Option<String>,
+    },
+    Filter(Vec<Condition>),
+    /// The outer input is the first one, the inner input the second, which looks up the outer values.
+    NestedLoopJoin {
+        outer_column: String,
+        inner_column: String,
+    },
+    /// The probe input is the first one, the build input the second.
+    HashJoin {
+        probe_column: String,
+        build_column: String,
+    },
+    /// Both inputs are sorted by their join columns.
+    MergeJoin {
+        left_column: String,
+        right_column: String,
+    },
+    Aggregate {
+        group_by: Vec<String>,
+        aggregates: Vec<Aggregate>,
+    },
+    Sort(Vec<OrderBy>),
+    Project(Vec<String>),
+    Limit {
+        limit: Option<usize>,
+        offset: usize,
+    },
+}
+
+impl PhysicalPlan {
+    /// A plan whose operator costs as much as given on top of its inputs.
+    fn new(
+        node: PhysicalNode,
+        inputs: Vec<PhysicalPlan>,
+        estimated_rows: f64,
+        operator_cost: f64,
+    ) -> Self {
+        let sorted_by = match &node {
+            PhysicalNode::Filter(_)
+            | PhysicalNode::NestedLoopJoin { .. }
+            | PhysicalNode::Project(_)
+            | PhysicalNode::Limit { .. } => inputs[0].sorted_by.clone(),
+            PhysicalNode::Sort(order_by) => order_by
+                .iter()
+                .take_while(|column| !column.descending)
+                .map(|column| column.column_name.clone())
+                .collect(),
+            PhysicalNode::MergeJoin { left_column, .. } => vec![left_column.clone()],
+            _ => vec![],
+        };
+
+        PhysicalPlan {
+            cost: operator_cost + inputs.iter().map(|input| input.cost).sum::<f64>(),
+            node,
+            inputs,
+            estimated_rows,
+            sorted_by,
+            statistics: None,
+        }
+    }
+
+    /// Lets every operator of the plan record its statistics once it is built, which are
+    /// returned in the order the operators are listed when the plan is displayed.
+    pub fn instrument(&mut self) -> Vec<Rc<RefCell<OperatorStatistics>>> {
+        let statistics = Rc::new(RefCell::new(OperatorStatistics::default()));
+        self.statistics = Some(Rc::clone(&statistics));
+
+        let mut all_statistics = vec![statistics];
+
+        for input in &mut self.inputs {
+            all_statistics.extend(input.instrument());
+        }
+
+        all_statistics
+    }
+
+    /// Turns the plan into the operators executing it.
+    pub fn build(self, engine: &BinaryEngine) -> Box<dyn Operator + '_> {
+        let mut inputs = self.inputs.into_iter().map(|input| input.build(engine));
+        let mut input = || inputs.next().expect("The operator is missing an input.");
+
+        let operator: Box<dyn Operator + '_> = match self.node {
+            PhysicalNode::Empty => Box::new(executor::Empty),
+            PhysicalNode::SeqScan(scan) => Box::new(SeqScan::new(&engine.data_manager, scan)),
+            PhysicalNode::IndexScan {
+                scan,
+                index,
+                key_range,
+                ..
+            } => Box::new(IndexScan::new(
+                &engine.data_manager,
+                &engine.index_manager,
+                scan,
+                index,
+                key_range,
+            )),
+            PhysicalNode::IndexOnlyScan {
+                scan,
+                index,
+                key_range,
+                ..
+            } => Box::new(IndexOnlyScan::new(
+                &engine.data_manager,
+                &engine.index_manager,
+                scan,
+                index,
+                key_range,
+            )),
+            PhysicalNode::Filter(where_clauses) => Box::new(Filter::new(input(), where_clauses)),
+            PhysicalNode::NestedLoopJoin {
+                outer_column,
+                inner_column,
+            } => Box::new(NestedLoopJoin::new(
+                input(),
+                input(),
+                outer_column,
+                inner_column,
+            )),
+            PhysicalNode::HashJoin {
+                probe_column,
+                build_column,
+            } => Box::new(HashJoin::new(
+                input(),
+                input(),
+                probe_column,
+                build_column,
+                engine.work_memory(),
+            )),
+            PhysicalNode::MergeJoin {
+                left_column,
+                right_column,
+            } => Box::new(MergeJoin::new(input(), input(), left_column, right_column)),
+            PhysicalNode::Aggregate {
+                group_by,
+                aggregates,
+            } => Box::new(executor::Aggregate::new(
+                input(),
+                group_by,
+                aggregates,
+                engine.work_memory(),
+            )),
+            PhysicalNode::Sort(order_by) => {
+                Box::new(Sort::new(input(), order_by, engine.work_memory()))
+            }
+            PhysicalNode::Project(columns) => Box::new(Project::new(input(), columns)),
+            PhysicalNode::Limit { limit, offset } => Box::new(Limit::new(input(), limit, offset)),
+        };
+
+        match self.statistics {
+            Some(statistics) => Box::new(Instrumented::new(operator, &engine.pager, statistics)),
+            None => operator,
+        }
+    }
+
+    fn write_tree(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        if depth > 0 {
+            write!(f, "\n{}  -> ", "    ".repeat(depth - 1))?;
+        }
+
+        write!(
+            f,
+            "{} (estimated rows: {})",
+            self.node,
+            self.estimated_rows.ceil()
+        )?;
+
+        for input in &self.inputs {
+            input.write_tree(f, depth + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for PhysicalPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_tree(f, 0)
+    }
+}
+
+impl fmt::Display for PhysicalNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PhysicalNode::Empty => write!(f, "Empty"),
+            PhysicalNode::SeqScan(scan) => {
+                write!(f, "Seq Scan on {}", scan.table_name)?;
+                write_where_clauses(f, &scan.where_clauses)
+            }
+            PhysicalNode::IndexScan {
+                scan,
+                index,
+                lookup,
+                ..
+            } => {
+                write!(f, "Index Scan on {} using {}", scan.table_name, index.name)?;
+                write_index_conditions(f, scan, index, lookup)
+            }
+            PhysicalNode::IndexOnlyScan {
+                scan,
+                index,
+                lookup,
+                ..
+            } => {
+                write!(
+                    f,
+                    "Index Only Scan on {} using {}",
+                    scan.table_name, index.name
+                )?;
+                write_index_conditions(f, scan, index, lookup)
+            }
+            PhysicalNode::Filter(where_clauses) => {
+                write!(f, "Filter")?;
+                write_where_clauses(f, where_clauses)
+            }
+            PhysicalNode::NestedLoopJoin {
+                outer_column,
+                inner_column,
+            } => write!(f, "Nested Loop Join on {} = {}", outer_column, inner_column),
+            PhysicalNode::HashJoin {
+                probe_column,
+                build_column,
+            } => write!(f, "Hash Join on {} = {}", probe_column, build_column),
+            PhysicalNode::MergeJoin {
+                left_column,
+                right_column,
+            } => write!(f, "Merge Join on {} = {}", left_column, right_column),
+            PhysicalNode::Aggregate {
+                group_by,
+                aggregates,
+            } => {
+                let aggregates: Vec<String> = aggregates.iter().map(Aggregate::to_string).collect();
+
+                write!(f, "Aggregate {}", aggregates.join(", "))?;
+
+                if !group_by.is_empty() {
+                    write!(f, " group by {}", group_by.join(", "))?;
+                }
+
+                Ok(())
+            }
+            PhysicalNode::Sort(order_by) => {
+                let columns: Vec<String> = order_by.iter().map(OrderBy::to_string).collect();
+
+                write!(f, "Sort by {}", columns.join(", "))
+            }
+            PhysicalNode::Project(columns) => write!(f, "Project {}", columns.join(", ")),
+            PhysicalNode::Limit { limit, offset } => {
+                write!(f, "Limit")?;
+
+                if let Some(limit) = limit {
+                    write!(f, " {}", limit)?;
+                }
+
+                if *offset > 0 {
+                    write!(f, " offset {}", offset)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+fn write_where_clauses(f: &mut fmt::Formatter<'_>, where_clauses: &[Condition]) -> fmt::Result {
+    if where_clauses.is_empty() {
+        return Ok(());
+    }
+
+    let conditions: Vec<String> = where_clauses.iter().map(Condition::to_string).collect();
+
+    write!(f, " where {}", conditions.join(" AND "))
+}
+
+/// The where clauses of an index scan, led by the lookup of the outer column if it has one.
+fn write_index_conditions(
+    f: &mut fmt::Formatter<'_>,
+    scan: &TableScan,
+    index: &Index,
+    lookup: &Option<String>,
+) -> fmt::Result {
+    let Some(outer_column) = lookup else {
+        return write_where_clauses(f, &scan.where_clauses);
+    };
+
+    write!(f, " where {} = {}", index.columns[0].name, outer_column)?;
+
+    for clause in &scan.where_clauses {
+        write!(f, " AND {}", clause)?;
+    }
+
+    Ok(())
+}
+
+/// Decides how every part of the logical plan is executed.
+pub fn choose(
+    engine: &BinaryEngine,
+    tables: &[Table],
+    plan: LogicalPlan,
+    snapshot: &Snapshot,
+) -> PhysicalPlan {
+    let choose_input = |input: Box<LogicalPlan>| choose(engine, tables, *input, snapshot);
+
+    match plan {
+        LogicalPlan::Scan {
+            table_name,
+            qualified,
+            where_clauses,
+            columns,
+        } => {
+            let table = find_table(tables, &table_name);
+
+            access_path(
+                engine,
+                table,
+                TableScan {
+                    qualifier: qualified.then(|| table_name.clone()),
+                    table_name,
+                    where_clauses,
+                    snapshot: snapshot.clone(),
+                    columns,
+                },
+            )
+        }
+        LogicalPlan::Empty => PhysicalPlan::new(PhysicalNode::Empty, vec![], 0.0, 0.0),
+        LogicalPlan::Filter {
+            input,
+            where_clauses,
+        } => filter(choose_input(input), where_clauses),
+        LogicalPlan::Join {
+            left,
+            right,
+            left_column,
+            right_column,
+        } => join(
+            engine,
+            tables,
+            *left,
+            *right,
+            left_column,
+            right_column,
+            snapshot,
+        ),
+        LogicalPlan::Aggregate {
+            input,
+            group_by,
+            aggregates,
+        } => {
+            let input = choose_input(input);
+            let estimated_rows = cost::group_count(input.estimated_rows, &group_by, tables);
+            let operator_cost = cost::processing_cost(input.estimated_rows);
+
+            PhysicalPlan::new(
+                PhysicalNode::Aggregate {
+                    group_by,
+                    aggregates,
+                },
+                vec![input],
+                estimated_rows,
+                operator_cost,
+            )
+        }
+        LogicalPlan::Sort { input, order_by } => {
+            // an ascending order by a join column is also met by merging the inputs of the join
+            let merged = match order_by.as_slice() {
+                [column] if !column.descending => {
+                    merge_join_sorted_by(engine, tables, &input, &column.column_name, snapshot)
+                }
+                _ => None,
+            };
+
+            let sorted = sort(engine, choose_input(input), order_by);
+
+            match merged {
+                Some(merged) if merged.cost < sorted.cost => merged,
+                _ => sorted,
+            }
+        }
+        LogicalPlan::Project { input, columns } => {
+            let input = choose_input(input);
+            let estimated_rows = input.estimated_rows;
+
+            PhysicalPlan::new(
+                PhysicalNode::Project(columns),
+                vec![input],
+                estimated_rows,
+                0.0,
+            )
+        }
+        LogicalPlan::Limit {
+            input,
+            limit,
+            offset,
+        } => {
+            let input = choose_input(input);
+            let remaining_rows = (input.estimated_rows - offset as f64).max(0.0);
+            let estimated_rows =
+                limit.map_or(remaining_rows, |limit| remaining_rows.min(limit as f64));
+
+            PhysicalPlan::new(
+                PhysicalNode::Limit { limit, offset },
+                vec![input],
+                estimated_rows,
+                0.0,
+            )
+        }
+    }
+}
+
+fn find_table<'a>(tables: &'a [Table], table_name: &str) -> &'a Table {
+    tables
+        .iter()
+        .find(|table| table.name == table_name)
+        .expect("The tables of a plan are loaded before it is made.")
+}
+
+fn filter(input: PhysicalPlan, where_clauses: Vec<Condition>) -> PhysicalPlan {
+    let estimated_rows = input.estimated_rows * cost::selectivity(None, &where_clauses);
+    let operator_cost = cost::processing_cost(input.estimated_rows);
+
+    PhysicalPlan::new(
+        PhysicalNode::Filter(where_clauses),
+        vec![input],
+        estimated_rows,
+        operator_cost,
+    )
+}
+
+/// Sorts the rows of the input, unless they already come out in that order.
+fn sort(engine: &BinaryEngine, input: PhysicalPlan, order_by: Vec<OrderBy>) -> PhysicalPlan {
+    let already_sorted = order_by.len() <= input.sorted_by.len()
+        && order_by
+            .iter()
+            .zip(&input.sorted_by)
+            .all(|(column, sorted_by)| !column.descending && column.column_name == *sorted_by);
+
+    if already_sorted {
+        return input;
+    }
+
+    let estimated_rows = input.estimated_rows;
+    let operator_cost = cost::sort_cost(estimated_rows, engine.work_memory);
+
+    PhysicalPlan::new(
+        PhysicalNode::Sort(order_by),
+        vec![input],
+        estimated_rows,
+        operator_cost,
+    )
+}
+
+/// Picks the cheapest way to join both inputs: a hash join building on the smaller one, a merge
+/// join of both sorted by their join columns, or a nested loop join looking up every row of one
+/// input in an index of the table the other one scans.
+fn join(
+    engine: &BinaryEngine,
+    tables: &[Table],
+    left: LogicalPlan,
+    right: LogicalPlan,
+    left_column: String,
+    right_column: String,
+    snapshot: &Snapshot,
+) -> PhysicalPlan {
+    let left_plan = choose(engine, tables, left.clone(), snapshot);
+    let right_plan = choose(engine, tables, right.clone(), snapshot);
+    let estimated_rows = cost::join_rows(
+        left_plan.estimated_rows,
+        right_plan.estimated_rows,
+        cost::column_statistics(tables, &left_column),
+        cost::column_statistics(tables, &right_column),
+    );
+
+    let mut candidates = vec![
+        hash_join(
+            engine,
+            left_plan.clone(),
+            right_plan.clone(),
+            left_column.clone(),
+            right_column.clone(),
+            estimated_rows,
+        ),
+        merge_join(
+            engine,
+            left_plan.clone(),
+            right_plan.clone(),
+            left_column.clone(),
+            right_column.clone(),
+            estimated_rows,
+        ),
+    ];
+
+    for (outer, inner, outer_column, inner_column) in [
+        (left_plan, &right, &left_column, &right_column),
+        (right_plan, &left, &right_column, &left_column),
+    ] {
+        if let Some(inner) = index_lookup(
+            engine,
+            tables,
+            inner,
+            inner_column,
+            outer_column,
+            &outer,
+            snapshot,
+        ) {
+            let operator_cost = cost::processing_cost(outer.estimated_rows);
+
+            candidates.push(PhysicalPlan::new(
+                PhysicalNode::NestedLoopJoin {
+                    outer_column: outer_column.clone(),
+                    inner_column: inner_column.clone(),
+                },
+                vec![outer, inner],
+                estimated_rows,
+                operator_cost,
+            ));
+        }
+    }
+
+    candidates
+        .into_iter()
+        .min_by(|left, right| left.cost.total_cmp(&right.cost))
+        .unwrap()
+}
+
+fn hash_join(
+    engine: &BinaryEngine,
+    left: PhysicalPlan,
+    right: PhysicalPlan,
+    left_column: String,
+    right_column: String,
+    estimated_rows: f64,
+) -> PhysicalPlan {
+    let (probe, build, probe_column, build_column) =
+        match left.estimated_rows < right.estimated_rows {
+            true => (right, left, right_column, left_column),
+            false => (left, right, left_column, right_column),
+        };
+
+    let operator_cost = cost::hash_join_cost(
+        build.estimated_rows,
+        probe.estimated_rows,
+        engine.work_memory,
+    );
+
+    PhysicalPlan::new(
+        PhysicalNode::HashJoin {
+            probe_column,
+            build_column,
+        },
+        vec![probe, build],
+        estimated_rows,
+        operator_cost,
+    )
+}
+
+/// Merges both inputs, sorting those that do not come out sorted by their join column already.
+fn merge_join(
+    engine: &BinaryEngine,
+    left: PhysicalPlan,
+    right: PhysicalPlan,
+    left_column: String,
+    right_column: String,
+    estimated_rows: f64,
+) -> PhysicalPlan {
+    let sorted_by = |column_name: &str| {
+        vec![OrderBy {
+            column_name: column_name.to_string(),
+            descending: false,
+        }]
+    };
+
+    let left = sort(engine, left, sorted_by(&left_column));
+    let right = sort(engine, right, sorted_by(&right_column));
+    let operator_cost = cost::merge_join_cost(left.estimated_rows, right.estimated_rows);
+
+    PhysicalPlan::new(
+        PhysicalNode::MergeJoin {
+            left_column,
+            right_column,
+        },
+        vec![left, right],
+        estimated_rows,
+        operator_cost,
+    )
+}
+
+/// Plans a merge join on the column that the rows are to be sorted by, if the plan joins on it,
+/// possibly below filters which keep the order of the rows.
+fn merge_join_sorted_by(
+    engine: &BinaryEngine,
+    tables: &[Table],
+    plan: &LogicalPlan,
+    column_name: &str,
+    snapshot: &Snapshot,
+) -> Option<PhysicalPlan> {
+    match plan {
+        LogicalPlan::Filter {
+            input,
+            where_clauses,
+        } => {
+            let input = merge_join_sorted_by(engine, tables, input, column_name, snapshot)?;
+
+            Some(filter(input, where_clauses.clone()))
+        }
+        LogicalPlan::Join {
+            left,
+            right,
+            left_column,
+            right_column,
+        } if column_name == left_column || column_name == right_column => {
+            let left = choose(engine, tables, *left.clone(), snapshot);
+            let right = choose(engine, tables, *right.clone(), snapshot);
+            let estimated_rows = cost::join_rows(
+                left.estimated_rows,
+                right.estimated_rows,
+                cost::column_statistics(tables, left_column),
+                cost::column_statistics(tables, right_column),
+            );
+
+            // the side sorted by the column comes first, so that the rows keep its order
+            let (left, right, left_column, right_column) = match column_name == left_column {
+                true => (left, right, left_column, right_column),
+                false => (right, left, right_column, left_column),
+            };
+
+            Some(merge_join(
+                engine,
+                left,
+                right,
+                left_column.clone(),
+                right_column.clone(),
+                estimated_rows,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Plans the inner input of a nested loop join as a scan of its table that looks up the values
+/// of the outer column in an index leading with the inner column, if the table has one.
+fn index_lookup(
+    engine: &BinaryEngine,
+    tables: &[Table],
+    inner: &LogicalPlan,
+    inner_column: &str,
+    outer_column: &str,
+    outer: &PhysicalPlan,
+    snapshot: &Snapshot,
+) -> Option<PhysicalPlan> {
+    let LogicalPlan::Scan {
+        table_name,
+        qualified: true,
+        where_clauses,
+        columns,
+    } = inner
+    else {
+        return None;
+    };
+
+    let table = find_table(tables, table_name);
+    let column_name = inner_column.strip_prefix(&format!("{}.", table_name))?;
+    let data_pages = engine.data_manager.data_page_count(&table.name);
+    let rows = cost::table_rows(table, data_pages);
+
+    let scan = TableScan {
+        table_name: table_name.clone(),
+        where_clauses: where_clauses.clone(),
+        snapshot: snapshot.clone(),
+        columns: columns.clone(),
+        qualifier: Some(table_name.clone()),
+    };
+
+    let matched_rows = match table.indices.iter().any(|index| {
+        index.unique && matches!(index.columns.as_slice(), [column] if column.name == column_name)
+    }) {
+        true => 1.0_f64.min(rows),
+        false => rows * cost::lookup_selectivity(table, column_name),
+    };
+
+    let mut cheapest: Option<(f64, &Index, bool)> = None;
+
+    for index in &table.indices {
+        let looks_up_column = index.where_clauses.is_empty()
+            && index.columns[0].function.is_none()
+            && index.columns[0].name == column_name
+            && (index.index_type != IndexType::Hash || index.columns.len() == 1);
+
+        if !looks_up_column {
+            continue;
+        }
+
+        let index_only =
+            covers_scan(index, table, &scan) && sees_all_versions(table, &scan.snapshot);
+
+        let cost = match index_only {
+            true => cost::index_only_scan_cost(matched_rows),
+            false => cost::index_scan_cost(matched_rows, data_pages),
+        };
+
+        if cheapest.is_none_or(|(cheapest, _, _)| cost < cheapest) {
+            cheapest = Some((cost, index, index_only));
+        }
+    }
+
+    let (lookup_cost, index, index_only) = cheapest?;
+    let estimated_rows = matched_rows * cost::selectivity(Some(table), &scan.where_clauses);
+    let (index, key_range, lookup) = (
+        index.clone(),
+        KeyRange {
+            prefix: vec![],
+            last: None,
+        },
+        Some(outer_column.to_string()),
+    );
+
+    let node = match index_only {
+        true => PhysicalNode::IndexOnlyScan {
+            scan,
+            index,
+            key_range,
+            lookup,
+        },
+        false => PhysicalNode::IndexScan {
+            scan,
+            index,
+            key_range,
+            lookup,
+        },
+    };
+
+    // the index is looked up once for every outer row
+    Some(PhysicalPlan::new(
+        node,
+        vec![],
+        estimated_rows,
+        lookup_cost * outer.estimated_rows,
+    ))
+}
+
+/// Picks the cheapest way to read the rows of the table: all of its data pages, the part
+/// of an index matching the where clauses, or only that part if the index stores every column needed.
+fn access_path(engine: &BinaryEngine, table: &Table, scan: TableScan) -> PhysicalPlan {
+    let data_pages = engine.data_manager.data_page_count(&table.name);
+    let rows = cost::table_rows(table, data_pages);
+    let estimated_rows = rows * cost::selectivity(Some(table), &scan.where_clauses);
+
+    let mut cheapest = (cost::seq_scan_cost(data_pages, rows), None);
+
+    for (index, key_range) in table.index_lookups(&scan.where_clauses) {
+        let mut matched_rows = rows
+            * cost::selectivity(
+                Some(table),
+                cost::key_range_clauses(index, &key_range, &scan.where_clauses),
+            );
+
+        if index.unique && key_range.is_point_lookup(index) {
+            matched_rows = matched_rows.min(1.0);
+        }
+
+        let index_only =
+            covers_scan(index, table, &scan) && sees_all_versions(table, &scan.snapshot);
+
+        let cost = match index_only {
+            true => cost::index_only_scan_cost(matched_rows),
+            false => cost::index_scan_cost(matched_rows, data_pages),
+        };
+
+        if cost < cheapest.0 {
+            cheapest = (cost, Some((index.clone(), key_range, index_only)));
+        }
+    }
+
+    let node = match cheapest.1 {
+        None => PhysicalNode::SeqScan(scan),
+        Some((index, key_range, true)) => PhysicalNode::IndexOnlyScan {
+            scan,
+            index,
+            key_range,
+            lookup: None,
+        },
+        Some((index, key_range, false)) => PhysicalNode::IndexScan {
+            scan,
+            index,
+            key_range,
+            lookup: None,
+        },
+    };
+
+    PhysicalPlan::new(node, vec![], estimated_rows, cheapest.0)
+}
+
+/// Whether the snapshot sees exactly the versions the indices of the table list,
+/// which is the case if none of them is deleted and every write to the table is visible.
+fn sees_all_versions(table: &Table, snapshot: &Snapshot) -> bool {
+    table.dead_versions == 0 && snapshot.sees_commit_of(table.last_writer)
+}
+
+/// Whether the index stores every column the scan keeps or filters on.
+fn covers_scan(index: &Index, table: &Table, scan: &TableScan) -> bool {
+    let stored_columns = index.stored_columns();
+
+    let read_columns: Vec<&str> = match &scan.columns {
+        Some(columns) => columns.iter().map(String::as_str).collect(),
+        None => table
+            .columns
+            .iter()
+            .map(|column| column.name.as_str())
+            .chain([table.primary_key.name.as_str()])
+            .collect(),
+    };
+
+    read_columns
+        .into_iter()
+        .chain(
+            scan.where_clauses
+                .iter()
+                .map(|clause| clause.column_name.as_str()),
+        )
+        .all(|column_name| stored_columns.contains(&column_name))
+}
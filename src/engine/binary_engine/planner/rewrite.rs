@@ -0,0 +1,451 @@
+use crate::dynamic_record::Value;
+use crate::metadata::{IndexKey, SqlType, Table};
+use crate::sql_parser::query::{Condition, Predicate};
+
+use super::logical::{aggregated_columns, LogicalPlan};
+
+/// Moves the where clauses of filters into the scans of the tables they are about,
+/// so that rows are dropped while they are read instead of after they were joined.
+pub fn push_down_predicates(plan: LogicalPlan) -> LogicalPlan {
+    match plan {
+        LogicalPlan::Filter {
+            input,
+            where_clauses,
+        } => push_down(push_down_predicates(*input), where_clauses),
+        plan => plan.map_inputs(push_down_predicates),
+    }
+}
+
+fn push_down(plan: LogicalPlan, where_clauses: Vec<Condition>) -> LogicalPlan {
+    if where_clauses.is_empty() {
+        return plan;
+    }
+
+    match plan {
+        LogicalPlan::Scan {
+            table_name,
+            qualified,
+            where_clauses: own_clauses,
+            columns,
+        } => {
+            let prefix = format!("{}.", table_name);
+
+            let (clauses, other_clauses): (Vec<Condition>, Vec<Condition>) = where_clauses
+                .into_iter()
+                .partition(|clause| !qualified || clause.column_name.starts_with(&prefix));
+
+            let scan = LogicalPlan::Scan {
+                where_clauses: own_clauses
+                    .into_iter()
+                    .chain(clauses.into_iter().map(|mut clause| {
+                        if qualified {
+                            clause.column_name.drain(..prefix.len());
+                        }
+
+                        clause
+                    }))
+                    .collect(),
+                table_name,
+                qualified,
+                columns,
+            };
+
+            filter(scan, other_clauses)
+        }
+        LogicalPlan::Filter {
+            input,
+            where_clauses: own_clauses,
+        } => push_down(
+            *input,
+            own_clauses.into_iter().chain(where_clauses).collect(),
+        ),
+        LogicalPlan::Join {
+            left,
+            right,
+            left_column,
+            right_column,
+        } => {
+            let (left_clauses, other_clauses) = partition_by_tables(&left, where_clauses);
+            let (right_clauses, other_clauses) = partition_by_tables(&right, other_clauses);
+
+            let join = LogicalPlan::Join {
+                left: Box::new(push_down(*left, left_clauses)),
+                right: Box::new(push_down(*right, right_clauses)),
+                left_column,
+                right_column,
+            };
+
+            filter(join, other_clauses)
+        }
+        plan => filter(plan, where_clauses),
+    }
+}
+
+/// Splits the where clauses into those on columns of the tables the plan reads and the rest.
+fn partition_by_tables(
+    plan: &LogicalPlan,
+    where_clauses: Vec<Condition>,
+) -> (Vec<Condition>, Vec<Condition>) {
+    let table_names = plan.table_names();
+
+    where_clauses.into_iter().partition(|clause| {
+        table_names
+            .iter()
+            .any(|table_name| clause.column_name.starts_with(&format!("{}.", table_name)))
+    })
+}
+
+fn filter(plan: LogicalPlan, where_clauses: Vec<Condition>) -> LogicalPlan {
+    if where_clauses.is_empty() {
+        return plan;
+    }
+
+    LogicalPlan::Filter {
+        input: Box::new(plan),
+        where_clauses,
+    }
+}
+
+/// Evaluates what is known before any row is read: scans whose where clauses contradict
+/// each other and limits of zero produce no rows, and neither does anything built on them
+/// alone. Where clauses implied by an equality on the same column are dropped.
+pub fn fold_constants(plan: LogicalPlan, tables: &[Table]) -> LogicalPlan {
+    match plan.map_inputs(|input| fold_constants(input, tables)) {
+        LogicalPlan::Scan {
+            table_name,
+            qualified,
+            where_clauses,
+            columns,
+        } => {
+            let where_clauses = match tables.iter().find(|table| table.name == table_name) {
+                Some(table) => fold_where_clauses(where_clauses, table),
+                None => Some(where_clauses),
+            };
+
+            match where_clauses {
+                Some(where_clauses) => LogicalPlan::Scan {
+                    table_name,
+                    qualified,
+                    where_clauses,
+                    columns,
+                },
+                None => LogicalPlan::Empty,
+            }
+        }
+        LogicalPlan::Limit { limit: Some(0), .. } => LogicalPlan::Empty,
+        LogicalPlan::Join { left, right, .. } if left.is_empty() || right.is_empty() => {
+            LogicalPlan::Empty
+        }
+        LogicalPlan::Filter { input, .. }
+        | LogicalPlan::Sort { input, .. }
+        | LogicalPlan::Project { input, .. }
+        | LogicalPlan::Limit { input, .. }
+            if input.is_empty() =>
+        {
+            LogicalPlan::Empty
+        }
+        plan => plan,
+    }
+}
+
+/// Returns None if no row can match all where clauses.
+fn fold_where_clauses(where_clauses: Vec<Condition>, table: &Table) -> Option<Vec<Condition>> {
+    let key_of = |clause: &Condition, literal: &str| {
+        let data_type = match clause.function {
+            Some(_) => &SqlType::Varchar,
+            None => table.column_type(&clause.column_name)?,
+        };
+
+        IndexKey::from_literal(literal, data_type)
+    };
+
+    let mut folded: Vec<Condition> = vec![];
+
+    for clause in where_clauses {
+        if let Predicate::Between(lower, upper) = &clause.predicate {
+            if let (Some(lower), Some(upper)) = (key_of(&clause, lower), key_of(&clause, upper)) {
+                if lower > upper {
+                    return None;
+                }
+            }
+        }
+
+        if !folded.contains(&clause) {
+            folded.push(clause);
+        }
+    }
+
+    let same_operand = |clause: &Condition, other: &Condition| {
+        clause.column_name == other.column_name && clause.function == other.function
+    };
+
+    for clause in folded.clone() {
+        let constant: Value = match &clause.predicate {
+            Predicate::Equal(literal) => match key_of(&clause, literal) {
+                Some(key) => key.to_value(),
+                None => continue,
+            },
+            _ => continue,
+        };
+
+        // already dropped, as it is implied by another equality
+        if !folded.contains(&clause) {
+            continue;
+        }
+
+        if folded
+            .iter()
+            .filter(|other| same_operand(&clause, other))
+            .any(|other| !constant.satisfies(&other.predicate))
+        {
+            return None;
+        }
+
+        folded.retain(|other| *other == clause || !same_operand(&clause, other));
+    }
+
+    Some(folded)
+}
+
+/// Lets every scan only keep the columns the operators above it read, all of them if
+/// there is no list of required columns.
+pub fn prune_projections(plan: LogicalPlan, required: Option<Vec<String>>) -> LogicalPlan {
+    let with = |required: &Option<Vec<String>>, columns: Vec<&String>| {
+        required.clone().map(|mut required| {
+            required.extend(columns.into_iter().cloned());
+            required
+        })
+    };
+
+    match plan {
+        LogicalPlan::Scan {
+            table_name,
+            qualified,
+            where_clauses,
+            ..
+        } => {
+            let prefix = format!("{}.", table_name);
+
+            let columns = required.map(|required| {
+                let mut columns: Vec<String> = required
+                    .iter()
+                    .filter_map(|column_name| match qualified {
+                        true => column_name.strip_prefix(&prefix).map(String::from),
+                        false => Some(column_name.clone()),
+                    })
+                    .collect();
+
+                columns.sort();
+                columns.dedup();
+                columns
+            });
+
+            LogicalPlan::Scan {
+                table_name,
+                qualified,
+                where_clauses,
+                columns,
+            }
+        }
+        LogicalPlan::Empty => LogicalPlan::Empty,
+        LogicalPlan::Filter {
+            input,
+            where_clauses,
+        } => {
+            let required = with(
+                &required,
+                where_clauses
+                    .iter()
+                    .map(|clause| &clause.column_name)
+                    .collect(),
+            );
+
+            LogicalPlan::Filter {
+                input: Box::new(prune_projections(*input, required)),
+                where_clauses,
+            }
+        }
+        LogicalPlan::Join {
+            left,
+            right,
+            left_column,
+            right_column,
+        } => {
+            let required = with(&required, vec![&left_column, &right_column]);
+
+            LogicalPlan::Join {
+                left: Box::new(prune_projections(*left, required.clone())),
+                right: Box::new(prune_projections(*right, required)),
+                left_column,
+                right_column,
+            }
+        }
+        LogicalPlan::Aggregate {
+            input,
+            group_by,
+            aggregates,
+        } => {
+            let required = group_by
+                .iter()
+                .chain(aggregated_columns(&aggregates))
+                .cloned()
+                .collect();
+
+            LogicalPlan::Aggregate {
+                input: Box::new(prune_projections(*input, Some(required))),
+                group_by,
+                aggregates,
+            }
+        }
+        LogicalPlan::Sort { input, order_by } => {
+            let required = with(
+                &required,
+                order_by.iter().map(|column| &column.column_name).collect(),
+            );
+
+            LogicalPlan::Sort {
+                input: Box::new(prune_projections(*input, required)),
+                order_by,
+            }
+        }
+        LogicalPlan::Project { input, columns } => LogicalPlan::Project {
+            input: Box::new(prune_projections(*input, Some(columns.clone()))),
+            columns,
+        },
+        LogicalPlan::Limit {
+            input,
+            limit,
+            offset,
+        } => LogicalPlan::Limit {
+            input: Box::new(prune_projections(*input, required)),
+            limit,
+            offset,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::SelectQuery;
+    use crate::metadata::Table;
+    use crate::sql_parser::query::Join;
+
+    fn users() -> Table {
+        Table::new(
+            String::from("users"),
+            vec![
+                vec![String::from("name"), String::from("VARCHAR")],
+                vec![String::from("age"), String::from("INTEGER")],
+            ],
+        )
+    }
+
+    fn rewrite(query: &SelectQuery) -> LogicalPlan {
+        let plan = push_down_predicates(LogicalPlan::from_query(query));
+        let plan = fold_constants(plan, &[users()]);
+
+        prune_projections(plan, None)
+    }
+
+    fn scan(table_name: &str, where_clauses: Vec<Condition>, columns: &[&str]) -> LogicalPlan {
+        LogicalPlan::Scan {
+            table_name: table_name.to_string(),
+            qualified: table_name != "users",
+            where_clauses,
+            columns: Some(columns.iter().map(|column| column.to_string()).collect()),
+        }
+    }
+
+    #[test]
+    fn test_pushes_where_clauses_into_the_scans_of_their_tables() {
+        let query = SelectQuery {
+            joins: vec![Join {
+                table_name: String::from("orders"),
+                left_column: String::from("orders.user_id"),
+                right_column: String::from("customers.id"),
+            }],
+            where_clauses: vec![
+                Condition::new("orders.total", Predicate::GreaterThan(String::from("5"))),
+                Condition::new("customers.name", Predicate::Equal(String::from("'ann'"))),
+            ],
+            ..SelectQuery::new(
+                String::from("customers"),
+                vec![String::from("orders.total")],
+                vec![],
+            )
+        };
+
+        assert_eq!(
+            rewrite(&query),
+            LogicalPlan::Project {
+                input: Box::new(LogicalPlan::Join {
+                    left: Box::new(scan(
+                        "customers",
+                        vec![Condition::new(
+                            "name",
+                            Predicate::Equal(String::from("'ann'"))
+                        )],
+                        &["id"]
+                    )),
+                    right: Box::new(scan(
+                        "orders",
+                        vec![Condition::new(
+                            "total",
+                            Predicate::GreaterThan(String::from("5"))
+                        )],
+                        &["total", "user_id"]
+                    )),
+                    left_column: String::from("customers.id"),
+                    right_column: String::from("orders.user_id"),
+                }),
+                columns: vec![String::from("orders.total")],
+            }
+        );
+    }
+
+    #[test]
+    fn test_folds_where_clauses_that_are_implied_or_contradict_each_other() {
+        let select = |where_clauses: Vec<Condition>| {
+            rewrite(&SelectQuery::new(
+                String::from("users"),
+                vec![String::from("name")],
+                where_clauses,
+            ))
+        };
+
+        let age = |predicate| Condition::new("age", predicate);
+
+        assert_eq!(
+            select(vec![
+                age(Predicate::Equal(String::from("5"))),
+                age(Predicate::GreaterThanOrEqual(String::from("3"))),
+                age(Predicate::Equal(String::from("5"))),
+            ]),
+            LogicalPlan::Project {
+                input: Box::new(scan(
+                    "users",
+                    vec![age(Predicate::Equal(String::from("5")))],
+                    &["name"]
+                )),
+                columns: vec![String::from("name")],
+            }
+        );
+        assert!(select(vec![
+            age(Predicate::Equal(String::from("5"))),
+            age(Predicate::GreaterThan(String::from("7"))),
+        ])
+        .is_empty());
+        assert!(select(vec![age(Predicate::Between(
+            String::from("9"),
+            String::from("3")
+        ))])
+        .is_empty());
+
+        assert!(rewrite(&SelectQuery {
+            limit: Some(0),
+            ..SelectQuery::new(String::from("users"), vec![], vec![])
+        })
+        .is_empty());
+    }
+}
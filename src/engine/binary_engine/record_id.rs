@@ -0,0 +1,24 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Location of a record: the data page it is stored on and its position within that page.
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub struct RecordId {
+    pub data_page: usize,
+    pub slot: usize,
+}
+
+impl RecordId {
+    pub fn new(data_page: usize, slot: usize) -> Self {
+        RecordId { data_page, slot }
+    }
+}
+
+impl fmt::Display for RecordId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.data_page, self.slot)
+    }
+}
@@ -1,50 +1,294 @@
-use std::collections::HashMap;
+use std::iter;
 
-use crate::engine;
+use crate::dynamic_record::DynamicRecord;
+use crate::engine::{self, RowCursor, SelectQuery};
+use crate::metadata;
+use crate::sql_parser::query::{Aggregate, Condition, Join, OrderBy};
+
+use super::executor::Operator;
+use super::lock_manager::ScanGuard;
+use super::mvcc::HorizonGuard;
+use super::planner::{self, PhysicalPlan};
+use super::utils::selected_all_columns;
 
 impl engine::Select for super::BinaryEngine {
-    fn select(
+    fn query(&self, query: SelectQuery) -> Result<RowCursor<'_>, String> {
+        let (plan, guards) = self.plan_query(query)?;
+
+        Ok(RowCursor::new(Execution {
+            plan: plan.build(self),
+            failed: false,
+            _guards: guards,
+        }))
+    }
+}
+
+/// Keeps the rows a plan reads where they are and visible to its snapshot until it was executed,
+/// without keeping commits of other sessions away.
+pub(super) struct QueryGuards<'a> {
+    _scans: Vec<ScanGuard<'a>>,
+    _horizon: HorizonGuard,
+}
+
+impl super::BinaryEngine {
+    /// Checks the query against the tables it reads and plans it.
+    pub(super) fn plan_query(
         &self,
-        table_name: String,
-        column_names: Vec<String>,
-        where_clauses: HashMap<String, String>,
-    ) -> super::EngineResult {
-        let table = self.table_manager.load_meta_data(&table_name);
-        if table.is_err() {
-            return Err(String::from("This table does not exist."));
-        }
-        let table = table.unwrap();
+        query: SelectQuery,
+    ) -> Result<(PhysicalPlan, QueryGuards<'_>), String> {
+        let table_names: Vec<&String> = iter::once(&query.table_name)
+            .chain(query.joins.iter().map(|join| &join.table_name))
+            .collect();
+
+        let scans = table_names
+            .iter()
+            .map(|table_name| self.lock_for_scan(table_name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // the cursor may outlive the statement and even the transaction that took the snapshot
+        let guards = QueryGuards {
+            _scans: scans,
+            _horizon: self.pager.hold_horizon(),
+        };
+
+        self.pager.start_statement();
+        let snapshot = self.pager.snapshot();
 
-        let data_page_indices = table.data_page_indices(&where_clauses);
+        let tables = table_names
+            .iter()
+            .map(|table_name| {
+                self.table_manager
+                    .load_meta_data(table_name)
+                    .map_err(|_| String::from("This table does not exist."))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
-        if  !table.all_columns_exist(column_names.clone()) {
-            return Err(String::from(
-                "Please choose only columns that exist on this table.",
+        let query = if query.joins.is_empty() {
+            check_columns(&tables[0], &query)?;
+            query
+        } else {
+            resolve_columns(&tables, query)?
+        };
+
+        let grouped = !query.group_by.is_empty()
+            || query
+                .selection
+                .iter()
+                .any(|item| Aggregate::from_selection(item).is_some());
+
+        if let Some(column_name) = query.selection.iter().find(|item| {
+            grouped && Aggregate::from_selection(item).is_none() && !query.group_by.contains(item)
+        }) {
+            return Err(format!(
+                "Column '{}' has to be aggregated or listed in GROUP BY.",
+                column_name
             ));
         }
 
-        let records = self.data_manager.load_table_contents(
-            &table_name,
-            column_names,
-            where_clauses,
-            data_page_indices,
-        );
+        Ok((planner::plan(self, &tables, &query, &snapshot), guards))
+    }
+}
+
+/// Pulls the rows of a plan, keeping the tables it reads from being compacted until it is dropped.
+/// It ends after the first error.
+struct Execution<'a> {
+    plan: Box<dyn Operator + 'a>,
+    failed: bool,
+    _guards: QueryGuards<'a>,
+}
 
-        match records {
-            Ok(records) => Ok(super::EngineResponse {
-                records: Some(records),
-                table: None,
-            }),
-            Err(e) => Err(e.to_string()),
+impl Iterator for Execution<'_> {
+    type Item = Result<DynamicRecord, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
         }
+
+        match self.plan.next() {
+            Ok(record) => record.map(Ok),
+            Err(error) => {
+                self.failed = true;
+                Some(Err(error.to_string()))
+            }
+        }
+    }
+}
+
+/// The columns of the table a query reads, or None if it reads all of them.
+fn referenced_columns(query: &SelectQuery, aggregates: &[Aggregate]) -> Option<Vec<String>> {
+    if query.selection.is_empty() || selected_all_columns(&query.selection) {
+        return None;
+    }
+
+    Some(
+        query
+            .selection
+            .iter()
+            .filter(|item| Aggregate::from_selection(item).is_none())
+            .chain(
+                aggregates
+                    .iter()
+                    .filter_map(|aggregate| aggregate.column_name.as_ref()),
+            )
+            .chain(&query.group_by)
+            .chain(
+                query
+                    .order_by
+                    .iter()
+                    .map(|column| &column.column_name)
+                    .filter(|column_name| Aggregate::from_selection(column_name).is_none()),
+            )
+            .cloned()
+            .collect(),
+    )
+}
+
+fn has_column(table: &metadata::Table, column_name: &str) -> bool {
+    table.primary_key.name == column_name
+        || table
+            .columns
+            .iter()
+            .any(|column| column.name == column_name)
+}
+
+/// Checks that every column a query on a single table reads exists on it.
+fn check_columns(table: &metadata::Table, query: &SelectQuery) -> Result<(), String> {
+    let aggregates: Vec<Aggregate> = query
+        .selection
+        .iter()
+        .filter_map(|item| Aggregate::from_selection(item))
+        .collect();
+
+    if referenced_columns(query, &aggregates)
+        .unwrap_or_default()
+        .iter()
+        .all(|column_name| has_column(table, column_name))
+    {
+        Ok(())
+    } else {
+        Err(String::from(
+            "Please choose only columns that exist on this table.",
+        ))
     }
 }
 
+/// Names every column of a query joining tables after its table, as in `users.name`,
+/// which is how the rows of joined tables name their columns.
+fn resolve_columns(tables: &[metadata::Table], query: SelectQuery) -> Result<SelectQuery, String> {
+    let resolve = |column_name: &str| -> Result<String, String> {
+        if let Some((table_name, name)) = column_name.split_once('.') {
+            return match tables.iter().find(|table| table.name == table_name) {
+                Some(table) if has_column(table, name) => Ok(column_name.to_string()),
+                _ => Err(format!("Column '{}' does not exist.", column_name)),
+            };
+        }
+
+        let mut owners = tables.iter().filter(|table| has_column(table, column_name));
+
+        match (owners.next(), owners.next()) {
+            (Some(table), None) => Ok(format!("{}.{}", table.name, column_name)),
+            (None, _) => Err(format!("Column '{}' does not exist.", column_name)),
+            (Some(_), Some(_)) => Err(format!("Column '{}' is ambiguous.", column_name)),
+        }
+    };
+
+    let resolve_item = |item: &String| -> Result<String, String> {
+        if selected_all_columns(std::slice::from_ref(item)) {
+            return Ok(item.clone());
+        }
+
+        match Aggregate::from_selection(item) {
+            Some(Aggregate {
+                function,
+                column_name,
+            }) => Ok(Aggregate {
+                function,
+                column_name: column_name.as_deref().map(resolve).transpose()?,
+            }
+            .to_string()),
+            None => resolve(item),
+        }
+    };
+
+    let joins = query
+        .joins
+        .into_iter()
+        .map(|join| {
+            Ok(Join {
+                left_column: resolve(&join.left_column)?,
+                right_column: resolve(&join.right_column)?,
+                ..join
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(SelectQuery {
+        selection: query
+            .selection
+            .iter()
+            .map(resolve_item)
+            .collect::<Result<_, _>>()?,
+        where_clauses: query
+            .where_clauses
+            .into_iter()
+            .map(|clause| {
+                Ok(Condition {
+                    column_name: resolve(&clause.column_name)?,
+                    ..clause
+                })
+            })
+            .collect::<Result<_, String>>()?,
+        joins,
+        group_by: query
+            .group_by
+            .iter()
+            .map(|column_name| resolve(column_name))
+            .collect::<Result<_, _>>()?,
+        order_by: query
+            .order_by
+            .into_iter()
+            .map(|column| {
+                Ok(OrderBy {
+                    column_name: resolve_item(&column.column_name)?,
+                    ..column
+                })
+            })
+            .collect::<Result<_, String>>()?,
+        ..query
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use super::*;
-    use crate::engine::{CreateTable, Insert, Select};
-    use crate::{engine::binary_engine::BinaryEngine, io_test_context::FileTestContext};
+    use std::fs;
+    use std::ops::Bound;
+
+    use crate::engine::binary_engine::BinaryEngine;
+    use crate::engine::{
+        CreateIndex, CreateTable, Engine, Insert, OpenOptions, Select, SelectQuery,
+    };
+    use crate::io_test_context::{FileTestContext, TestDatabase};
+    use crate::metadata::{ColumnRange, Index, IndexColumn, IndexType, KeyRange};
+    use crate::sql_parser::query::{Condition, Function, Predicate};
+    use crate::sql_parser::SqlParser;
+
+    /// The values of the columns of every row in the order the query returns them.
+    fn rows(engine: &BinaryEngine, query: &str, column_names: &[&str]) -> Vec<Vec<String>> {
+        engine
+            .execute(SqlParser().parse_query(query.to_string()).unwrap())
+            .unwrap()
+            .records
+            .unwrap()
+            .iter()
+            .map(|record| {
+                column_names
+                    .iter()
+                    .map(|column_name| record.fields.get(*column_name).unwrap().to_string())
+                    .collect()
+            })
+            .collect()
+    }
 
     #[test]
     fn test_can_select_from_table() {
@@ -75,7 +319,7 @@ mod tests {
         let result = engine.select(
             context.table_name().to_string(),
             vec![String::from("name")],
-            HashMap::new(),
+            vec![],
         );
 
         match result {
@@ -96,7 +340,7 @@ mod tests {
         let result = engine.select(
             String::from("non_existant_table"),
             vec![String::from("name")],
-            HashMap::new(),
+            vec![],
         );
 
         if result.is_ok() {
@@ -127,7 +371,7 @@ mod tests {
         let result = engine.select(
             context.table_name().to_string(),
             vec![String::from("email")],
-            HashMap::new(),
+            vec![],
         );
 
         if result.is_ok() {
@@ -164,9 +408,10 @@ mod tests {
             )
             .unwrap();
 
-        let mut where_clauses = HashMap::new();
-        where_clauses.insert(String::from("name"), "some".to_string());
-        where_clauses.insert(String::from("email"), "some@mail.com".to_string());
+        let where_clauses = vec![
+            Condition::new("name", Predicate::Equal("some".to_string())),
+            Condition::new("email", Predicate::Equal("some@mail.com".to_string())),
+        ];
 
         let result = engine.select(context.table_name().to_string(), vec![], where_clauses);
 
@@ -224,8 +469,7 @@ mod tests {
             )
             .unwrap();
 
-        let mut where_clauses = HashMap::new();
-        where_clauses.insert(String::from("id"), "1".to_string());
+        let where_clauses = vec![Condition::new("id", Predicate::Equal("1".to_string()))];
 
         let result = engine.select(
             context.table_name().to_string(),
@@ -279,7 +523,7 @@ mod tests {
         let result = engine.select(
             context.table_name().to_string(),
             vec![String::from("*")],
-            HashMap::new(),
+            vec![],
         );
 
         match result {
@@ -292,4 +536,743 @@ mod tests {
             Err(message) => panic!("{}", message),
         }
     }
+
+    #[test]
+    fn test_can_select_ranges_using_an_index_in_typed_order() {
+        let context = FileTestContext::new();
+        let engine = BinaryEngine::new();
+
+        engine
+            .create_table(
+                context.table_name().to_string(),
+                vec![
+                    vec!["email".to_string(), "VARCHAR".to_string()],
+                    vec!["age".to_string(), "INTEGER".to_string()],
+                ],
+            )
+            .unwrap();
+
+        engine
+            .create_index(
+                context.table_name().to_string(),
+                Index::new(String::from("age_index"), vec![IndexColumn::new("age")]),
+            )
+            .unwrap();
+
+        engine
+            .create_index(
+                context.table_name().to_string(),
+                Index::new(String::from("email_index"), vec![IndexColumn::new("email")]),
+            )
+            .unwrap();
+
+        engine
+            .insert(
+                context.table_name().to_string(),
+                vec!["email".to_string(), "age".to_string()],
+                (1..=12)
+                    .map(|age| vec![format!("'user{}@mail.com'", age), age.to_string()])
+                    .collect(),
+            )
+            .unwrap();
+
+        let count = |where_clauses: Vec<Condition>| {
+            engine
+                .select(context.table_name().to_string(), vec![], where_clauses)
+                .unwrap()
+                .records
+                .unwrap()
+                .len()
+        };
+
+        assert_eq!(
+            count(vec![Condition::new(
+                "age",
+                Predicate::GreaterThan(String::from("9"))
+            )]),
+            3
+        );
+        assert_eq!(
+            count(vec![Condition::new(
+                "age",
+                Predicate::Between(String::from("2"), String::from("10"))
+            )]),
+            9
+        );
+        assert_eq!(
+            count(vec![Condition::new(
+                "email",
+                Predicate::Like(String::from("'user1%'"))
+            )]),
+            4
+        );
+        assert_eq!(
+            count(vec![
+                Condition::new("email", Predicate::Like(String::from("'user1%'"))),
+                Condition::new("age", Predicate::LessThanOrEqual(String::from("10"))),
+            ]),
+            2
+        );
+    }
+
+    #[test]
+    fn test_can_select_using_a_composite_index() {
+        let context = FileTestContext::new();
+        let engine = BinaryEngine::new();
+
+        engine
+            .create_table(
+                context.table_name().to_string(),
+                vec![
+                    vec!["city".to_string(), "VARCHAR".to_string()],
+                    vec!["age".to_string(), "INTEGER".to_string()],
+                ],
+            )
+            .unwrap();
+
+        engine
+            .create_index(
+                context.table_name().to_string(),
+                Index::new(
+                    String::from("city_age_index"),
+                    vec![IndexColumn::new("city"), IndexColumn::new("age")],
+                ),
+            )
+            .unwrap();
+
+        engine
+            .insert(
+                context.table_name().to_string(),
+                vec!["city".to_string(), "age".to_string()],
+                (1..=12)
+                    .map(|age| {
+                        let city = if age % 2 == 0 { "'berlin'" } else { "'paris'" };
+                        vec![city.to_string(), age.to_string()]
+                    })
+                    .collect(),
+            )
+            .unwrap();
+
+        let count = |where_clauses: Vec<Condition>| {
+            engine
+                .select(context.table_name().to_string(), vec![], where_clauses)
+                .unwrap()
+                .records
+                .unwrap()
+                .len()
+        };
+
+        assert_eq!(
+            count(vec![Condition::new(
+                "city",
+                Predicate::Equal(String::from("'berlin'"))
+            )]),
+            6
+        );
+        assert_eq!(
+            count(vec![
+                Condition::new("city", Predicate::Equal(String::from("'berlin'"))),
+                Condition::new("age", Predicate::Equal(String::from("4"))),
+            ]),
+            1
+        );
+        assert_eq!(
+            count(vec![
+                Condition::new("age", Predicate::GreaterThan(String::from("6"))),
+                Condition::new("city", Predicate::Equal(String::from("'paris'"))),
+            ]),
+            3
+        );
+        assert_eq!(
+            count(vec![Condition::new(
+                "age",
+                Predicate::LessThan(String::from("5"))
+            )]),
+            4
+        );
+    }
+
+    #[test]
+    fn test_can_select_using_a_hash_index() {
+        let context = FileTestContext::new();
+        let engine = BinaryEngine::new();
+
+        engine
+            .create_table(
+                context.table_name().to_string(),
+                vec![vec!["email".to_string(), "VARCHAR".to_string()]],
+            )
+            .unwrap();
+
+        engine
+            .create_index(
+                context.table_name().to_string(),
+                Index {
+                    index_type: IndexType::Hash,
+                    ..Index::new(String::from("email_index"), vec![IndexColumn::new("email")])
+                },
+            )
+            .unwrap();
+
+        engine
+            .insert(
+                context.table_name().to_string(),
+                vec!["email".to_string()],
+                (1..=12)
+                    .map(|number| vec![format!("'user{}@mail.com'", number)])
+                    .collect(),
+            )
+            .unwrap();
+
+        let count = |where_clauses: Vec<Condition>| {
+            engine
+                .select(context.table_name().to_string(), vec![], where_clauses)
+                .unwrap()
+                .records
+                .unwrap()
+                .len()
+        };
+
+        assert_eq!(
+            count(vec![Condition::new(
+                "email",
+                Predicate::Equal(String::from("'user7@mail.com'"))
+            )]),
+            1
+        );
+        assert_eq!(
+            count(vec![Condition::new(
+                "email",
+                Predicate::Like(String::from("'user1%'"))
+            )]),
+            4
+        );
+    }
+
+    #[test]
+    fn test_can_select_using_partial_and_expression_indexes() {
+        let context = FileTestContext::new();
+        let engine = BinaryEngine::new();
+
+        engine
+            .create_table(
+                context.table_name().to_string(),
+                vec![
+                    vec!["email".to_string(), "VARCHAR".to_string()],
+                    vec!["status".to_string(), "VARCHAR".to_string()],
+                ],
+            )
+            .unwrap();
+
+        engine
+            .create_index(
+                context.table_name().to_string(),
+                Index {
+                    where_clauses: vec![Condition::new(
+                        "status",
+                        Predicate::Equal(String::from("'open'")),
+                    )],
+                    ..Index::new(String::from("open_index"), vec![IndexColumn::new("email")])
+                },
+            )
+            .unwrap();
+
+        engine
+            .create_index(
+                context.table_name().to_string(),
+                Index::new(
+                    String::from("lower_email_index"),
+                    vec![IndexColumn::with_function(Function::Lower, "email")],
+                ),
+            )
+            .unwrap();
+
+        engine
+            .insert(
+                context.table_name().to_string(),
+                vec!["email".to_string(), "status".to_string()],
+                vec![
+                    vec!["'John@Mail.com'".to_string(), "'open'".to_string()],
+                    vec!["'jane@mail.com'".to_string(), "'closed'".to_string()],
+                    vec!["'doe@mail.com'".to_string(), "'open'".to_string()],
+                ],
+            )
+            .unwrap();
+
+        let table = engine
+            .table_manager
+            .load_meta_data(context.table_name())
+            .unwrap();
+        let open_index = table
+            .indices
+            .iter()
+            .find(|index| index.name == "open_index")
+            .unwrap();
+        let indexed_rows = engine
+            .index_manager
+            .scan(
+                context.table_name(),
+                open_index,
+                &KeyRange {
+                    prefix: vec![],
+                    last: Some(ColumnRange::Range(Bound::Unbounded, Bound::Unbounded)),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(indexed_rows.len(), 2);
+
+        let emails = |where_clauses: Vec<Condition>| {
+            let mut emails: Vec<String> = engine
+                .select(context.table_name().to_string(), vec![], where_clauses)
+                .unwrap()
+                .records
+                .unwrap()
+                .iter()
+                .map(|record| record.fields.get("email").unwrap().to_string())
+                .collect();
+            emails.sort();
+            emails
+        };
+
+        assert_eq!(
+            emails(vec![
+                Condition::new("status", Predicate::Equal(String::from("'open'"))),
+                Condition::new("email", Predicate::GreaterThanOrEqual(String::from("'A'"))),
+            ]),
+            vec!["John@Mail.com", "doe@mail.com"]
+        );
+        assert_eq!(
+            emails(vec![Condition::new(
+                "email",
+                Predicate::Equal(String::from("'jane@mail.com'"))
+            )]),
+            vec!["jane@mail.com"]
+        );
+        assert_eq!(
+            emails(vec![Condition::with_function(
+                Function::Lower,
+                "email",
+                Predicate::Equal(String::from("'john@mail.com'"))
+            )]),
+            vec!["John@Mail.com"]
+        );
+    }
+
+    #[test]
+    fn test_answers_covered_queries_from_the_index_alone() {
+        let context = FileTestContext::new();
+        let engine = BinaryEngine::new();
+
+        engine
+            .create_table(
+                context.table_name().to_string(),
+                vec![
+                    vec!["email".to_string(), "VARCHAR".to_string()],
+                    vec!["name".to_string(), "VARCHAR".to_string()],
+                    vec!["age".to_string(), "INTEGER".to_string()],
+                ],
+            )
+            .unwrap();
+
+        engine
+            .create_index(
+                context.table_name().to_string(),
+                Index {
+                    included_columns: vec![String::from("name")],
+                    ..Index::new(String::from("email_index"), vec![IndexColumn::new("email")])
+                },
+            )
+            .unwrap();
+
+        let long_name = "n".repeat(1000);
+
+        engine
+            .insert(
+                context.table_name().to_string(),
+                vec!["email".to_string(), "name".to_string(), "age".to_string()],
+                vec![
+                    vec![
+                        "'john@mail.com'".to_string(),
+                        "'john'".to_string(),
+                        "30".to_string(),
+                    ],
+                    vec![
+                        "'long@mail.com'".to_string(),
+                        format!("'{}'", long_name),
+                        "40".to_string(),
+                    ],
+                ],
+            )
+            .unwrap();
+
+        let select = |column_names: Vec<&str>, email: &str| {
+            engine.select(
+                context.table_name().to_string(),
+                column_names.into_iter().map(String::from).collect(),
+                vec![Condition::new(
+                    "email",
+                    Predicate::Equal(format!("'{}'", email)),
+                )],
+            )
+        };
+
+        // too large to be included in the index, so it is read from the data page
+        let records = select(vec!["name"], "long@mail.com")
+            .unwrap()
+            .records
+            .unwrap();
+        assert!(records[0].fields.get("name").unwrap().fullfills(&long_name));
+
+        fs::write(
+            engine.file_paths.data_page(context.table_name(), 1),
+            "corrupted",
+        )
+        .unwrap();
+
+        let records = select(vec!["email", "name"], "john@mail.com")
+            .unwrap()
+            .records
+            .unwrap();
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].fields.len(), 2);
+        assert!(records[0].fields.get("name").unwrap().fullfills("john"));
+
+        assert!(select(vec!["age"], "john@mail.com").is_err());
+    }
+
+    #[test]
+    fn test_sorts_groups_and_limits_rows() {
+        let context = FileTestContext::new();
+        let table_name = context.table_name();
+        let engine = BinaryEngine::new();
+
+        for query in [
+            format!(
+                "CREATE TABLE {} (name VARCHAR, city VARCHAR, age INTEGER);",
+                table_name
+            ),
+            format!(
+                "INSERT INTO {} (name, city, age) VALUES ('ann', 'paris', 31), ('bob', 'berlin', 25), \
+                 ('cid', 'paris', 47), ('dan', 'rome', 19), ('eve', 'berlin', 38);",
+                table_name
+            ),
+        ] {
+            engine
+                .execute(SqlParser().parse_query(query).unwrap())
+                .unwrap();
+        }
+
+        assert_eq!(
+            rows(
+                &engine,
+                &format!(
+                    "SELECT name FROM {} ORDER BY age DESC LIMIT 2 OFFSET 1;",
+                    table_name
+                ),
+                &["name"]
+            ),
+            vec![vec!["eve"], vec!["ann"]]
+        );
+        assert_eq!(
+            rows(
+                &engine,
+                &format!(
+                    "SELECT city, COUNT(*), MAX(age) FROM {} WHERE age > 20 GROUP BY city ORDER BY COUNT(*) DESC, city;",
+                    table_name
+                ),
+                &["city", "COUNT(*)", "MAX(age)"]
+            ),
+            vec![vec!["berlin", "2", "38"], vec!["paris", "2", "47"]]
+        );
+        assert_eq!(
+            rows(
+                &engine,
+                &format!(
+                    "SELECT COUNT(*), SUM(age) FROM {} WHERE age > 90;",
+                    table_name
+                ),
+                &["COUNT(*)"]
+            ),
+            vec![vec!["0"]]
+        );
+
+        assert!(engine
+            .execute(
+                SqlParser()
+                    .parse_query(format!("SELECT name, COUNT(*) FROM {};", table_name))
+                    .unwrap()
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_fails_once_a_sum_overflows() {
+        let context = FileTestContext::new();
+        let table_name = context.table_name();
+        let engine = BinaryEngine::new();
+
+        for query in [
+            format!("CREATE TABLE {} (name VARCHAR, age INTEGER);", table_name),
+            format!(
+                "INSERT INTO {} (name, age) VALUES ('ann', {}), ('bob', 1);",
+                table_name,
+                i64::MAX
+            ),
+        ] {
+            engine
+                .execute(SqlParser().parse_query(query).unwrap())
+                .unwrap();
+        }
+
+        assert_eq!(
+            rows(
+                &engine,
+                &format!("SELECT SUM(age) FROM {} WHERE age > 1;", table_name),
+                &["SUM(age)"]
+            ),
+            vec![vec![i64::MAX.to_string()]]
+        );
+        assert_eq!(
+            engine
+                .execute(
+                    SqlParser()
+                        .parse_query(format!("SELECT SUM(age) FROM {};", table_name))
+                        .unwrap()
+                )
+                .unwrap_err(),
+            "Integer overflow in SUM."
+        );
+    }
+
+    #[test]
+    fn test_sorts_and_groups_rows_beyond_the_work_memory_in_temporary_files() {
+        let context = FileTestContext::new();
+        let table_name = context.table_name();
+        let engine = BinaryEngine::new();
+        let spilling_engine = BinaryEngine::open(&OpenOptions {
+            work_memory: 512,
+            ..OpenOptions::from_env()
+        })
+        .unwrap();
+
+        let values: Vec<String> = (0..200)
+            .map(|number| format!("('user{}', 'city{}', {})", number, number % 7, number % 50))
+            .collect();
+
+        for query in [
+            format!(
+                "CREATE TABLE {} (name VARCHAR, city VARCHAR, age INTEGER);",
+                table_name
+            ),
+            format!(
+                "INSERT INTO {} (name, city, age) VALUES {};",
+                table_name,
+                values.join(", ")
+            ),
+        ] {
+            engine
+                .execute(SqlParser().parse_query(query).unwrap())
+                .unwrap();
+        }
+
+        let sorted = format!(
+            "SELECT name, age FROM {} ORDER BY age DESC, name;",
+            table_name
+        );
+        let grouped = format!(
+            "SELECT city, COUNT(*), SUM(age), MIN(name), MAX(age) FROM {} GROUP BY city ORDER BY city;",
+            table_name
+        );
+        let grouped_columns = ["city", "COUNT(*)", "SUM(age)", "MIN(name)", "MAX(age)"];
+
+        assert_eq!(
+            rows(&spilling_engine, &sorted, &["name", "age"]),
+            rows(&engine, &sorted, &["name", "age"])
+        );
+        assert_eq!(
+            rows(&spilling_engine, &grouped, &grouped_columns),
+            rows(&engine, &grouped, &grouped_columns)
+        );
+        assert_eq!(rows(&engine, &grouped, &grouped_columns).len(), 7);
+
+        let temp_files = || {
+            fs::read_dir(engine.file_paths.temp_path())
+                .map(|entries| entries.count())
+                .unwrap_or(0)
+        };
+
+        let mut cursor = spilling_engine
+            .query(
+                SelectQuery::try_from(SqlParser().parse_query(sorted).unwrap().statement).unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            cursor.next().unwrap().unwrap().fields["age"].to_string(),
+            "49"
+        );
+        assert!(temp_files() > 0);
+
+        drop(cursor);
+        assert_eq!(temp_files(), 0);
+    }
+
+    #[test]
+    fn test_joins_tables_on_equal_columns() {
+        let users = FileTestContext::new();
+        let orders = FileTestContext::new();
+        let (users, orders) = (users.table_name(), orders.table_name());
+        let engine = BinaryEngine::new();
+
+        for query in [
+            format!("CREATE TABLE {} (name VARCHAR);", users),
+            format!("CREATE TABLE {} (user_id INTEGER, total INTEGER);", orders),
+            format!(
+                "INSERT INTO {} (name) VALUES ('ann'), ('bob'), ('cid');",
+                users
+            ),
+            format!(
+                "INSERT INTO {} (user_id, total) VALUES (1, 10), (3, 20), (1, 5), (4, 7);",
+                orders
+            ),
+        ] {
+            engine
+                .execute(SqlParser().parse_query(query).unwrap())
+                .unwrap();
+        }
+
+        let name = format!("{}.name", users);
+        let total = format!("{}.total", orders);
+
+        assert_eq!(
+            rows(
+                &engine,
+                &format!(
+                    "SELECT name, total FROM {users} JOIN {orders} ON {users}.id = user_id WHERE total > 5 ORDER BY total;"
+                ),
+                &[&name, &total]
+            ),
+            vec![vec!["ann", "10"], vec!["cid", "20"]]
+        );
+        assert_eq!(
+            rows(
+                &engine,
+                &format!(
+                    "SELECT name, SUM(total) FROM {users} JOIN {orders} ON user_id = {users}.id GROUP BY name ORDER BY name;"
+                ),
+                &[&name, &format!("SUM({})", total)]
+            ),
+            vec![vec!["ann", "15"], vec!["cid", "20"]]
+        );
+
+        assert!(engine
+            .execute(
+                SqlParser()
+                    .parse_query(format!(
+                        "SELECT id FROM {users} JOIN {orders} ON {users}.id = user_id;"
+                    ))
+                    .unwrap()
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_reads_rows_only_as_the_cursor_is_advanced() {
+        let context = FileTestContext::new();
+        let engine = BinaryEngine::new();
+
+        engine
+            .create_table(
+                context.table_name().to_string(),
+                vec![vec!["name".to_string(), "VARCHAR".to_string()]],
+            )
+            .unwrap();
+
+        engine
+            .insert(
+                context.table_name().to_string(),
+                vec!["name".to_string()],
+                vec![vec!["'john'".to_string()], vec!["'doe'".to_string()]],
+            )
+            .unwrap();
+
+        let mut cursor = engine
+            .query(SelectQuery::new(
+                context.table_name().to_string(),
+                vec![],
+                vec![],
+            ))
+            .unwrap();
+
+        fs::write(
+            engine.file_paths.data_page(context.table_name(), 1),
+            "corrupted",
+        )
+        .unwrap();
+
+        assert!(cursor.next().unwrap().is_err());
+        assert!(cursor.next().is_none());
+    }
+
+    #[test]
+    fn test_open_cursors_keep_their_rows_without_blocking_commits() {
+        let database = TestDatabase::new();
+        let reader = BinaryEngine::open(&database.options()).unwrap();
+        let writer = BinaryEngine::open(&database.options()).unwrap();
+
+        let execute = |query: &str| {
+            writer
+                .execute(SqlParser().parse_query(query.to_string()).unwrap())
+                .unwrap()
+        };
+        let query = || {
+            reader
+                .query(
+                    SelectQuery::try_from(
+                        SqlParser()
+                            .parse_query(String::from("SELECT * FROM users;"))
+                            .unwrap()
+                            .statement,
+                    )
+                    .unwrap(),
+                )
+                .unwrap()
+        };
+
+        execute("CREATE TABLE users (name VARCHAR);");
+        execute("INSERT INTO users (name) VALUES ('a'), ('b');");
+
+        // the writes commit while the cursor is open, but their garbage collection
+        // must not empty the slots of the versions the cursor still sees
+        let cursor = query();
+        execute("DELETE FROM users;");
+        execute("INSERT INTO users (name) VALUES ('c');");
+
+        let mut names: Vec<String> = cursor
+            .map(|record| record.unwrap().fields["name"].to_string())
+            .collect();
+        names.sort();
+
+        assert_eq!(names, vec!["a", "b"]);
+
+        // moving the rows to other record ids has to wait for the cursor
+        let cursor = query();
+
+        std::thread::scope(|scope| {
+            let vacuum = scope.spawn(|| execute("VACUUM users;"));
+
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            assert!(!vacuum.is_finished());
+
+            drop(cursor);
+            vacuum.join().unwrap();
+        });
+
+        assert_eq!(
+            rows(&writer, "SELECT * FROM users;", &["name"]),
+            vec![vec!["c"]]
+        );
+    }
 }
@@ -1,36 +1,55 @@
 use std::fs;
-use std::io::{self, Read, Write};
-use std::rc::Rc;
+use std::io;
+use std::sync::Arc;
 
+use super::pager::Pager;
 use crate::{engine::file_paths::FilePaths, metadata};
 
 pub struct TableManager {
-    file_paths: Rc<FilePaths>,
+    file_paths: Arc<FilePaths>,
+    pager: Arc<Pager>,
 }
 
 impl TableManager {
-    pub fn new(file_paths: Rc<FilePaths>) -> Self {
-        TableManager { file_paths }
+    pub fn new(file_paths: Arc<FilePaths>, pager: Arc<Pager>) -> Self {
+        TableManager { file_paths, pager }
     }
 
     pub fn store_meta_data(&self, table: &metadata::Table) -> io::Result<()> {
-        let serialized_table = &bincode::serialize(table).unwrap();
+        self.pager.atomically(|| {
+            self.pager.write(
+                &self.file_paths.meta_data_path(&table.name),
+                bincode::serialize(table).unwrap(),
+            );
 
-        let mut file = fs::File::create(self.file_paths.meta_data_path(&table.name))?;
-        file.write_all(serialized_table)?;
+            Ok(())
+        })
+    }
+
+    /// The names of all tables, i.e. of the directories holding table metadata.
+    pub fn table_names(&self) -> io::Result<Vec<String>> {
+        let mut table_names = vec![];
+
+        for entry in fs::read_dir(self.file_paths.base_path())? {
+            let table_name = entry?.file_name().to_string_lossy().to_string();
 
-        for index in &table.indices {
-            fs::File::create(self.file_paths.index_path(&table.name, &index.name))?;
+            if self
+                .pager
+                .exists(&self.file_paths.meta_data_path(&table_name))
+            {
+                table_names.push(table_name);
+            }
         }
 
-        Ok(())
+        table_names.sort();
+
+        Ok(table_names)
     }
 
     pub fn load_meta_data(&self, table_name: &str) -> io::Result<metadata::Table> {
-        let mut file = fs::File::open(self.file_paths.meta_data_path(table_name))?;
-
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)?;
+        let buffer = self
+            .pager
+            .read(&self.file_paths.meta_data_path(table_name))?;
 
         let result: bincode::Result<metadata::Table> = bincode::deserialize(&buffer[..]);
 
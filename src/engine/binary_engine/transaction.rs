@@ -0,0 +1,536 @@
+use super::{EngineResponse, EngineResult};
+use crate::engine::transaction::Transaction;
+use crate::sql_parser::query::IsolationLevel;
+
+impl Transaction for super::BinaryEngine {
+    fn begin(&self) -> EngineResult {
+        if self.pager.in_transaction() {
+            return Err(String::from("A transaction is already in progress."));
+        }
+
+        self.pager
+            .begin()
+            .map_err(|error| format!("Failed to begin the transaction: {}", error))?;
+
+        Ok(EngineResponse {
+            table: None,
+            records: None,
+            message: None,
+        })
+    }
+
+    fn commit(&self) -> EngineResult {
+        if !self.pager.in_transaction() {
+            return Err(String::from("There is no transaction in progress."));
+        }
+
+        self.pager
+            .commit()
+            .map_err(|error| format!("Failed to commit the transaction: {}", error))?;
+
+        Ok(EngineResponse {
+            table: None,
+            records: None,
+            message: None,
+        })
+    }
+
+    fn rollback(&self) -> EngineResult {
+        if !self.pager.in_transaction() {
+            return Err(String::from("There is no transaction in progress."));
+        }
+
+        self.pager.rollback();
+
+        Ok(EngineResponse {
+            table: None,
+            records: None,
+            message: None,
+        })
+    }
+
+    fn savepoint(&self, name: String) -> EngineResult {
+        if !self.pager.in_transaction() {
+            return Err(String::from(
+                "Savepoints can only be used in a transaction.",
+            ));
+        }
+
+        self.pager.savepoint(Some(&name));
+
+        Ok(EngineResponse {
+            table: None,
+            records: None,
+            message: None,
+        })
+    }
+
+    fn rollback_to_savepoint(&self, name: String) -> EngineResult {
+        self.pager
+            .rollback_to_savepoint(Some(&name))
+            .map_err(|error| error.to_string())?;
+
+        Ok(EngineResponse {
+            table: None,
+            records: None,
+            message: None,
+        })
+    }
+
+    fn release_savepoint(&self, name: String) -> EngineResult {
+        self.pager
+            .release_savepoint(Some(&name))
+            .map_err(|error| error.to_string())?;
+
+        Ok(EngineResponse {
+            table: None,
+            records: None,
+            message: None,
+        })
+    }
+
+    fn set_isolation_level(&self, level: IsolationLevel) -> EngineResult {
+        if !self.pager.in_transaction() {
+            return Err(String::from(
+                "The isolation level can only be set in a transaction.",
+            ));
+        }
+
+        self.pager.set_isolation_level(level)?;
+
+        Ok(EngineResponse {
+            table: None,
+            records: None,
+            message: None,
+        })
+    }
+
+    fn in_transaction(&self, statement: Box<dyn FnOnce() -> EngineResult + '_>) -> EngineResult {
+        let explicit = self.pager.in_transaction();
+
+        if explicit {
+            self.pager.savepoint(None);
+        } else {
+            self.pager
+                .begin()
+                .map_err(|error| format!("Failed to begin the statement: {}", error))?;
+        }
+
+        self.pager.start_statement();
+
+        match statement() {
+            Ok(response) if explicit => {
+                self.pager
+                    .release_savepoint(None)
+                    .map_err(|error| error.to_string())?;
+
+                Ok(response)
+            }
+            Ok(response) => {
+                self.pager
+                    .commit()
+                    .map_err(|error| format!("Failed to commit the statement: {}", error))?;
+
+                Ok(response)
+            }
+            // the transaction goes on without the writes of the failed statement,
+            // unless it was already rolled back to resolve a deadlock
+            Err(message) if explicit && self.pager.in_transaction() => {
+                self.pager
+                    .rollback_to_savepoint(None)
+                    .and_then(|_| self.pager.release_savepoint(None))
+                    .map_err(|error| error.to_string())?;
+
+                Err(message)
+            }
+            Err(message) => {
+                self.pager.rollback();
+
+                Err(message)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use crate::engine::{binary_engine::BinaryEngine, Engine, EngineResult};
+    use crate::io_test_context::{FileTestContext, TestDatabase};
+    use crate::sql_parser::SqlParser;
+
+    fn execute(engine: &BinaryEngine, query: &str) -> EngineResult {
+        engine.execute(SqlParser().parse_query(query.to_string()).unwrap())
+    }
+
+    fn count(engine: &BinaryEngine, table_name: &str) -> usize {
+        execute(engine, &format!("SELECT * FROM {};", table_name))
+            .unwrap()
+            .records
+            .unwrap()
+            .len()
+    }
+
+    #[test]
+    fn test_writes_of_a_transaction_become_visible_on_commit() {
+        let context = FileTestContext::new();
+        let table_name = context.table_name();
+        let engine = BinaryEngine::new();
+
+        execute(
+            &engine,
+            &format!("CREATE TABLE {} (name VARCHAR);", table_name),
+        )
+        .unwrap();
+        execute(&engine, "BEGIN;").unwrap();
+        execute(
+            &engine,
+            &format!("INSERT INTO {} (name) VALUES ('a'), ('b');", table_name),
+        )
+        .unwrap();
+
+        assert_eq!(count(&engine, table_name), 2);
+        assert_eq!(count(&BinaryEngine::new(), table_name), 0);
+
+        execute(&engine, "COMMIT;").unwrap();
+
+        assert_eq!(count(&BinaryEngine::new(), table_name), 2);
+    }
+
+    #[test]
+    fn test_rollback_discards_all_writes_of_the_transaction() {
+        let context = FileTestContext::new();
+        let table_name = context.table_name();
+        let engine = BinaryEngine::new();
+
+        execute(
+            &engine,
+            &format!("CREATE TABLE {} (name VARCHAR);", table_name),
+        )
+        .unwrap();
+        execute(&engine, "BEGIN;").unwrap();
+        execute(
+            &engine,
+            &format!("INSERT INTO {} (name) VALUES ('a');", table_name),
+        )
+        .unwrap();
+        execute(
+            &engine,
+            &format!("CREATE INDEX name_index ON {} (name);", table_name),
+        )
+        .unwrap();
+        execute(&engine, "ROLLBACK;").unwrap();
+
+        assert_eq!(count(&engine, table_name), 0);
+        assert!(engine
+            .table_manager
+            .load_meta_data(table_name)
+            .unwrap()
+            .indices
+            .iter()
+            .all(|index| index.name != "name_index"));
+    }
+
+    #[test]
+    fn test_a_failing_statement_only_rolls_back_its_own_writes() {
+        let context = FileTestContext::new();
+        let table_name = context.table_name();
+        let engine = BinaryEngine::new();
+
+        execute(
+            &engine,
+            &format!("CREATE TABLE {} (number INTEGER);", table_name),
+        )
+        .unwrap();
+        execute(&engine, "BEGIN;").unwrap();
+        execute(
+            &engine,
+            &format!("INSERT INTO {} (number) VALUES (1);", table_name),
+        )
+        .unwrap();
+
+        assert!(execute(
+            &engine,
+            &format!("INSERT INTO {} (number) VALUES (2), ('two');", table_name),
+        )
+        .is_err());
+
+        execute(&engine, "COMMIT;").unwrap();
+
+        assert_eq!(count(&BinaryEngine::new(), table_name), 1);
+    }
+
+    #[test]
+    fn test_rolling_back_to_a_savepoint_keeps_earlier_writes() {
+        let context = FileTestContext::new();
+        let table_name = context.table_name();
+        let engine = BinaryEngine::new();
+
+        for query in [
+            format!("CREATE TABLE {} (name VARCHAR);", table_name),
+            String::from("BEGIN;"),
+            format!("INSERT INTO {} (name) VALUES ('a');", table_name),
+            String::from("SAVEPOINT batch;"),
+            format!("INSERT INTO {} (name) VALUES ('b'), ('c');", table_name),
+            format!("CREATE INDEX name_index ON {} (name);", table_name),
+            String::from("ROLLBACK TO SAVEPOINT batch;"),
+            format!("INSERT INTO {} (name) VALUES ('d');", table_name),
+            String::from("RELEASE SAVEPOINT batch;"),
+            String::from("COMMIT;"),
+        ] {
+            execute(&engine, &query).unwrap();
+        }
+
+        let engine = BinaryEngine::new();
+        let names: Vec<String> = execute(&engine, &format!("SELECT * FROM {};", table_name))
+            .unwrap()
+            .records
+            .unwrap()
+            .iter()
+            .map(|record| record.fields.get("name").unwrap().to_string())
+            .collect();
+
+        assert_eq!(names, vec!["a", "d"]);
+        assert_eq!(
+            engine
+                .table_manager
+                .load_meta_data(table_name)
+                .unwrap()
+                .indices
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_savepoints_need_a_transaction_and_a_known_name() {
+        let engine = BinaryEngine::new();
+
+        assert!(execute(&engine, "SAVEPOINT batch;").is_err());
+
+        execute(&engine, "BEGIN;").unwrap();
+
+        assert_eq!(
+            execute(&engine, "RELEASE SAVEPOINT batch;").unwrap_err(),
+            "Savepoint 'batch' does not exist."
+        );
+
+        execute(&engine, "ROLLBACK;").unwrap();
+    }
+
+    #[test]
+    fn test_transactions_cannot_be_nested() {
+        let engine = BinaryEngine::new();
+
+        execute(&engine, "BEGIN;").unwrap();
+
+        assert_eq!(
+            execute(&engine, "BEGIN;").unwrap_err(),
+            "A transaction is already in progress."
+        );
+
+        execute(&engine, "ROLLBACK;").unwrap();
+    }
+
+    #[test]
+    fn test_sessions_of_one_engine_run_concurrently() {
+        // a database of its own, so that the sessions only wait for each other
+        let database = TestDatabase::new();
+        let table_name = String::from("concurrent");
+        let engine = Arc::new(BinaryEngine::open(&database.options()).unwrap());
+
+        execute(
+            &engine,
+            &format!("CREATE TABLE {} (name VARCHAR);", table_name),
+        )
+        .unwrap();
+
+        let writers: Vec<_> = (0..4)
+            .map(|_| {
+                let engine = Arc::clone(&engine);
+                let table_name = table_name.clone();
+
+                thread::spawn(move || {
+                    for _ in 0..5 {
+                        execute(&engine, "BEGIN;").unwrap();
+                        execute(
+                            &engine,
+                            &format!("INSERT INTO {} (name) VALUES ('a'), ('b');", table_name),
+                        )
+                        .unwrap();
+                        execute(&engine, "COMMIT;").unwrap();
+                    }
+                })
+            })
+            .collect();
+
+        let readers: Vec<_> = (0..2)
+            .map(|_| {
+                let engine = Arc::clone(&engine);
+                let table_name = table_name.clone();
+
+                thread::spawn(move || {
+                    for _ in 0..10 {
+                        // every transaction inserts two records, which are seen together or not at all
+                        assert_eq!(count(&engine, &table_name) % 2, 0);
+                    }
+                })
+            })
+            .collect();
+
+        for handle in writers.into_iter().chain(readers) {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(count(&engine, &table_name), 40);
+    }
+
+    #[test]
+    fn test_engines_on_the_same_database_share_their_locks() {
+        let database = TestDatabase::new();
+        let table_name = String::from("shared");
+
+        execute(
+            &BinaryEngine::open(&database.options()).unwrap(),
+            &format!("CREATE TABLE {} (name VARCHAR, age INTEGER);", table_name),
+        )
+        .unwrap();
+
+        // every row lands on the same data page, which neither engine may overwrite with a stale copy
+        let writers: Vec<_> = ["a", "b"]
+            .into_iter()
+            .map(|name| {
+                let table_name = table_name.clone();
+                let options = database.options();
+
+                thread::spawn(move || {
+                    let engine = BinaryEngine::open(&options).unwrap();
+
+                    for age in 0..10 {
+                        execute(
+                            &engine,
+                            &format!(
+                                "INSERT INTO {} (name, age) VALUES ('{}', {});",
+                                table_name, name, age
+                            ),
+                        )
+                        .unwrap();
+                        execute(
+                            &engine,
+                            &format!(
+                                "UPDATE {} SET age = 100 WHERE name = '{}';",
+                                table_name, name
+                            ),
+                        )
+                        .unwrap();
+                    }
+                })
+            })
+            .collect();
+
+        for handle in writers {
+            handle.join().unwrap();
+        }
+
+        let engine = BinaryEngine::open(&database.options()).unwrap();
+
+        assert_eq!(count(&engine, &table_name), 20);
+        assert_eq!(
+            execute(
+                &engine,
+                &format!("SELECT * FROM {} WHERE age = 100;", table_name)
+            )
+            .unwrap()
+            .records
+            .unwrap()
+            .len(),
+            20
+        );
+    }
+
+    #[test]
+    fn test_sql_engine_can_be_shared_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<crate::sql_engine::SQLEngine>();
+    }
+
+    #[test]
+    fn test_isolation_levels_decide_which_commits_a_transaction_sees() {
+        let context = FileTestContext::new();
+        let table_name = context.table_name();
+        let (reader, writer) = (BinaryEngine::new(), BinaryEngine::new());
+        let insert = format!("INSERT INTO {} (name) VALUES ('a');", table_name);
+
+        execute(
+            &writer,
+            &format!("CREATE TABLE {} (name VARCHAR);", table_name),
+        )
+        .unwrap();
+        execute(&writer, &insert).unwrap();
+
+        execute(&reader, "BEGIN;").unwrap();
+        assert_eq!(count(&reader, table_name), 1);
+        execute(&writer, &insert).unwrap();
+        assert_eq!(count(&reader, table_name), 2);
+        execute(&reader, "COMMIT;").unwrap();
+
+        execute(&reader, "BEGIN;").unwrap();
+        execute(&reader, "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ;").unwrap();
+        assert_eq!(count(&reader, table_name), 2);
+        execute(&writer, &insert).unwrap();
+        assert_eq!(count(&reader, table_name), 2);
+
+        assert!(execute(&reader, "SET TRANSACTION ISOLATION LEVEL READ COMMITTED;").is_err());
+
+        execute(&reader, "COMMIT;").unwrap();
+        assert_eq!(count(&reader, table_name), 3);
+    }
+
+    #[test]
+    fn test_deleting_a_row_deleted_after_the_snapshot_is_a_conflict() {
+        let context = FileTestContext::new();
+        let table_name = context.table_name();
+        let (first, second) = (BinaryEngine::new(), BinaryEngine::new());
+
+        execute(
+            &first,
+            &format!("CREATE TABLE {} (name VARCHAR);", table_name),
+        )
+        .unwrap();
+        execute(
+            &first,
+            &format!("INSERT INTO {} (name) VALUES ('a'), ('b');", table_name),
+        )
+        .unwrap();
+
+        execute(&first, "BEGIN;").unwrap();
+        execute(&first, "SET TRANSACTION ISOLATION LEVEL SNAPSHOT;").unwrap();
+        assert_eq!(count(&first, table_name), 2);
+
+        execute(
+            &second,
+            &format!("DELETE FROM {} WHERE name = 'a';", table_name),
+        )
+        .unwrap();
+
+        assert_eq!(
+            execute(&first, &format!("DELETE FROM {};", table_name)).unwrap_err(),
+            format!(
+                "Could not serialize access to '{}' because of a concurrent delete.",
+                table_name
+            )
+        );
+
+        execute(
+            &first,
+            &format!("DELETE FROM {} WHERE name = 'b';", table_name),
+        )
+        .unwrap();
+        execute(&first, "COMMIT;").unwrap();
+
+        assert_eq!(count(&second, table_name), 0);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::engine::update::Update;
+use crate::sql_parser::query::Condition;
+use crate::utils::unquote;
+
+impl Update for super::BinaryEngine {
+    /// Replaces every matching row by a new version with the assigned values, deleting the old one.
+    fn update(
+        &self,
+        table_name: String,
+        assignments: Vec<(String, String)>,
+        where_clauses: Vec<Condition>,
+    ) -> super::EngineResult {
+        let _guard = self.lock_for_write(&table_name)?;
+
+        let Ok(mut table) = self.table_manager.load_meta_data(&table_name) else {
+            return Err(format!("Table '{}' does not exist.", table_name));
+        };
+
+        let values = assignments
+            .iter()
+            .map(|(column_name, value)| {
+                let Some(column) = table
+                    .columns
+                    .iter()
+                    .find(|column| column.name == *column_name)
+                else {
+                    return Err(format!("Column '{}' does not exist.", column_name));
+                };
+
+                match column.data_type.to_value(unquote(value)) {
+                    Some(value) => Ok((column_name.clone(), value)),
+                    None => Err(format!("Type does not allow {} value", value)),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.collect_garbage(&mut table)
+            .map_err(|error| error.to_string())?;
+
+        let records: Vec<_> = self
+            .delete_matching(&mut table, &where_clauses)?
+            .into_iter()
+            .map(|(_, mut record)| {
+                record.fields.extend(values.iter().cloned());
+                record
+            })
+            .collect();
+
+        self.check_unique_indices(&table_name, &table, &records)?;
+
+        let updated = records.len();
+
+        if updated > 0 {
+            self.store_records(&table, records)?;
+
+            table.last_writer = self
+                .pager
+                .transaction_id()
+                .map_err(|error| error.to_string())?;
+
+            self.table_manager
+                .store_meta_data(&table)
+                .map_err(|error| error.to_string())?;
+        }
+
+        Ok(super::EngineResponse {
+            table: None,
+            records: None,
+            message: Some(format!("Updated {} records.", updated)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{binary_engine::BinaryEngine, Engine, EngineResult};
+    use crate::io_test_context::FileTestContext;
+    use crate::sql_parser::SqlParser;
+
+    fn execute(engine: &BinaryEngine, query: &str) -> EngineResult {
+        engine.execute(SqlParser().parse_query(query.to_string()).unwrap())
+    }
+
+    fn rows(engine: &BinaryEngine, query: &str) -> Vec<String> {
+        let mut rows: Vec<String> = execute(engine, query)
+            .unwrap()
+            .records
+            .unwrap()
+            .iter()
+            .map(|record| format!("{} {}", record.fields["name"], record.fields["age"]))
+            .collect();
+
+        rows.sort();
+        rows
+    }
+
+    #[test]
+    fn test_updates_the_rows_matching_the_where_clauses() {
+        let context = FileTestContext::new();
+        let table_name = context.table_name();
+        let engine = BinaryEngine::new();
+
+        for query in [
+            format!("CREATE TABLE {} (name VARCHAR, age INTEGER);", table_name),
+            format!("CREATE INDEX age_index ON {} (age);", table_name),
+            format!(
+                "INSERT INTO {} (name, age) VALUES ('a', 20), ('b', 30), ('c', 40);",
+                table_name
+            ),
+            format!(
+                "UPDATE {} SET age = 35, name = 'd' WHERE age >= 30 AND name = 'b';",
+                table_name
+            ),
+        ] {
+            execute(&engine, &query).unwrap();
+        }
+
+        assert_eq!(
+            rows(&engine, &format!("SELECT * FROM {};", table_name)),
+            vec!["a 20", "c 40", "d 35"]
+        );
+        assert_eq!(
+            rows(
+                &engine,
+                &format!("SELECT * FROM {} WHERE age = 35;", table_name)
+            ),
+            vec!["d 35"]
+        );
+        assert!(rows(
+            &engine,
+            &format!("SELECT * FROM {} WHERE age = 30;", table_name)
+        )
+        .is_empty());
+
+        assert_eq!(
+            execute(&engine, &format!("UPDATE {} SET city = 'x';", table_name)).unwrap_err(),
+            "Column 'city' does not exist."
+        );
+        assert!(execute(&engine, &format!("UPDATE {} SET age = 'x';", table_name)).is_err());
+
+        assert_eq!(
+            execute(
+                &engine,
+                &format!("UPDATE {} SET age = 41 WHERE age >= 35;", table_name)
+            )
+            .unwrap()
+            .message,
+            Some(String::from("Updated 2 records."))
+        );
+    }
+
+    #[test]
+    fn test_cannot_update_a_row_to_a_duplicate_key_of_a_unique_index() {
+        let context = FileTestContext::new();
+        let table_name = context.table_name();
+        let engine = BinaryEngine::new();
+
+        for query in [
+            format!("CREATE TABLE {} (name VARCHAR, age INTEGER);", table_name),
+            format!("CREATE UNIQUE INDEX name_index ON {} (name);", table_name),
+            format!(
+                "INSERT INTO {} (name, age) VALUES ('a', 20), ('b', 30);",
+                table_name
+            ),
+        ] {
+            execute(&engine, &query).unwrap();
+        }
+
+        assert_eq!(
+            execute(
+                &engine,
+                &format!("UPDATE {} SET name = 'b' WHERE name = 'a';", table_name)
+            )
+            .unwrap_err(),
+            "Duplicate key ('b') violates unique index 'name_index'."
+        );
+        assert!(execute(&engine, &format!("UPDATE {} SET name = 'c';", table_name)).is_err());
+        assert_eq!(
+            rows(&engine, &format!("SELECT * FROM {};", table_name)),
+            vec!["a 20", "b 30"]
+        );
+
+        // the old version of the row itself does not count as a duplicate
+        execute(
+            &engine,
+            &format!(
+                "UPDATE {} SET name = 'a', age = 21 WHERE name = 'a';",
+                table_name
+            ),
+        )
+        .unwrap();
+        execute(
+            &engine,
+            &format!("UPDATE {} SET name = 'c' WHERE name = 'b';", table_name),
+        )
+        .unwrap();
+
+        assert_eq!(
+            rows(&engine, &format!("SELECT * FROM {};", table_name)),
+            vec!["a 21", "c 30"]
+        );
+        assert_eq!(
+            rows(
+                &engine,
+                &format!("SELECT * FROM {} WHERE name = 'b';", table_name)
+            ),
+            Vec::<String>::new()
+        );
+        execute(
+            &engine,
+            &format!("INSERT INTO {} (name, age) VALUES ('b', 50);", table_name),
+        )
+        .unwrap();
+    }
+}
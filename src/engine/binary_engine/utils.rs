@@ -1,3 +1,3 @@
-pub fn selected_all_columns(column_names: &Vec<String>) -> bool {
+pub fn selected_all_columns(column_names: &[String]) -> bool {
     column_names.len() == 1 && column_names.first().unwrap() == "*"
 }
@@ -0,0 +1,207 @@
+use crate::engine::vacuum::Vacuum;
+
+impl Vacuum for super::BinaryEngine {
+    fn vacuum(&self, table_name: Option<String>) -> super::EngineResult {
+        let table_names = match table_name {
+            Some(table_name) => vec![table_name],
+            None => self
+                .table_manager
+                .table_names()
+                .map_err(|error| format!("Failed to list the tables: {}", error))?,
+        };
+
+        let messages = table_names
+            .iter()
+            .map(|table_name| self.vacuum_table(table_name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(super::EngineResponse {
+            table: None,
+            records: None,
+            message: Some(messages.join("\n")),
+        })
+    }
+}
+
+impl super::BinaryEngine {
+    /// Rewrites the data pages of the table densely and rebuilds its indices from them. All of it
+    /// is committed at once, so a crash either leaves the table as it was or fully compacted.
+    /// As the rows move to other record ids, it waits for the queries reading the table to end.
+    fn vacuum_table(&self, table_name: &str) -> Result<String, String> {
+        let _guard = self.lock_exclusively(table_name)?;
+
+        let Ok(mut table) = self.table_manager.load_meta_data(table_name) else {
+            return Err(format!("Table '{}' does not exist.", table_name));
+        };
+
+        let data_page_count = self.data_manager.data_page_count(table_name);
+
+        let versions = self
+            .data_manager
+            .compact(table_name, self.pager.horizon())
+            .map_err(|error| format!("Failed to compact '{}': {}", table_name, error))?;
+
+        for index in &table.indices {
+            self.index_manager
+                .delete_index_file(table_name, index)
+                .and_then(|_| self.index_manager.create_index_file(table_name, index))
+                .map_err(|error| format!("Failed to rebuild index '{}': {}", index.name, error))?;
+
+            for (record_id, version) in &versions {
+                self.index_manager
+                    .insert(table_name, index, &version.record, *record_id)
+                    .map_err(|error| {
+                        format!("Failed to rebuild index '{}': {}", index.name, error)
+                    })?;
+            }
+        }
+
+        table.dead_versions = versions
+            .iter()
+            .filter(|(_, version)| version.xmax.is_some())
+            .count();
+
+        self.table_manager
+            .store_meta_data(&table)
+            .map_err(|error| error.to_string())?;
+
+        Ok(format!(
+            "Vacuumed '{}' from {} to {} data pages.",
+            table_name,
+            data_page_count,
+            self.data_manager.data_page_count(table_name)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::binary_engine::{data_manager::DATA_PAGE_SIZE, BinaryEngine};
+    use crate::engine::{Engine, EngineResult, IntegrityCheck};
+    use crate::io_test_context::TestDatabase;
+    use crate::sql_parser::SqlParser;
+
+    fn execute(engine: &BinaryEngine, query: &str) -> EngineResult {
+        engine.execute(SqlParser().parse_query(query.to_string()).unwrap())
+    }
+
+    fn names(engine: &BinaryEngine, query: &str) -> Vec<String> {
+        let mut names: Vec<String> = execute(engine, query)
+            .unwrap()
+            .records
+            .unwrap()
+            .iter()
+            .map(|record| record.fields.get("name").unwrap().to_string())
+            .collect();
+
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_compacts_data_pages_and_rebuilds_indices() {
+        // a database of its own, so that no transaction of a concurrent test holds the horizon back
+        let database = TestDatabase::new();
+        let table_name = "vacuumed";
+        let engine = BinaryEngine::open(&database.options()).unwrap();
+        let reader = BinaryEngine::open(&database.options()).unwrap();
+
+        let payload = "p".repeat(DATA_PAGE_SIZE / 5);
+        let large_payload = "l".repeat(DATA_PAGE_SIZE * 2);
+
+        execute(
+            &engine,
+            &format!(
+                "CREATE TABLE {} (name VARCHAR, payload VARCHAR);",
+                table_name
+            ),
+        )
+        .unwrap();
+        execute(
+            &engine,
+            &format!(
+                "CREATE INDEX name_index ON {} (name) USING HASH;",
+                table_name
+            ),
+        )
+        .unwrap();
+
+        for name in 0..12 {
+            execute(
+                &engine,
+                &format!(
+                    "INSERT INTO {} (name, payload) VALUES ('{}', '{}');",
+                    table_name, name, payload
+                ),
+            )
+            .unwrap();
+        }
+
+        execute(
+            &engine,
+            &format!(
+                "INSERT INTO {} (name, payload) VALUES ('large', '{}');",
+                table_name, large_payload
+            ),
+        )
+        .unwrap();
+        execute(&reader, "BEGIN;").unwrap();
+        execute(&reader, "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ;").unwrap();
+        execute(&reader, &format!("SELECT name FROM {};", table_name)).unwrap();
+
+        execute(
+            &engine,
+            &format!(
+                "DELETE FROM {} WHERE name >= '2' AND name <= '9';",
+                table_name
+            ),
+        )
+        .unwrap();
+
+        let data_page_count = engine.data_manager.data_page_count(table_name);
+        assert!(data_page_count > 1);
+
+        // the snapshot of the reader still sees the deleted rows
+        execute(&engine, &format!("VACUUM {};", table_name)).unwrap();
+        assert_eq!(
+            engine.data_manager.data_page_count(table_name),
+            data_page_count
+        );
+        assert_eq!(
+            names(&reader, &format!("SELECT name FROM {};", table_name)).len(),
+            13
+        );
+
+        execute(&reader, "COMMIT;").unwrap();
+        execute(&engine, &format!("VACUUM {};", table_name)).unwrap();
+
+        assert_eq!(engine.data_manager.data_page_count(table_name), 1);
+        assert_eq!(
+            names(&engine, &format!("SELECT name FROM {};", table_name)),
+            vec!["0", "1", "10", "11", "large"]
+        );
+        assert_eq!(
+            names(
+                &engine,
+                &format!("SELECT name FROM {} WHERE name = '10';", table_name)
+            ),
+            vec!["10"]
+        );
+
+        let large = execute(
+            &engine,
+            &format!("SELECT * FROM {} WHERE name = 'large';", table_name),
+        )
+        .unwrap()
+        .records
+        .unwrap();
+        assert!(large[0].fields["payload"].fullfills(&large_payload));
+
+        assert!(engine
+            .integrity_check(Some(table_name.to_string()))
+            .unwrap()
+            .records
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,3 @@
+pub trait Checkpoint {
+    fn checkpoint(&self) -> super::EngineResult;
+}
@@ -1,8 +1,5 @@
+use crate::metadata::Index;
+
 pub trait CreateIndex {
-    fn create_index(
-        &self,
-        table_name: String,
-        column_name: String,
-        index_name: String,
-    ) -> super::EngineResult;
+    fn create_index(&self, table_name: String, index: Index) -> super::EngineResult;
 }
@@ -0,0 +1,23 @@
+use crate::dynamic_record::DynamicRecord;
+
+/// Streams the rows of a query. Every row is only computed once it is read, and the tables
+/// the query reads stay locked against commits of other sessions until the cursor is dropped.
+pub struct RowCursor<'a> {
+    rows: Box<dyn Iterator<Item = Result<DynamicRecord, String>> + 'a>,
+}
+
+impl<'a> RowCursor<'a> {
+    pub fn new(rows: impl Iterator<Item = Result<DynamicRecord, String>> + 'a) -> Self {
+        RowCursor {
+            rows: Box::new(rows),
+        }
+    }
+}
+
+impl Iterator for RowCursor<'_> {
+    type Item = Result<DynamicRecord, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rows.next()
+    }
+}
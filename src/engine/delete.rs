@@ -0,0 +1,5 @@
+use crate::sql_parser::query::Condition;
+
+pub trait Delete {
+    fn delete(&self, table_name: String, where_clauses: Vec<Condition>) -> super::EngineResult;
+}
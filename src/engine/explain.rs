@@ -0,0 +1,7 @@
+use super::SelectQuery;
+
+pub trait Explain {
+    /// Plans the query, returning a row for every line of the plan. If it is analyzed, the query
+    /// is run and every line also shows what its operator actually did.
+    fn explain(&self, query: SelectQuery, analyze: bool) -> super::EngineResult;
+}
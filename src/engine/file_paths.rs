@@ -13,6 +13,37 @@ impl FilePaths {
         FilePaths { base_path }
     }
 
+    pub fn at(base_path: &str) -> Self {
+        FilePaths {
+            base_path: base_path.to_string(),
+        }
+    }
+
+    pub fn base_path(&self) -> &str {
+        &self.base_path
+    }
+
+    pub fn lock_path(&self) -> String {
+        format!("{}/database.lock", self.base_path)
+    }
+
+    pub fn format_version_path(&self) -> String {
+        format!("{}/format_version.bin", self.base_path)
+    }
+
+    pub fn wal_path(&self) -> String {
+        format!("{}/wal.log", self.base_path)
+    }
+
+    pub fn transaction_ids_path(&self) -> String {
+        format!("{}/transaction_ids.bin", self.base_path)
+    }
+
+    /// Where operators keep the rows that do not fit into memory while a query runs.
+    pub fn temp_path(&self) -> String {
+        format!("{}/temp", self.base_path)
+    }
+
     pub fn table_path(&self, table_name: &str) -> String {
         String::from(&self.base_path) + "/" + table_name
     }
@@ -31,4 +62,11 @@ impl FilePaths {
             self.base_path, table_name, data_page_index
         )
     }
+
+    pub fn overflow_page(&self, table_name: &str, overflow_page_index: usize) -> String {
+        format!(
+            "{}/{}/overflow_page_{}.bin",
+            self.base_path, table_name, overflow_page_index
+        )
+    }
 }
@@ -0,0 +1,4 @@
+pub trait IntegrityCheck {
+    /// Verifies the table, or all tables if there is none, returning one row per problem found.
+    fn integrity_check(&self, table_name: Option<String>) -> super::EngineResult;
+}
@@ -1,49 +1,123 @@
+mod analyze;
 mod binary_engine;
+mod checkpoint;
 mod create_index;
 mod create_table;
+mod cursor;
+mod delete;
+mod explain;
 mod file_paths;
 mod insert;
+mod integrity_check;
+mod open_options;
 mod select;
+mod transaction;
+mod update;
+mod vacuum;
 
 use crate::dynamic_record;
 pub use crate::engine::binary_engine::utils;
+pub use crate::engine::cursor::RowCursor;
+pub use crate::engine::open_options::OpenOptions;
+pub use crate::engine::select::SelectQuery;
 use crate::metadata;
 use crate::sql_parser::query::Statement;
 use crate::{engine::binary_engine::BinaryEngine, sql_parser::query::Query};
 
+use self::analyze::Analyze;
+use self::checkpoint::Checkpoint;
 use self::create_index::CreateIndex;
 use self::create_table::CreateTable;
+use self::delete::Delete;
+use self::explain::Explain;
 use self::insert::Insert;
+use self::integrity_check::IntegrityCheck;
 use self::select::Select;
+use self::transaction::Transaction;
+use self::update::Update;
+use self::vacuum::Vacuum;
 
-pub fn io_engine_factory(storage_type: self::Type) -> Box<dyn self::Engine> {
+pub fn io_engine_factory(
+    storage_type: self::Type,
+    options: &OpenOptions,
+) -> Result<Box<dyn self::Engine>, String> {
     match storage_type {
-        self::Type::Binary => Box::new(BinaryEngine::new()),
+        self::Type::Binary => Ok(Box::new(BinaryEngine::open(options)?)),
     }
 }
 
-pub trait Engine: Select + CreateIndex + CreateTable + Insert {
+pub trait Engine:
+    Select
+    + CreateIndex
+    + CreateTable
+    + Insert
+    + Delete
+    + Update
+    + Transaction
+    + Checkpoint
+    + IntegrityCheck
+    + Vacuum
+    + Explain
+    + Analyze
+    + Send
+    + Sync
+{
     fn execute(&self, query: Query) -> EngineResult {
         match query.statement {
+            Statement::Begin => self.begin(),
+            Statement::Commit => self.commit(),
+            Statement::Rollback => self.rollback(),
+            Statement::Checkpoint => self.checkpoint(),
+            Statement::Savepoint { name } => self.savepoint(name),
+            Statement::RollbackToSavepoint { name } => self.rollback_to_savepoint(name),
+            Statement::ReleaseSavepoint { name } => self.release_savepoint(name),
+            Statement::SetIsolationLevel { level } => self.set_isolation_level(level),
+            statement => self.in_transaction(Box::new(move || self.execute_statement(statement))),
+        }
+    }
+
+    fn execute_statement(&self, statement: Statement) -> EngineResult {
+        match statement {
             Statement::CreateTable {
                 table_name,
                 columns,
             } => self.create_table(table_name, columns),
-            Statement::Select {
-                table_name,
-                selection,
-                where_clauses,
-            } => self.select(table_name, selection, where_clauses),
+            statement @ Statement::Select { .. } => {
+                let records = self
+                    .query(SelectQuery::try_from(statement)?)?
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(EngineResponse {
+                    records: Some(records),
+                    table: None,
+                    message: None,
+                })
+            }
             Statement::InsertInto {
                 table_name,
                 column_names,
                 values,
             } => self.insert(table_name, column_names, values),
-            Statement::CreateIndex {
+            Statement::CreateIndex { table_name, index } => self.create_index(table_name, index),
+            Statement::Delete {
+                table_name,
+                where_clauses,
+            } => self.delete(table_name, where_clauses),
+            Statement::Update {
                 table_name,
-                column_name,
-                index_name,
-            } => self.create_index(table_name, column_name, index_name),
+                assignments,
+                where_clauses,
+            } => self.update(table_name, assignments, where_clauses),
+            Statement::IntegrityCheck { table_name } => self.integrity_check(table_name),
+            Statement::Vacuum { table_name } => self.vacuum(table_name),
+            Statement::Analyze { table_name } => self.analyze(table_name),
+            Statement::Explain { statement, analyze } => {
+                self.explain(SelectQuery::try_from(*statement)?, analyze)
+            }
+            statement => Err(format!(
+                "'{}' cannot run inside another statement.",
+                statement
+            )),
         }
     }
 }
@@ -52,9 +126,12 @@ pub enum Type {
     Binary,
 }
 
+#[derive(Debug)]
 pub struct EngineResponse {
     pub records: Option<Vec<dynamic_record::DynamicRecord>>,
     pub table: Option<metadata::Table>,
+    /// What a statement without rows did, e.g. how many rows it deleted.
+    pub message: Option<String>,
 }
 
 pub type EngineResult = std::result::Result<EngineResponse, String>;
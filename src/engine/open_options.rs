@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use dotenvy::dotenv;
+
+/// How many bytes of rows a sort or an aggregation keeps in memory by default.
+const DEFAULT_WORK_MEMORY: usize = 4 * 1024 * 1024;
+
+/// How a process opens the database.
+#[derive(Debug, Clone)]
+pub struct OpenOptions {
+    /// The directory holding the database, `DATABASE_BASE_DIR` if it is not set.
+    pub directory: Option<String>,
+    /// Shares the database with other readers, but rejects all writes.
+    pub read_only: bool,
+    /// How long to wait for another process to release the database before giving up.
+    pub busy_timeout: Duration,
+    /// How many bytes of rows a sort or an aggregation keeps in memory before it
+    /// writes them to temporary files.
+    pub work_memory: usize,
+}
+
+impl Default for OpenOptions {
+    fn default() -> Self {
+        OpenOptions {
+            directory: None,
+            read_only: false,
+            busy_timeout: Duration::ZERO,
+            work_memory: DEFAULT_WORK_MEMORY,
+        }
+    }
+}
+
+impl OpenOptions {
+    /// Reads the options from `DATABASE_READ_ONLY`, `DATABASE_BUSY_TIMEOUT` (in milliseconds)
+    /// and `DATABASE_WORK_MEMORY` (in bytes).
+    pub fn from_env() -> Self {
+        dotenv().ok();
+
+        let read_only = std::env::var("DATABASE_READ_ONLY")
+            .is_ok_and(|read_only| matches!(read_only.to_lowercase().as_str(), "1" | "true"));
+
+        let busy_timeout = std::env::var("DATABASE_BUSY_TIMEOUT")
+            .ok()
+            .and_then(|milliseconds| milliseconds.parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or_default();
+
+        let work_memory = std::env::var("DATABASE_WORK_MEMORY")
+            .ok()
+            .and_then(|bytes| bytes.parse().ok())
+            .unwrap_or(DEFAULT_WORK_MEMORY);
+
+        OpenOptions {
+            directory: None,
+            read_only,
+            busy_timeout,
+            work_memory,
+        }
+    }
+}
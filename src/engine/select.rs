@@ -1,10 +1,78 @@
-use std::collections::HashMap;
+use crate::sql_parser::query::{Condition, Join, OrderBy, Statement};
+
+use super::RowCursor;
+
+/// A SELECT statement as the engine runs it.
+#[derive(Debug, Clone, Default)]
+pub struct SelectQuery {
+    pub table_name: String,
+    pub selection: Vec<String>,
+    pub where_clauses: Vec<Condition>,
+    pub joins: Vec<Join>,
+    pub group_by: Vec<String>,
+    pub order_by: Vec<OrderBy>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+impl SelectQuery {
+    pub fn new(table_name: String, selection: Vec<String>, where_clauses: Vec<Condition>) -> Self {
+        SelectQuery {
+            table_name,
+            selection,
+            where_clauses,
+            ..SelectQuery::default()
+        }
+    }
+}
+
+impl TryFrom<Statement> for SelectQuery {
+    type Error = String;
+
+    fn try_from(statement: Statement) -> Result<Self, Self::Error> {
+        match statement {
+            Statement::Select {
+                table_name,
+                selection,
+                where_clauses,
+                joins,
+                group_by,
+                order_by,
+                limit,
+                offset,
+            } => Ok(SelectQuery {
+                table_name,
+                selection,
+                where_clauses,
+                joins,
+                group_by,
+                order_by,
+                limit,
+                offset,
+            }),
+            statement => Err(format!("'{}' does not return rows.", statement)),
+        }
+    }
+}
 
 pub trait Select {
+    /// Plans the query and returns a cursor that only computes its rows as they are read.
+    fn query(&self, query: SelectQuery) -> Result<RowCursor<'_>, String>;
+
     fn select(
         &self,
         table_name: String,
         column_names: Vec<String>,
-        where_clauses: HashMap<String, String>,
-    ) -> super::EngineResult;
+        where_clauses: Vec<Condition>,
+    ) -> super::EngineResult {
+        let records = self
+            .query(SelectQuery::new(table_name, column_names, where_clauses))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(super::EngineResponse {
+            records: Some(records),
+            table: None,
+            message: None,
+        })
+    }
 }
@@ -0,0 +1,23 @@
+use crate::sql_parser::query::IsolationLevel;
+
+pub trait Transaction {
+    fn begin(&self) -> super::EngineResult;
+
+    fn commit(&self) -> super::EngineResult;
+
+    fn rollback(&self) -> super::EngineResult;
+
+    fn savepoint(&self, name: String) -> super::EngineResult;
+
+    fn rollback_to_savepoint(&self, name: String) -> super::EngineResult;
+
+    fn release_savepoint(&self, name: String) -> super::EngineResult;
+
+    fn set_isolation_level(&self, level: IsolationLevel) -> super::EngineResult;
+
+    /// Runs a statement inside the open transaction, or in an implicit one of its own.
+    fn in_transaction(
+        &self,
+        statement: Box<dyn FnOnce() -> super::EngineResult + '_>,
+    ) -> super::EngineResult;
+}
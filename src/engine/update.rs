@@ -0,0 +1,10 @@
+use crate::sql_parser::query::Condition;
+
+pub trait Update {
+    fn update(
+        &self,
+        table_name: String,
+        assignments: Vec<(String, String)>,
+        where_clauses: Vec<Condition>,
+    ) -> super::EngineResult;
+}
@@ -0,0 +1,4 @@
+pub trait Vacuum {
+    /// Compacts the table, or all tables if there is none.
+    fn vacuum(&self, table_name: Option<String>) -> super::EngineResult;
+}
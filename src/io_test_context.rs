@@ -2,6 +2,8 @@ use std::fs;
 
 use rand::{distributions::Alphanumeric, Rng};
 
+use crate::engine::OpenOptions;
+
 pub struct FileTestContext {
     table_name: String,
 }
@@ -35,3 +37,39 @@ impl Drop for FileTestContext {
         };
     }
 }
+
+/// A database in a fresh directory of its own, for tests whose transactions must not
+/// share a horizon with those of concurrent tests.
+pub struct TestDatabase {
+    directory: String,
+}
+
+impl TestDatabase {
+    pub fn new() -> Self {
+        let directory_name: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(10)
+            .map(char::from)
+            .collect();
+
+        TestDatabase {
+            directory: std::env::temp_dir()
+                .join(directory_name)
+                .to_string_lossy()
+                .to_string(),
+        }
+    }
+
+    pub fn options(&self) -> OpenOptions {
+        OpenOptions {
+            directory: Some(self.directory.clone()),
+            ..OpenOptions::from_env()
+        }
+    }
+}
+
+impl Drop for TestDatabase {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.directory).ok();
+    }
+}
@@ -4,6 +4,7 @@ pub mod sql_engine;
 pub mod sql_parser;
 pub mod write_ahead_log;
 pub mod dynamic_record;
+#[cfg(test)]
 mod io_test_context;
 pub mod utils;
 
@@ -5,27 +5,66 @@ use std::env;
 use std::io::{stdin, stdout, Write};
 
 const QUIT_STRING: char = 'q';
+const READ_ONLY_FLAG: &str = "--read-only";
+const INTEGRITY_CHECK_COMMAND: &str = "integrity-check";
 
 fn main() {
-    let engine = SQLEngine::new(engine::Type::Binary);
+    let mut options = engine::OpenOptions::from_env();
 
-    let mut args = env::args();
+    let mut args: Vec<String> = env::args().skip(1).collect();
 
-    if args.len() == 2 {
-        run_engine_with_cli_arg(engine, &mut args);
+    if let Some(position) = args.iter().position(|arg| arg == READ_ONLY_FLAG) {
+        args.remove(position);
+        options.read_only = true;
+    }
+
+    let engine = match SQLEngine::open(engine::Type::Binary, &options) {
+        Ok(engine) => engine,
+        Err(message) => {
+            eprintln!("ERROR: {}", message);
+            std::process::exit(1);
+        }
+    };
+
+    if args
+        .first()
+        .is_some_and(|arg| arg == INTEGRITY_CHECK_COMMAND)
+    {
+        run_integrity_check(engine, args.get(1));
+    } else if args.len() == 1 {
+        run_engine_with_cli_arg(engine, args.remove(0));
     } else {
         run_engine_with_user_input(engine);
     }
 }
 
-fn run_engine_with_cli_arg(engine: SQLEngine, args: &mut std::env::Args) {
-    let query = args
-        .nth(1)
-        .expect("Please specify one query wrapped in double quotes.");
+fn run_engine_with_cli_arg(engine: SQLEngine, query: String) {
+    let (duration, result) = track_time(|| engine.execute(query));
+
+    log_engine_output(result, duration);
+}
+
+/// Verifies the database, or one of its tables, exiting with a failure if there are any problems.
+fn run_integrity_check(engine: SQLEngine, table_name: Option<&String>) {
+    let query = match table_name {
+        Some(table_name) => format!("PRAGMA integrity_check({});", table_name),
+        None => String::from("PRAGMA integrity_check;"),
+    };
 
     let (duration, result) = track_time(|| engine.execute(query));
 
+    let failed = result.as_ref().map_or(true, |response| {
+        response
+            .records
+            .as_ref()
+            .is_some_and(|records| !records.is_empty())
+    });
+
     log_engine_output(result, duration);
+
+    if failed {
+        std::process::exit(1);
+    }
 }
 
 fn run_engine_with_user_input(engine: SQLEngine) {
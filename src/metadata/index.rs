@@ -1,41 +1,603 @@
-use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Bound;
 
 use serde::{Deserialize, Serialize};
 
+use crate::dynamic_record::{DynamicRecord, Value};
+use crate::sql_parser::query::{Condition, Function, Predicate};
+use crate::utils::unquote;
+
+use super::{SqlType, Table};
+
+/// Describes an index of a table. The entries themselves live in the index's own file.
+/// Partial indexes only hold the records matching their where clauses.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Index {
     pub name: String,
-    pub column_name: String,
-    tree: BTreeMap<String, Vec<usize>>,
+    pub columns: Vec<IndexColumn>,
+    pub unique: bool,
+    pub index_type: IndexType,
+    pub where_clauses: Vec<Condition>,
+    pub included_columns: Vec<String>,
 }
 
 impl Index {
-    pub fn new(index_name: String, column_name: &str) -> Self {
+    pub fn new(index_name: String, columns: Vec<IndexColumn>) -> Self {
         Index {
             name: index_name,
-            column_name: String::from(column_name),
-            tree: BTreeMap::new(),
+            columns,
+            unique: false,
+            index_type: IndexType::default(),
+            where_clauses: vec![],
+            included_columns: vec![],
+        }
+    }
+
+    /// Builds the (tuple) key of a record, or None if one of the indexed columns has no value
+    /// or the record is not part of a partial index.
+    pub fn key_of(&self, record: &DynamicRecord) -> Option<Vec<IndexKey>> {
+        if !record.entry_should_be_included(&self.where_clauses) {
+            return None;
         }
+
+        self.columns
+            .iter()
+            .map(|column| {
+                let value = record.fields.get(&column.name)?;
+
+                Some(match column.function {
+                    Some(function) => IndexKey::from_value(&value.apply(function)),
+                    None => IndexKey::from_value(value),
+                })
+            })
+            .collect()
+    }
+
+    /// The values of the INCLUDE columns stored next to the key, None for columns without a value.
+    pub fn included_values_of(&self, record: &DynamicRecord) -> Vec<Option<IndexKey>> {
+        self.included_columns
+            .iter()
+            .map(|column_name| record.fields.get(column_name).map(IndexKey::from_value))
+            .collect()
     }
 
-    pub fn update_tree(&mut self, values: (String, usize)) {
-        if self.tree.contains_key(&values.0) {
-            let previous_values = self.tree.get(&values.0).unwrap();
-            let mut new_values = previous_values.clone();
-            new_values.push(values.1);
+    /// The columns a record rebuilt from an index entry has: plain key columns and the included ones.
+    pub fn stored_columns(&self) -> Vec<&str> {
+        self.columns
+            .iter()
+            .filter(|column| column.function.is_none())
+            .map(|column| column.name.as_str())
+            .chain(self.included_columns.iter().map(String::as_str))
+            .collect()
+    }
 
-            self.tree.insert(values.0, new_values);
-        } else {
-            self.tree.insert(values.0, vec![values.1]);
+    /// Rebuilds the stored columns of a record from an index entry, or None if some included
+    /// values could not be stored in the index.
+    pub fn record_of(
+        &self,
+        key: &[IndexKey],
+        included: &[Option<IndexKey>],
+    ) -> Option<DynamicRecord> {
+        if included.len() != self.included_columns.len() {
+            return None;
         }
+
+        let mut fields = HashMap::new();
+
+        for (column, key) in self.columns.iter().zip(key) {
+            if column.function.is_none() {
+                fields.insert(column.name.clone(), key.to_value());
+            }
+        }
+
+        for (column_name, value) in self.included_columns.iter().zip(included) {
+            fields.insert(column_name.clone(), value.as_ref()?.to_value());
+        }
+
+        Some(DynamicRecord::new(fields))
+    }
+
+    /// Whether every record matching the where clauses is part of the index,
+    /// which for partial indexes requires them to imply the index's own where clauses.
+    pub fn covers(&self, where_clauses: &[Condition], table: &Table) -> bool {
+        self.where_clauses.iter().all(|index_condition| {
+            where_clauses
+                .iter()
+                .any(|condition| implies(condition, index_condition, table))
+        })
     }
 
-    pub fn data_page_indices(&self, key: &str) -> std::result::Result<Vec<usize>, String> {
-        let value = self.tree.get(key);
+    pub fn column_list(&self) -> String {
+        let columns: Vec<String> = self.columns.iter().map(IndexColumn::to_string).collect();
 
+        columns.join(", ")
+    }
+}
+
+/// A column of an index key, optionally with a function applied to it, as in `LOWER(email)`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct IndexColumn {
+    pub name: String,
+    pub function: Option<Function>,
+}
+
+impl IndexColumn {
+    pub fn new(name: &str) -> Self {
+        IndexColumn {
+            name: String::from(name),
+            function: None,
+        }
+    }
+
+    pub fn with_function(function: Function, name: &str) -> Self {
+        IndexColumn {
+            function: Some(function),
+            ..IndexColumn::new(name)
+        }
+    }
+
+    /// Whether a where clause restricts exactly this column, including the function applied to it.
+    pub fn matches(&self, condition: &Condition) -> bool {
+        self.name == condition.column_name && self.function == condition.function
+    }
+}
+
+impl fmt::Display for IndexColumn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.function {
+            Some(function) => write!(f, "{}({})", function, self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// Whether every value satisfying the condition also satisfies the other one.
+fn implies(condition: &Condition, other: &Condition, table: &Table) -> bool {
+    if condition.column_name != other.column_name || condition.function != other.function {
+        return false;
+    }
+
+    if condition.predicate == other.predicate {
+        return true;
+    }
+
+    let Some(data_type) = table.column_type(&condition.column_name) else {
+        return false;
+    };
+
+    if let Predicate::Equal(literal) = &condition.predicate {
+        return data_type
+            .to_value(unquote(literal))
+            .is_some_and(|value| value.satisfies(&other.predicate));
+    }
+
+    match (
+        ColumnRange::from_predicate(&condition.predicate, data_type),
+        ColumnRange::from_predicate(&other.predicate, data_type),
+    ) {
+        (Some(range), Some(other_range)) => range.implies(&other_range),
+        _ => false,
+    }
+}
+
+/// How the entries of an index are stored. Hash indexes can only answer lookups of complete keys.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IndexType {
+    #[default]
+    BTree,
+    Hash,
+}
+
+impl IndexType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "BTREE" => Some(Self::BTree),
+            "HASH" => Some(Self::Hash),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for IndexType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::BTree => write!(f, "BTREE"),
+            Self::Hash => write!(f, "HASH"),
+        }
+    }
+}
+
+/// Keys are ordered by their type, so integers sort numerically instead of as strings.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IndexKey {
+    Int(i64),
+    Text(String),
+}
+
+impl IndexKey {
+    pub fn from_value(value: &Value) -> Self {
         match value {
-            Some(value) => Ok(value.clone()),
-            None => Err(String::from("Key does not exist.")),
+            Value::Int(integer) => Self::Int(*integer),
+            Value::Text(text) => Self::Text(text.clone()),
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        match self {
+            Self::Int(integer) => Value::Int(*integer),
+            Self::Text(text) => Value::Text(text.clone()),
+        }
+    }
+
+    pub fn from_literal(literal: &str, data_type: &SqlType) -> Option<Self> {
+        Some(Self::from_value(&data_type.to_value(unquote(literal))?))
+    }
+
+    /// Formats a (tuple) key the way it would be written in a query, e.g. `('berlin', 30)`.
+    pub fn format_key(key: &[IndexKey]) -> String {
+        let parts: Vec<String> = key.iter().map(IndexKey::to_string).collect();
+
+        format!("({})", parts.join(", "))
+    }
+}
+
+impl fmt::Display for IndexKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Int(integer) => write!(f, "{}", integer),
+            Self::Text(text) => write!(f, "'{}'", text),
+        }
+    }
+}
+
+/// The part of an index a query has to look at: equal values for the leading columns of the index,
+/// optionally followed by a range on the next column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyRange {
+    pub prefix: Vec<IndexKey>,
+    pub last: Option<ColumnRange>,
+}
+
+impl KeyRange {
+    /// Matches as many leading columns of the index as possible against the where clauses.
+    /// Returns None if not even the first column of the index is restricted.
+    pub fn new(index: &Index, where_clauses: &[Condition], table: &Table) -> Option<Self> {
+        let mut prefix = vec![];
+
+        for column in &index.columns {
+            let data_type = table.column_type(&column.name)?;
+
+            let column_ranges: Vec<ColumnRange> = where_clauses
+                .iter()
+                .filter(|condition| column.matches(condition))
+                .filter_map(|condition| {
+                    ColumnRange::from_predicate(&condition.predicate, data_type)
+                })
+                .collect();
+
+            if let Some(key) = column_ranges.iter().find_map(ColumnRange::point_key) {
+                prefix.push(key.clone());
+                continue;
+            }
+
+            let last = column_ranges.into_iter().next();
+
+            if prefix.is_empty() && last.is_none() {
+                return None;
+            }
+
+            return Some(KeyRange { prefix, last });
         }
+
+        Some(KeyRange { prefix, last: None })
+    }
+
+    pub fn is_point_lookup(&self, index: &Index) -> bool {
+        self.prefix.len() == index.columns.len()
+    }
+
+    pub fn matched_columns(&self) -> usize {
+        self.prefix.len() + usize::from(self.last.is_some())
+    }
+
+    /// The smallest key the scan has to start at.
+    pub fn start(&self) -> Vec<IndexKey> {
+        let mut start = self.prefix.clone();
+
+        match &self.last {
+            Some(ColumnRange::Range(Bound::Included(key) | Bound::Excluded(key), _)) => {
+                start.push(key.clone())
+            }
+            Some(ColumnRange::Prefix(prefix)) => start.push(IndexKey::Text(prefix.clone())),
+            _ => {}
+        }
+
+        start
+    }
+
+    /// False once a scan in key order has passed the end of the range.
+    pub fn in_range(&self, key: &[IndexKey]) -> bool {
+        if key.len() < self.prefix.len() || key[..self.prefix.len()] != self.prefix[..] {
+            return false;
+        }
+
+        match (&self.last, key.get(self.prefix.len())) {
+            (Some(ColumnRange::Range(_, upper)), Some(key)) => match upper {
+                Bound::Included(upper) => key <= upper,
+                Bound::Excluded(upper) => key < upper,
+                Bound::Unbounded => true,
+            },
+            (Some(ColumnRange::Prefix(prefix)), Some(key)) => {
+                matches!(key, IndexKey::Text(text) if text.starts_with(prefix.as_str()))
+            }
+            _ => true,
+        }
+    }
+
+    /// Whether a key that is in range should be part of the result, which only excludes an exclusive lower bound.
+    pub fn includes(&self, key: &[IndexKey]) -> bool {
+        match (&self.last, key.get(self.prefix.len())) {
+            (Some(ColumnRange::Range(Bound::Excluded(lower), _)), Some(key)) => key > lower,
+            _ => true,
+        }
+    }
+}
+
+/// The values a single column has to lie within.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnRange {
+    Range(Bound<IndexKey>, Bound<IndexKey>),
+    Prefix(String),
+}
+
+impl ColumnRange {
+    /// Returns None if the predicate cannot be answered by scanning a part of the index.
+    pub fn from_predicate(predicate: &Predicate, data_type: &SqlType) -> Option<Self> {
+        let key = |literal: &str| IndexKey::from_literal(literal, data_type);
+
+        match predicate {
+            Predicate::Equal(literal) => Some(Self::Range(
+                Bound::Included(key(literal)?),
+                Bound::Included(key(literal)?),
+            )),
+            Predicate::LessThan(literal) => Some(Self::Range(
+                Bound::Unbounded,
+                Bound::Excluded(key(literal)?),
+            )),
+            Predicate::LessThanOrEqual(literal) => Some(Self::Range(
+                Bound::Unbounded,
+                Bound::Included(key(literal)?),
+            )),
+            Predicate::GreaterThan(literal) => Some(Self::Range(
+                Bound::Excluded(key(literal)?),
+                Bound::Unbounded,
+            )),
+            Predicate::GreaterThanOrEqual(literal) => Some(Self::Range(
+                Bound::Included(key(literal)?),
+                Bound::Unbounded,
+            )),
+            Predicate::Between(lower, upper) => Some(Self::Range(
+                Bound::Included(key(lower)?),
+                Bound::Included(key(upper)?),
+            )),
+            Predicate::Like(pattern) => {
+                let SqlType::Varchar = data_type else {
+                    return None;
+                };
+
+                let pattern = unquote(pattern);
+                let prefix = pattern.strip_suffix('%').unwrap_or(pattern);
+
+                if prefix.contains(['%', '_']) {
+                    None
+                } else if prefix.len() == pattern.len() {
+                    Self::from_predicate(&Predicate::Equal(prefix.to_string()), data_type)
+                } else {
+                    Some(Self::Prefix(prefix.to_string()))
+                }
+            }
+        }
+    }
+
+    /// Whether every key within this range is also within the other one.
+    pub fn implies(&self, other: &ColumnRange) -> bool {
+        match (self, other) {
+            (Self::Range(lower, upper), Self::Range(other_lower, other_upper)) => {
+                lower_bound_within(lower, other_lower) && upper_bound_within(upper, other_upper)
+            }
+            (Self::Prefix(prefix), Self::Prefix(other_prefix)) => prefix.starts_with(other_prefix),
+            (Self::Range(..), Self::Prefix(other_prefix)) => matches!(
+                self.point_key(),
+                Some(IndexKey::Text(text)) if text.starts_with(other_prefix.as_str())
+            ),
+            (Self::Prefix(_), Self::Range(..)) => false,
+        }
+    }
+
+    pub fn point_key(&self) -> Option<&IndexKey> {
+        match self {
+            Self::Range(Bound::Included(lower), Bound::Included(upper)) if lower == upper => {
+                Some(lower)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn lower_bound_within(bound: &Bound<IndexKey>, other: &Bound<IndexKey>) -> bool {
+    match (bound, other) {
+        (_, Bound::Unbounded) => true,
+        (Bound::Unbounded, _) => false,
+        (Bound::Included(key), Bound::Excluded(other_key)) => key > other_key,
+        (
+            Bound::Included(key) | Bound::Excluded(key),
+            Bound::Included(other_key) | Bound::Excluded(other_key),
+        ) => key >= other_key,
+    }
+}
+
+fn upper_bound_within(bound: &Bound<IndexKey>, other: &Bound<IndexKey>) -> bool {
+    match (bound, other) {
+        (_, Bound::Unbounded) => true,
+        (Bound::Unbounded, _) => false,
+        (Bound::Included(key), Bound::Excluded(other_key)) => key < other_key,
+        (
+            Bound::Included(key) | Bound::Excluded(key),
+            Bound::Included(other_key) | Bound::Excluded(other_key),
+        ) => key <= other_key,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orders_integer_keys_numerically() {
+        assert!(IndexKey::Int(9) < IndexKey::Int(10));
+        assert!(
+            IndexKey::from_literal("9", &SqlType::Integer)
+                < IndexKey::from_literal("10", &SqlType::Integer)
+        );
+    }
+
+    #[test]
+    fn test_only_uses_prefix_like_patterns_for_range_scans() {
+        let data_type = SqlType::Varchar;
+
+        assert_eq!(
+            ColumnRange::from_predicate(&Predicate::Like(String::from("'abc%'")), &data_type),
+            Some(ColumnRange::Prefix(String::from("abc")))
+        );
+        assert!(
+            ColumnRange::from_predicate(&Predicate::Like(String::from("'%abc'")), &data_type)
+                .is_none()
+        );
+        assert!(
+            ColumnRange::from_predicate(&Predicate::Like(String::from("'a_c%'")), &data_type)
+                .is_none()
+        );
+        assert!(
+            ColumnRange::from_predicate(&Predicate::Like(String::from("'abc'")), &data_type)
+                .unwrap()
+                .point_key()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_matches_the_leftmost_columns_of_a_composite_index() {
+        let table = Table::new(
+            "my_table".to_string(),
+            vec![
+                vec![String::from("city"), String::from("VARCHAR")],
+                vec![String::from("age"), String::from("INTEGER")],
+                vec![String::from("name"), String::from("VARCHAR")],
+            ],
+        );
+        let index = Index::new(
+            String::from("composite_index"),
+            vec![
+                IndexColumn::new("city"),
+                IndexColumn::new("age"),
+                IndexColumn::new("name"),
+            ],
+        );
+
+        let key_range = KeyRange::new(
+            &index,
+            &[
+                Condition::new("age", Predicate::GreaterThan(String::from("30"))),
+                Condition::new("city", Predicate::Equal(String::from("'berlin'"))),
+            ],
+            &table,
+        )
+        .unwrap();
+
+        assert_eq!(
+            key_range.prefix,
+            vec![IndexKey::Text(String::from("berlin"))]
+        );
+        assert_eq!(
+            key_range.last,
+            Some(ColumnRange::Range(
+                Bound::Excluded(IndexKey::Int(30)),
+                Bound::Unbounded
+            ))
+        );
+        assert!(!key_range.is_point_lookup(&index));
+
+        assert!(KeyRange::new(
+            &index,
+            &[Condition::new("age", Predicate::Equal(String::from("30")))],
+            &table,
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_partial_indexes_only_cover_queries_implying_their_where_clauses() {
+        let table = Table::new(
+            "orders".to_string(),
+            vec![
+                vec![String::from("status"), String::from("VARCHAR")],
+                vec![String::from("amount"), String::from("INTEGER")],
+            ],
+        );
+        let index = Index {
+            where_clauses: vec![
+                Condition::new("status", Predicate::Equal(String::from("'open'"))),
+                Condition::new("amount", Predicate::GreaterThan(String::from("10"))),
+            ],
+            ..Index::new(
+                String::from("open_orders"),
+                vec![IndexColumn::new("amount")],
+            )
+        };
+
+        let status =
+            |literal: &str| Condition::new("status", Predicate::Equal(literal.to_string()));
+        let amount = |predicate: Predicate| Condition::new("amount", predicate);
+
+        assert!(index.covers(
+            &[
+                status("'open'"),
+                amount(Predicate::GreaterThanOrEqual(String::from("20")))
+            ],
+            &table
+        ));
+        assert!(index.covers(
+            &[
+                status("'open'"),
+                amount(Predicate::Between(String::from("11"), String::from("15")))
+            ],
+            &table
+        ));
+        assert!(index.covers(
+            &[
+                status("'open'"),
+                amount(Predicate::Equal(String::from("12")))
+            ],
+            &table
+        ));
+        assert!(!index.covers(
+            &[
+                status("'open'"),
+                amount(Predicate::GreaterThanOrEqual(String::from("10")))
+            ],
+            &table
+        ));
+        assert!(!index.covers(
+            &[
+                status("'closed'"),
+                amount(Predicate::GreaterThan(String::from("10")))
+            ],
+            &table
+        ));
+        assert!(!index.covers(
+            &[amount(Predicate::GreaterThan(String::from("10")))],
+            &table
+        ));
     }
 }
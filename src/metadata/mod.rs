@@ -1,9 +1,11 @@
-mod table;
+mod column;
 mod index;
 mod sql_type;
-mod column;
+mod statistics;
+mod table;
 
-pub use crate::metadata::table::Table;
 pub use crate::metadata::column::Column;
-pub use crate::metadata::index::Index;
+pub use crate::metadata::index::{ColumnRange, Index, IndexColumn, IndexKey, IndexType, KeyRange};
 pub use crate::metadata::sql_type::SqlType;
+pub use crate::metadata::statistics::{ColumnStatistics, StatisticsCollector, TableStatistics};
+pub use crate::metadata::table::Table;
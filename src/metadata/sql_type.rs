@@ -1,3 +1,5 @@
+use crate::dynamic_record::Value;
+
 pub const VARCHAR: &str = "VARCHAR";
 pub const INTEGER: &str = "INTEGER";
 pub const INTEGER_SHORTHAND: &str = "INT";
@@ -25,6 +27,14 @@ impl SqlType {
             SqlType::Integer => value.parse::<i64>().is_ok(),
         }
     }
+
+    /// Converts an (unquoted) literal into a value of this type.
+    pub fn to_value(&self, value: &str) -> Option<Value> {
+        match self {
+            SqlType::Varchar => Some(Value::Text(value.to_string())),
+            SqlType::Integer => value.parse::<i64>().ok().map(Value::Int),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -58,4 +68,14 @@ mod tests {
         assert!(!SqlType::Integer.allows_value("asdf".to_string()));
         assert!(!SqlType::Integer.allows_value("another value".to_string()));
     }
+
+    #[test]
+    fn test_converts_literals_into_typed_values() {
+        match SqlType::Integer.to_value("42") {
+            Some(super::Value::Int(42)) => {}
+            _ => panic!("wrong value"),
+        }
+
+        assert!(SqlType::Integer.to_value("42a").is_none());
+    }
 }
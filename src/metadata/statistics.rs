@@ -0,0 +1,270 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+
+use rand::rngs::ThreadRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::dynamic_record::DynamicRecord;
+
+use super::IndexKey;
+
+/// How many buckets the histogram of a column has at most.
+const HISTOGRAM_BUCKETS: usize = 10;
+
+/// How many values of a column are kept to build its histogram and estimate its distinct values.
+const SAMPLE_SIZE: usize = 30_000;
+
+/// What ANALYZE found out about the rows of a table when it last ran.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TableStatistics {
+    pub row_count: usize,
+    pub data_page_count: usize,
+    pub columns: HashMap<String, ColumnStatistics>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ColumnStatistics {
+    pub distinct_count: usize,
+    /// The fraction of rows without a value in the column.
+    pub null_fraction: f64,
+    /// Ascending bounds of buckets that each hold about the same number of rows,
+    /// from the smallest value to the largest.
+    pub histogram: Vec<IndexKey>,
+}
+
+/// Collects the statistics of the given columns from the rows of a table as they are read,
+/// keeping a uniform random sample of at most `SAMPLE_SIZE` values of every column.
+pub struct StatisticsCollector {
+    row_count: usize,
+    columns: Vec<(String, ColumnSample)>,
+    random: ThreadRng,
+}
+
+#[derive(Default)]
+struct ColumnSample {
+    value_count: usize,
+    sample: Vec<IndexKey>,
+}
+
+impl StatisticsCollector {
+    pub fn new(column_names: &[&str]) -> Self {
+        StatisticsCollector {
+            row_count: 0,
+            columns: column_names
+                .iter()
+                .map(|column_name| (column_name.to_string(), ColumnSample::default()))
+                .collect(),
+            random: rand::thread_rng(),
+        }
+    }
+
+    pub fn add(&mut self, row: &DynamicRecord) {
+        self.row_count += 1;
+
+        for (column_name, sample) in &mut self.columns {
+            if let Some(value) = row.fields.get(column_name) {
+                sample.add(IndexKey::from_value(value), &mut self.random);
+            }
+        }
+    }
+
+    pub fn finish(self, data_page_count: usize) -> TableStatistics {
+        let row_count = self.row_count;
+
+        TableStatistics {
+            row_count,
+            data_page_count,
+            columns: self
+                .columns
+                .into_iter()
+                .map(|(column_name, sample)| (column_name, sample.finish(row_count)))
+                .collect(),
+        }
+    }
+}
+
+impl ColumnSample {
+    /// Reservoir sampling: the n-th value replaces a random one of the sample
+    /// with a probability of `SAMPLE_SIZE / n`.
+    fn add(&mut self, key: IndexKey, random: &mut ThreadRng) {
+        self.value_count += 1;
+
+        if self.sample.len() < SAMPLE_SIZE {
+            self.sample.push(key);
+        } else {
+            let position = random.gen_range(0..self.value_count);
+
+            if position < SAMPLE_SIZE {
+                self.sample[position] = key;
+            }
+        }
+    }
+
+    fn finish(mut self, row_count: usize) -> ColumnStatistics {
+        let keys = &mut self.sample;
+        keys.sort();
+
+        let buckets = HISTOGRAM_BUCKETS.min(keys.len().saturating_sub(1));
+
+        let histogram = match keys.is_empty() {
+            true => vec![],
+            false => (0..=buckets)
+                .map(|bucket| keys[bucket * (keys.len() - 1) / buckets.max(1)].clone())
+                .collect(),
+        };
+
+        ColumnStatistics {
+            distinct_count: self.distinct_count(),
+            null_fraction: match row_count {
+                0 => 0.0,
+                _ => 1.0 - self.value_count as f64 / row_count as f64,
+            },
+            histogram,
+        }
+    }
+
+    /// Exact if the sample holds all values, otherwise estimated from how many of the sampled
+    /// values occur only once (Haas and Stokes' Duj1 estimator).
+    fn distinct_count(&self) -> usize {
+        let mut occurrences: BTreeMap<&IndexKey, usize> = BTreeMap::new();
+
+        for key in &self.sample {
+            *occurrences.entry(key).or_default() += 1;
+        }
+
+        let distinct = occurrences.len();
+
+        if self.sample.len() == self.value_count {
+            return distinct;
+        }
+
+        let (sampled, total) = (self.sample.len() as f64, self.value_count as f64);
+        let once = occurrences.values().filter(|count| **count == 1).count() as f64;
+        let estimate = sampled * distinct as f64 / (sampled - once + once * sampled / total);
+
+        (estimate.round() as usize).clamp(distinct, self.value_count)
+    }
+}
+
+impl ColumnStatistics {
+    /// The estimated fraction of the rows with a value in the column that lies within the bounds.
+    pub fn range_fraction(&self, lower: Bound<&IndexKey>, upper: Bound<&IndexKey>) -> f64 {
+        let below = |bound: Bound<&IndexKey>, unbounded: f64| match bound {
+            Bound::Included(key) | Bound::Excluded(key) => self.fraction_below(key),
+            Bound::Unbounded => unbounded,
+        };
+
+        (below(upper, 1.0) - below(lower, 0.0)).max(0.0)
+    }
+
+    /// The estimated fraction of the values that are smaller than the key, interpolating
+    /// linearly within the bucket of integer keys.
+    fn fraction_below(&self, key: &IndexKey) -> f64 {
+        let (Some(first), Some(last)) = (self.histogram.first(), self.histogram.last()) else {
+            return 0.0;
+        };
+
+        if key <= first {
+            return 0.0;
+        }
+
+        if key > last {
+            return 1.0;
+        }
+
+        let buckets = (self.histogram.len() - 1).max(1);
+        let bucket = self
+            .histogram
+            .windows(2)
+            .position(|bounds| key <= &bounds[1])
+            .unwrap_or(buckets - 1);
+
+        let within_bucket = match (
+            &self.histogram[bucket],
+            &self.histogram[(bucket + 1).min(self.histogram.len() - 1)],
+            key,
+        ) {
+            (IndexKey::Int(lower), IndexKey::Int(upper), IndexKey::Int(key)) if upper > lower => {
+                (key - lower) as f64 / (upper - lower) as f64
+            }
+            _ => 0.5,
+        };
+
+        (bucket as f64 + within_bucket) / buckets as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dynamic_record::Value;
+
+    #[test]
+    fn test_collects_distinct_counts_null_fractions_and_histograms() {
+        let rows: Vec<DynamicRecord> = (0..100)
+            .map(|number| {
+                let mut fields = HashMap::from([(String::from("age"), Value::Int(number))]);
+
+                if number % 4 != 0 {
+                    fields.insert(
+                        String::from("city"),
+                        Value::Text(format!("c{}", number % 3)),
+                    );
+                }
+
+                DynamicRecord::new(fields)
+            })
+            .collect();
+
+        let mut collector = StatisticsCollector::new(&["age", "city"]);
+        rows.iter().for_each(|row| collector.add(row));
+
+        let statistics = collector.finish(2);
+        let age = &statistics.columns["age"];
+        let city = &statistics.columns["city"];
+
+        assert_eq!(statistics.row_count, 100);
+        assert_eq!(age.distinct_count, 100);
+        assert_eq!(age.histogram.len(), HISTOGRAM_BUCKETS + 1);
+        assert_eq!(age.histogram.first(), Some(&IndexKey::Int(0)));
+        assert_eq!(age.histogram.last(), Some(&IndexKey::Int(99)));
+        assert_eq!(city.distinct_count, 3);
+        assert_eq!(city.null_fraction, 0.25);
+
+        let fraction = age.range_fraction(Bound::Included(&IndexKey::Int(20)), Bound::Unbounded);
+        assert!((fraction - 0.8).abs() < 0.02, "{}", fraction);
+        assert_eq!(
+            age.range_fraction(Bound::Unbounded, Bound::Excluded(&IndexKey::Int(-5))),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_samples_the_values_of_large_tables() {
+        let mut collector = StatisticsCollector::new(&["id", "group"]);
+
+        for number in 0..100_000 {
+            collector.add(&DynamicRecord::new(HashMap::from([
+                (String::from("id"), Value::Int(number)),
+                (String::from("group"), Value::Int(number % 100)),
+            ])));
+        }
+
+        assert!(collector
+            .columns
+            .iter()
+            .all(|(_, sample)| sample.sample.len() == SAMPLE_SIZE));
+
+        let statistics = collector.finish(40);
+        let (id, group) = (&statistics.columns["id"], &statistics.columns["group"]);
+
+        assert_eq!(statistics.row_count, 100_000);
+        assert_eq!(group.distinct_count, 100);
+        assert_eq!(id.distinct_count, 100_000);
+        assert_eq!(id.null_fraction, 0.0);
+
+        let fraction = id.range_fraction(Bound::Unbounded, Bound::Excluded(&IndexKey::Int(25_000)));
+        assert!((fraction - 0.25).abs() < 0.05, "{}", fraction);
+    }
+}
@@ -1,8 +1,7 @@
-use std::collections::HashMap;
-
 use crate::engine::utils::selected_all_columns;
+use crate::sql_parser::query::Condition;
 
-use super::sql_type::SqlType;
+use super::{sql_type::SqlType, IndexType, KeyRange, TableStatistics};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -12,6 +11,12 @@ pub struct Table {
     latest_primary_key: usize,
     pub columns: Vec<super::Column>,
     pub indices: Vec<super::Index>,
+    /// The last transaction that inserted or deleted rows of the table.
+    pub last_writer: u64,
+    /// Deleted versions that are still stored, because some snapshot may see them.
+    pub dead_versions: usize,
+    /// What ANALYZE collected about the rows, if it ran on the table.
+    pub statistics: Option<TableStatistics>,
 }
 
 impl Table {
@@ -45,12 +50,18 @@ impl Table {
         Table {
             name: table_name,
             columns,
-            indices: vec![super::Index::new(
-                format!("{}_index", &primary_key.name),
-                &primary_key.name,
-            )],
+            indices: vec![super::Index {
+                unique: true,
+                ..super::Index::new(
+                    format!("{}_index", &primary_key.name),
+                    vec![super::IndexColumn::new(&primary_key.name)],
+                )
+            }],
             primary_key,
             latest_primary_key: 0,
+            last_writer: 0,
+            dead_versions: 0,
+            statistics: None,
         }
     }
 
@@ -62,35 +73,46 @@ impl Table {
         new_key
     }
 
-    pub fn data_page_indices(&self, where_clauses: &HashMap<String, String>) -> Option<Vec<usize>> {
-        let indexed_columns = self.indexed_columns_names();
-
-        for key in where_clauses.keys() {
-            if indexed_columns.contains(key) {
-                return Some(
-                    self.index(key)
-                        .unwrap()
-                        .data_page_indices(where_clauses.get(key).unwrap())
-                        .unwrap(),
-                );
-            }
-        }
+    pub fn latest_primary_key(&self) -> usize {
+        self.latest_primary_key
+    }
 
-        None
+    /// Picks the index to use for the given where clauses, together with the part of it to scan.
+    /// Point lookups are preferred, then the index matching the most columns.
+    pub fn index_lookup(&self, where_clauses: &[Condition]) -> Option<(&super::Index, KeyRange)> {
+        self.index_lookups(where_clauses)
+            .max_by_key(|(index, key_range)| {
+                (
+                    key_range.is_point_lookup(index),
+                    key_range.matched_columns(),
+                )
+            })
     }
 
-    fn index(&self, column_name: &str) -> Option<super::Index> {
+    /// Every index that can answer the where clauses, together with the part of it to scan.
+    pub fn index_lookups<'a: 'b, 'b>(
+        &'a self,
+        where_clauses: &'b [Condition],
+    ) -> impl Iterator<Item = (&'a super::Index, KeyRange)> + 'b {
         self.indices
             .iter()
-            .find(|index| index.column_name == column_name)
-            .cloned()
+            .rev()
+            .filter(|index| index.covers(where_clauses, self))
+            .filter_map(|index| Some((index, KeyRange::new(index, where_clauses, self)?)))
+            .filter(|(index, key_range)| {
+                index.index_type != IndexType::Hash || key_range.is_point_lookup(index)
+            })
     }
 
-    fn indexed_columns_names(&self) -> Vec<String> {
-        self.indices
+    pub fn column_type(&self, column_name: &str) -> Option<&SqlType> {
+        if self.primary_key.name == column_name {
+            return Some(&self.primary_key.data_type);
+        }
+
+        self.columns
             .iter()
-            .map(|index| index.column_name.clone())
-            .collect()
+            .find(|column| column.name == column_name)
+            .map(|column| &column.data_type)
     }
 
     pub fn all_columns_exist(&self, column_names: Vec<String>) -> bool {
@@ -116,6 +138,7 @@ impl Table {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::sql_parser::query::Predicate;
 
     #[test]
     fn test_can_create_a_table_with_the_columns_input() {
@@ -140,4 +163,31 @@ mod tests {
             _ => panic!("failed"),
         }
     }
+
+    #[test]
+    fn test_only_uses_hash_indices_for_complete_keys() {
+        let mut table = Table::new(
+            "my_table".to_string(),
+            vec![vec![String::from("email"), String::from("VARCHAR")]],
+        );
+        table.indices.push(super::super::Index {
+            index_type: IndexType::Hash,
+            ..super::super::Index::new(
+                String::from("email_index"),
+                vec![super::super::IndexColumn::new("email")],
+            )
+        });
+
+        let equal = [Condition::new(
+            "email",
+            Predicate::Equal(String::from("'a@mail.com'")),
+        )];
+        let prefix = [Condition::new(
+            "email",
+            Predicate::Like(String::from("'a%'")),
+        )];
+
+        assert_eq!(table.index_lookup(&equal).unwrap().0.name, "email_index");
+        assert!(table.index_lookup(&prefix).is_none());
+    }
 }
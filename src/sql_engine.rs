@@ -7,10 +7,23 @@ pub struct SQLEngine {
 
 impl SQLEngine {
     pub fn new(io_type: engine::Type) -> Self {
-        SQLEngine {
+        Self::open(io_type, &engine::OpenOptions::from_env())
+            .expect("\tFailed to open the database.")
+    }
+
+    pub fn open(io_type: engine::Type, options: &engine::OpenOptions) -> Result<Self, String> {
+        Ok(SQLEngine {
             input_parser: sql_parser::SqlParser(),
-            io_engine: engine::io_engine_factory(io_type),
-        }
+            io_engine: engine::io_engine_factory(io_type, options)?,
+        })
+    }
+
+    /// Runs a SELECT statement, returning a cursor to stream through its rows.
+    pub fn query(&self, query: String) -> Result<engine::RowCursor<'_>, String> {
+        let query = self.input_parser.parse_query(query)?;
+
+        self.io_engine
+            .query(engine::SelectQuery::try_from(query.statement)?)
     }
 
     pub fn execute(&self, query: String) -> engine::EngineResult {
@@ -27,9 +40,6 @@ impl SQLEngine {
 
 impl Default for SQLEngine {
     fn default() -> Self {
-        SQLEngine {
-            input_parser: sql_parser::SqlParser(),
-            io_engine: engine::io_engine_factory(engine::Type::Binary),
-        }
+        SQLEngine::new(engine::Type::Binary)
     }
 }
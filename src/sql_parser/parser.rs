@@ -12,7 +12,7 @@ impl SqlParser {
         let mut graphemes: Vec<String> = Vec::with_capacity(raw_graphemes.len());
 
         for grapheme in raw_graphemes {
-            let grapheme_with_spaces = grapheme
+            let grapheme_with_spaces = separate_comparison_operators(grapheme)
                 .replace('(', " ( ")
                 .replace(',', " , ")
                 .replace(')', " ) ")
@@ -37,7 +37,7 @@ impl SqlParser {
 
         if let StatementType::Invalid = statement_type {
             return Err(String::from(
-                "Unimplemented Command. Please use 'SELECT', 'CREATE TABLE' or 'INSERT'",
+                "Unimplemented Command. Please use 'SELECT', 'CREATE TABLE', 'INSERT', 'UPDATE', 'DELETE', 'BEGIN', 'COMMIT', 'ROLLBACK', 'SAVEPOINT', 'RELEASE', 'SET TRANSACTION', 'CHECKPOINT', 'PRAGMA', 'VACUUM', 'EXPLAIN' or 'ANALYZE'",
             ));
         }
 
@@ -49,3 +49,29 @@ impl SqlParser {
         }
     }
 }
+
+/// Surrounds comparison operators with spaces, so that `id>=5` becomes `id >= 5`.
+fn separate_comparison_operators(grapheme: &str) -> String {
+    let mut separated = String::with_capacity(grapheme.len());
+    let mut chars = grapheme.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        if !matches!(char, '<' | '>' | '=' | '!') {
+            separated.push(char);
+            continue;
+        }
+
+        separated.push(' ');
+        separated.push(char);
+
+        if let Some(next_char) =
+            chars.next_if(|next_char| *next_char == '=' || (char == '<' && *next_char == '>'))
+        {
+            separated.push(next_char);
+        }
+
+        separated.push(' ');
+    }
+
+    separated
+}
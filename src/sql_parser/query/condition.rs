@@ -0,0 +1,77 @@
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::Function;
+
+/// A single where clause on a column or a function of it. Multiple conditions are combined with AND.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub column_name: String,
+    pub function: Option<Function>,
+    pub predicate: Predicate,
+}
+
+impl Condition {
+    pub fn new(column_name: &str, predicate: Predicate) -> Self {
+        Condition {
+            column_name: String::from(column_name),
+            function: None,
+            predicate,
+        }
+    }
+
+    pub fn with_function(function: Function, column_name: &str, predicate: Predicate) -> Self {
+        Condition {
+            function: Some(function),
+            ..Condition::new(column_name, predicate)
+        }
+    }
+}
+
+/// The literals are kept as written in the query, including quotes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Predicate {
+    Equal(String),
+    LessThan(String),
+    LessThanOrEqual(String),
+    GreaterThan(String),
+    GreaterThanOrEqual(String),
+    Between(String, String),
+    Like(String),
+}
+
+impl Predicate {
+    pub fn new(operator: &str, value: String) -> Option<Self> {
+        match operator.to_uppercase().as_ref() {
+            "=" => Some(Self::Equal(value)),
+            "<" => Some(Self::LessThan(value)),
+            "<=" => Some(Self::LessThanOrEqual(value)),
+            ">" => Some(Self::GreaterThan(value)),
+            ">=" => Some(Self::GreaterThanOrEqual(value)),
+            "LIKE" => Some(Self::Like(value)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operand = match &self.function {
+            Some(function) => format!("{}({})", function, self.column_name),
+            None => self.column_name.clone(),
+        };
+
+        match &self.predicate {
+            Predicate::Equal(value) => write!(f, "{} = {}", operand, value),
+            Predicate::LessThan(value) => write!(f, "{} < {}", operand, value),
+            Predicate::LessThanOrEqual(value) => write!(f, "{} <= {}", operand, value),
+            Predicate::GreaterThan(value) => write!(f, "{} > {}", operand, value),
+            Predicate::GreaterThanOrEqual(value) => write!(f, "{} >= {}", operand, value),
+            Predicate::Between(lower, upper) => {
+                write!(f, "{} BETWEEN {} AND {}", operand, lower, upper)
+            }
+            Predicate::Like(pattern) => write!(f, "{} LIKE {}", operand, pattern),
+        }
+    }
+}
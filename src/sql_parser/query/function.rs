@@ -0,0 +1,29 @@
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Scalar functions that can be applied to a column in where clauses and index definitions.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Lower,
+    Upper,
+}
+
+impl Function {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "LOWER" => Some(Self::Lower),
+            "UPPER" => Some(Self::Upper),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lower => write!(f, "LOWER"),
+            Self::Upper => write!(f, "UPPER"),
+        }
+    }
+}
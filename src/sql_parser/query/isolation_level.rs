@@ -0,0 +1,35 @@
+use core::fmt;
+
+/// How much of the changes committed by other transactions a transaction sees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IsolationLevel {
+    /// Every statement sees the changes committed before it started.
+    #[default]
+    ReadCommitted,
+    /// Every statement sees the changes committed before the first statement of the transaction.
+    RepeatableRead,
+}
+
+impl IsolationLevel {
+    pub fn from_graphemes(graphemes: &[String]) -> Option<Self> {
+        let name: Vec<String> = graphemes
+            .iter()
+            .map(|grapheme| grapheme.to_uppercase())
+            .collect();
+
+        match name.join(" ").as_str() {
+            "READ COMMITTED" => Some(Self::ReadCommitted),
+            "REPEATABLE READ" | "SNAPSHOT" => Some(Self::RepeatableRead),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for IsolationLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReadCommitted => write!(f, "READ COMMITTED"),
+            Self::RepeatableRead => write!(f, "REPEATABLE READ"),
+        }
+    }
+}
@@ -1,6 +1,14 @@
+mod condition;
+mod function;
+mod isolation_level;
+mod select_clause;
 mod statement;
 mod statement_type;
 
+pub use crate::sql_parser::query::condition::{Condition, Predicate};
+pub use crate::sql_parser::query::function::Function;
+pub use crate::sql_parser::query::isolation_level::IsolationLevel;
+pub use crate::sql_parser::query::select_clause::{Aggregate, AggregateFunction, Join, OrderBy};
 pub use crate::sql_parser::query::statement::Statement;
 pub use crate::sql_parser::query::statement_type::StatementType;
 
@@ -0,0 +1,103 @@
+use core::fmt;
+
+/// `JOIN table_name ON left_column = right_column`, keeping the rows of both tables with equal values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Join {
+    pub table_name: String,
+    pub left_column: String,
+    pub right_column: String,
+}
+
+/// A column of `ORDER BY`, sorted in ascending order unless it is descending.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderBy {
+    pub column_name: String,
+    pub descending: bool,
+}
+
+/// An aggregate in the selection, like `COUNT(*)` or `MAX(age)`. It is written back the same
+/// way, which is also the name of the column holding its result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregate {
+    pub function: AggregateFunction,
+    /// None for `COUNT(*)`.
+    pub column_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    /// Recognizes an aggregate among the selected items, which are columns otherwise.
+    pub fn from_selection(item: &str) -> Option<Self> {
+        let (name, argument) = item.strip_suffix(')')?.split_once('(')?;
+
+        let function = match name.to_uppercase().as_str() {
+            "COUNT" => AggregateFunction::Count,
+            "SUM" => AggregateFunction::Sum,
+            "MIN" => AggregateFunction::Min,
+            "MAX" => AggregateFunction::Max,
+            _ => return None,
+        };
+
+        let column_name = match argument {
+            "*" if function == AggregateFunction::Count => None,
+            "" | "*" => return None,
+            column_name => Some(column_name.to_string()),
+        };
+
+        Some(Aggregate {
+            function,
+            column_name,
+        })
+    }
+}
+
+impl fmt::Display for Join {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "JOIN {} ON {} = {}",
+            self.table_name, self.left_column, self.right_column
+        )
+    }
+}
+
+impl fmt::Display for OrderBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.column_name)?;
+
+        if self.descending {
+            write!(f, " DESC")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}({})",
+            self.function,
+            self.column_name.as_deref().unwrap_or("*")
+        )
+    }
+}
+
+impl fmt::Display for AggregateFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Count => write!(f, "COUNT"),
+            Self::Sum => write!(f, "SUM"),
+            Self::Min => write!(f, "MIN"),
+            Self::Max => write!(f, "MAX"),
+        }
+    }
+}
@@ -1,11 +1,18 @@
 use core::fmt;
-use std::collections::HashMap;
+
+use super::{Condition, IsolationLevel, Join, OrderBy};
+use crate::metadata::{Index, IndexType};
 
 pub enum Statement {
     Select {
         table_name: String,
         selection: Vec<String>,
-        where_clauses: HashMap<String, String>,
+        where_clauses: Vec<Condition>,
+        joins: Vec<Join>,
+        group_by: Vec<String>,
+        order_by: Vec<OrderBy>,
+        limit: Option<usize>,
+        offset: Option<usize>,
     },
     InsertInto {
         table_name: String,
@@ -18,18 +25,72 @@ pub enum Statement {
     },
     CreateIndex {
         table_name: String,
-        column_name: String,
-        index_name: String,
+        index: Index,
+    },
+    Delete {
+        table_name: String,
+        where_clauses: Vec<Condition>,
+    },
+    /// Sets the columns of the matching rows to the values, one column and value per assignment.
+    Update {
+        table_name: String,
+        assignments: Vec<(String, String)>,
+        where_clauses: Vec<Condition>,
+    },
+    Begin,
+    Commit,
+    Rollback,
+    Checkpoint,
+    Savepoint {
+        name: String,
+    },
+    RollbackToSavepoint {
+        name: String,
+    },
+    ReleaseSavepoint {
+        name: String,
+    },
+    SetIsolationLevel {
+        level: IsolationLevel,
+    },
+    IntegrityCheck {
+        table_name: Option<String>,
+    },
+    Vacuum {
+        table_name: Option<String>,
+    },
+    Analyze {
+        table_name: Option<String>,
+    },
+    /// Shows how the SELECT statement would be executed, running it to also show
+    /// what every operator actually did if it is analyzed.
+    Explain {
+        statement: Box<Statement>,
+        analyze: bool,
     },
 }
 
 impl Statement {
-    pub fn table_name(&self) -> &str {
+    pub fn table_name(&self) -> Option<&str> {
         match self {
             Self::Select { table_name, .. }
             | Self::InsertInto { table_name, .. }
             | Self::CreateTable { table_name, .. }
-            | Self::CreateIndex { table_name, .. } => table_name,
+            | Self::CreateIndex { table_name, .. }
+            | Self::Delete { table_name, .. }
+            | Self::Update { table_name, .. } => Some(table_name),
+            Self::IntegrityCheck { table_name }
+            | Self::Vacuum { table_name }
+            | Self::Analyze { table_name } => table_name.as_deref(),
+            Self::Explain { statement, .. } => statement.table_name(),
+            Self::Begin
+            | Self::Commit
+            | Self::Rollback
+            | Self::Checkpoint
+            | Self::Savepoint { .. }
+            | Self::RollbackToSavepoint { .. }
+            | Self::ReleaseSavepoint { .. }
+            | Self::SetIsolationLevel { .. } => None,
         }
     }
 }
@@ -41,7 +102,47 @@ impl fmt::Display for Statement {
                 table_name,
                 selection,
                 where_clauses,
-            } => write!(f, "SELECT {} FROM {};", selection.join(", "), table_name),
+                joins,
+                group_by,
+                order_by,
+                limit,
+                offset,
+            } => {
+                write!(f, "SELECT {} FROM {}", selection.join(", "), table_name)?;
+
+                for join in joins {
+                    write!(f, " {}", join)?;
+                }
+
+                if !where_clauses.is_empty() {
+                    let conditions: Vec<String> = where_clauses
+                        .iter()
+                        .map(|clause| clause.to_string())
+                        .collect();
+
+                    write!(f, " WHERE {}", conditions.join(" AND "))?;
+                }
+
+                if !group_by.is_empty() {
+                    write!(f, " GROUP BY {}", group_by.join(", "))?;
+                }
+
+                if !order_by.is_empty() {
+                    let columns: Vec<String> = order_by.iter().map(OrderBy::to_string).collect();
+
+                    write!(f, " ORDER BY {}", columns.join(", "))?;
+                }
+
+                if let Some(limit) = limit {
+                    write!(f, " LIMIT {}", limit)?;
+                }
+
+                if let Some(offset) = offset {
+                    write!(f, " OFFSET {}", offset)?;
+                }
+
+                write!(f, ";")
+            }
             Self::InsertInto {
                 table_name,
                 column_names: columns,
@@ -77,17 +178,106 @@ impl fmt::Display for Statement {
                     column_strings.join(",\n")
                 )
             }
-            Self::CreateIndex {
-                table_name,
-                column_name,
-                index_name,
-            } => {
+            Self::CreateIndex { table_name, index } => {
                 write!(
                     f,
-                    "CREATE INDEX {}\nON {}({});",
-                    index_name, table_name, column_name
-                )
+                    "CREATE {}INDEX {}\nON {}({})",
+                    if index.unique { "UNIQUE " } else { "" },
+                    index.name,
+                    table_name,
+                    index.column_list()
+                )?;
+
+                if !index.included_columns.is_empty() {
+                    write!(f, " INCLUDE ({})", index.included_columns.join(", "))?;
+                }
+
+                if index.index_type != IndexType::default() {
+                    write!(f, " USING {}", index.index_type)?;
+                }
+
+                if !index.where_clauses.is_empty() {
+                    let conditions: Vec<String> = index
+                        .where_clauses
+                        .iter()
+                        .map(|clause| clause.to_string())
+                        .collect();
+
+                    write!(f, " WHERE {}", conditions.join(" AND "))?;
+                }
+
+                write!(f, ";")
+            }
+            Self::Delete {
+                table_name,
+                where_clauses,
+            } => {
+                write!(f, "DELETE FROM {}", table_name)?;
+
+                if !where_clauses.is_empty() {
+                    let conditions: Vec<String> = where_clauses
+                        .iter()
+                        .map(|clause| clause.to_string())
+                        .collect();
+
+                    write!(f, " WHERE {}", conditions.join(" AND "))?;
+                }
+
+                write!(f, ";")
+            }
+            Self::Update {
+                table_name,
+                assignments,
+                where_clauses,
+            } => {
+                let assignments: Vec<String> = assignments
+                    .iter()
+                    .map(|(column_name, value)| format!("{} = {}", column_name, value))
+                    .collect();
+
+                write!(f, "UPDATE {} SET {}", table_name, assignments.join(", "))?;
+
+                if !where_clauses.is_empty() {
+                    let conditions: Vec<String> = where_clauses
+                        .iter()
+                        .map(|clause| clause.to_string())
+                        .collect();
+
+                    write!(f, " WHERE {}", conditions.join(" AND "))?;
+                }
+
+                write!(f, ";")
+            }
+            Self::Begin => write!(f, "BEGIN;"),
+            Self::Commit => write!(f, "COMMIT;"),
+            Self::Rollback => write!(f, "ROLLBACK;"),
+            Self::Checkpoint => write!(f, "CHECKPOINT;"),
+            Self::Savepoint { name } => write!(f, "SAVEPOINT {};", name),
+            Self::RollbackToSavepoint { name } => write!(f, "ROLLBACK TO SAVEPOINT {};", name),
+            Self::ReleaseSavepoint { name } => write!(f, "RELEASE SAVEPOINT {};", name),
+            Self::SetIsolationLevel { level } => {
+                write!(f, "SET TRANSACTION ISOLATION LEVEL {};", level)
             }
+            Self::IntegrityCheck { table_name: None } => write!(f, "PRAGMA integrity_check;"),
+            Self::IntegrityCheck {
+                table_name: Some(table_name),
+            } => write!(f, "PRAGMA integrity_check({});", table_name),
+            Self::Vacuum { table_name: None } => write!(f, "VACUUM;"),
+            Self::Vacuum {
+                table_name: Some(table_name),
+            } => write!(f, "VACUUM {};", table_name),
+            Self::Analyze { table_name: None } => write!(f, "ANALYZE;"),
+            Self::Analyze {
+                table_name: Some(table_name),
+            } => write!(f, "ANALYZE {};", table_name),
+            Self::Explain {
+                statement,
+                analyze: false,
+            } => write!(f, "EXPLAIN {}", statement),
+            Self::Explain {
+                statement,
+                analyze: true,
+            } => write!(f, "EXPLAIN ANALYZE {}", statement),
         }
     }
 }
@@ -5,6 +5,19 @@ pub enum StatementType {
     InsertInto,
     CreateTable,
     CreateIndex,
+    Update,
+    Begin,
+    Commit,
+    Rollback,
+    Checkpoint,
+    Savepoint,
+    Release,
+    SetTransaction,
+    Delete,
+    Pragma,
+    Vacuum,
+    Explain,
+    Analyze,
 }
 
 impl StatementType {
@@ -29,6 +42,19 @@ impl StatementType {
             StatementType::InsertInto => 2,
             StatementType::CreateTable => 3,
             StatementType::CreateIndex => 4,
+            StatementType::Update => 5,
+            StatementType::Begin => 6,
+            StatementType::Commit => 7,
+            StatementType::Rollback => 8,
+            StatementType::Checkpoint => 9,
+            StatementType::Savepoint => 10,
+            StatementType::Release => 11,
+            StatementType::SetTransaction => 12,
+            StatementType::Delete => 13,
+            StatementType::Pragma => 14,
+            StatementType::Vacuum => 15,
+            StatementType::Explain => 16,
+            StatementType::Analyze => 17,
         }
     }
 }
@@ -39,7 +65,20 @@ impl From<&str> for StatementType {
             "SELECT" => StatementType::Select,
             "INSERT INTO" => StatementType::InsertInto,
             "CREATE TABLE" => StatementType::CreateTable,
-            "CREATE INDEX" => StatementType::CreateIndex,
+            "CREATE INDEX" | "CREATE UNIQUE" => StatementType::CreateIndex,
+            "UPDATE" => StatementType::Update,
+            "BEGIN" => StatementType::Begin,
+            "COMMIT" => StatementType::Commit,
+            "ROLLBACK" => StatementType::Rollback,
+            "CHECKPOINT" => StatementType::Checkpoint,
+            "SAVEPOINT" => StatementType::Savepoint,
+            "RELEASE" => StatementType::Release,
+            "SET TRANSACTION" => StatementType::SetTransaction,
+            "DELETE FROM" => StatementType::Delete,
+            "PRAGMA" => StatementType::Pragma,
+            "VACUUM" => StatementType::Vacuum,
+            "EXPLAIN" => StatementType::Explain,
+            "ANALYZE" => StatementType::Analyze,
             _ => StatementType::Invalid,
         }
     }
@@ -77,5 +116,35 @@ mod tests {
     fn test_creates_create_index_command_correctly() {
         let query_type = StatementType::new("create", "index");
         assert_eq!(query_type.id(), StatementType::CreateIndex.id());
+
+        let query_type = StatementType::new("create", "unique");
+        assert_eq!(query_type.id(), StatementType::CreateIndex.id());
+    }
+
+    #[test]
+    fn test_creates_delete_command_correctly() {
+        let query_type = StatementType::new("delete", "from");
+        assert_eq!(query_type.id(), StatementType::Delete.id());
+    }
+
+    #[test]
+    fn test_creates_update_command_correctly() {
+        let query_type = StatementType::new("update", "users");
+        assert_eq!(query_type.id(), StatementType::Update.id());
+    }
+
+    #[test]
+    fn test_creates_transaction_commands_correctly() {
+        let query_type = StatementType::new("begin", ";");
+        assert_eq!(query_type.id(), StatementType::Begin.id());
+
+        let query_type = StatementType::new("COMMIT", ";");
+        assert_eq!(query_type.id(), StatementType::Commit.id());
+
+        let query_type = StatementType::new("Rollback", "work");
+        assert_eq!(query_type.id(), StatementType::Rollback.id());
+
+        let query_type = StatementType::new("set", "transaction");
+        assert_eq!(query_type.id(), StatementType::SetTransaction.id());
     }
 }
@@ -0,0 +1,37 @@
+use crate::sql_parser::query::Statement;
+
+use super::StatementParser;
+
+pub struct AnalyzeStatementParser();
+
+impl StatementParser for AnalyzeStatementParser {
+    fn parse_statement(&mut self, graphemes: Vec<String>) -> super::StatementResult {
+        match graphemes.as_slice() {
+            [_, semicolon] if semicolon == ";" => Ok(Statement::Analyze { table_name: None }),
+            [_, table_name, semicolon] if semicolon == ";" => Ok(Statement::Analyze {
+                table_name: Some(table_name.clone()),
+            }),
+            _ => Err(String::from("Invalid query.")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sql_parser::SqlParser;
+
+    #[test]
+    fn test_can_parse_an_analyze_statement() {
+        let input_parser = SqlParser();
+
+        let query = input_parser.parse_query(String::from("analyze;"));
+        assert_eq!(query.unwrap().statement.to_string(), "ANALYZE;");
+
+        let query = input_parser.parse_query(String::from("ANALYZE users;"));
+        assert_eq!(query.unwrap().statement.to_string(), "ANALYZE users;");
+
+        assert!(input_parser
+            .parse_query(String::from("ANALYZE users orders;"))
+            .is_err());
+    }
+}
@@ -0,0 +1,31 @@
+use crate::sql_parser::query::Statement;
+
+use super::StatementParser;
+
+pub struct CheckpointStatementParser();
+
+impl StatementParser for CheckpointStatementParser {
+    fn parse_statement(&mut self, graphemes: Vec<String>) -> super::StatementResult {
+        match graphemes.as_slice() {
+            [_, semicolon] if semicolon == ";" => Ok(Statement::Checkpoint),
+            _ => Err(String::from("Invalid query.")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sql_parser::SqlParser;
+
+    #[test]
+    fn test_can_parse_a_checkpoint_statement() {
+        let input_parser = SqlParser();
+
+        let query = input_parser.parse_query(String::from("checkpoint;"));
+        assert_eq!(query.unwrap().statement.to_string(), "CHECKPOINT;");
+
+        assert!(input_parser
+            .parse_query(String::from("CHECKPOINT users;"))
+            .is_err());
+    }
+}
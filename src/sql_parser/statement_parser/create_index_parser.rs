@@ -1,18 +1,25 @@
-use crate::sql_parser::query::Statement;
+use crate::metadata::{Index, IndexColumn, IndexType};
+use crate::sql_parser::query::{Function, Statement};
 
+use super::where_clause_parser::parse_where_clauses;
 use super::StatementParser;
 
-const CREATE_INDEX_GRAPHEMS: [&str; 2] = ["CREATE", "INDEX"];
+const CREATE_INDEX_GRAPHEMS: [&str; 3] = ["CREATE", "UNIQUE", "INDEX"];
 
 pub struct CreateIndexStatementParser {
     state: ParserState,
+    depth: usize,
 }
 
 impl StatementParser for CreateIndexStatementParser {
     fn parse_statement(&mut self, graphemes: Vec<String>) -> super::StatementResult {
         let mut table_name = String::new();
-        let mut column_name = String::new();
+        let mut column_graphemes: Vec<String> = Vec::new();
         let mut index_name = String::new();
+        let mut unique = false;
+        let mut index_type = IndexType::default();
+        let mut included_columns: Vec<String> = Vec::new();
+        let mut where_graphemes: Vec<String> = Vec::new();
 
         for grapheme in graphemes {
             let changed_parser_state = self.change_parser_state(&grapheme);
@@ -23,29 +30,51 @@ impl StatementParser for CreateIndexStatementParser {
 
             match self.state {
                 ParserState::Index => {
-                    if grapheme.to_uppercase() != CREATE_INDEX_GRAPHEMS[0]
-                        && grapheme.to_uppercase() != CREATE_INDEX_GRAPHEMS[1]
+                    if grapheme.to_uppercase() == CREATE_INDEX_GRAPHEMS[1] && index_name.is_empty()
                     {
+                        unique = true;
+                    } else if !CREATE_INDEX_GRAPHEMS.contains(&grapheme.to_uppercase().as_str()) {
                         index_name = grapheme.to_string();
                     }
                 }
                 ParserState::Table => {
                     table_name = grapheme.to_string();
-                    self.state = ParserState::Column
                 }
                 ParserState::Column => {
-                    if grapheme != "(" && grapheme != ")" && grapheme != ";" {
-                        column_name = grapheme.to_string();
+                    column_graphemes.push(grapheme);
+                }
+                ParserState::Using => {
+                    if grapheme.to_uppercase() == "USING" || grapheme == ";" {
+                        continue;
+                    }
+
+                    match IndexType::from_name(&grapheme) {
+                        Some(parsed_type) => index_type = parsed_type,
+                        None => return Err(format!("Unsupported index type '{}'.", grapheme)),
+                    }
+                }
+                ParserState::Include => {
+                    if grapheme != "(" && grapheme != "," {
+                        included_columns.push(grapheme);
+                    }
+                }
+                ParserState::WhereClauses => {
+                    if grapheme != ";" {
+                        where_graphemes.push(grapheme);
                     }
                 }
             }
         }
 
-        Ok(Statement::CreateIndex {
-            table_name,
-            column_name,
-            index_name,
-        })
+        let index = Index {
+            unique,
+            index_type,
+            where_clauses: parse_where_clauses(&where_graphemes)?,
+            included_columns,
+            ..Index::new(index_name, parse_index_columns(&column_graphemes)?)
+        };
+
+        Ok(Statement::CreateIndex { table_name, index })
     }
 }
 
@@ -53,6 +82,7 @@ impl CreateIndexStatementParser {
     pub fn new() -> Self {
         Self {
             state: ParserState::Index,
+            depth: 0,
         }
     }
 
@@ -74,15 +104,69 @@ impl CreateIndexStatementParser {
                     false
                 }
             }
-            ParserState::Column => false,
+            ParserState::Column => match grapheme {
+                "(" => {
+                    self.depth += 1;
+                    false
+                }
+                ")" if self.depth == 0 => {
+                    self.state = ParserState::Using;
+                    true
+                }
+                ")" => {
+                    self.depth -= 1;
+                    false
+                }
+                _ => false,
+            },
+            ParserState::Using => match grapheme.to_uppercase().as_str() {
+                "INCLUDE" => {
+                    self.state = ParserState::Include;
+                    true
+                }
+                "WHERE" => {
+                    self.state = ParserState::WhereClauses;
+                    true
+                }
+                _ => false,
+            },
+            ParserState::Include => {
+                if grapheme == ")" {
+                    self.state = ParserState::Using;
+                    true
+                } else {
+                    false
+                }
+            }
+            ParserState::WhereClauses => false,
         }
     }
 }
 
+/// Parses the comma separated key columns, each either a column name or a function of one like `LOWER(email)`.
+fn parse_index_columns(graphemes: &[String]) -> Result<Vec<IndexColumn>, String> {
+    graphemes
+        .split(|grapheme| grapheme == ",")
+        .map(|column| match column {
+            [name] => Ok(IndexColumn::new(name)),
+            [function, open, name, close] if open == "(" && close == ")" => {
+                match Function::from_name(function) {
+                    Some(function) => Ok(IndexColumn::with_function(function, name)),
+                    None => Err(format!("Unsupported function '{}'.", function)),
+                }
+            }
+            _ => Err(format!("Invalid index column '{}'.", column.join(""))),
+        })
+        .collect()
+}
+
 enum ParserState {
     Index,
     Table,
     Column,
+    Using,
+    Include,
+    WhereClauses,
 }
 
 #[cfg(test)]
@@ -100,4 +184,86 @@ mod tests {
             String::from("CREATE INDEX my_index\nON users(email);")
         );
     }
+
+    #[test]
+    fn test_can_parse_a_create_index_statement_over_multiple_columns() {
+        let input_parser = SqlParser();
+        let query = input_parser.parse_query(String::from(
+            "CREATE INDEX name_index ON users (last_name,first_name, age);",
+        ));
+
+        assert_eq!(
+            query.unwrap().statement.to_string(),
+            String::from("CREATE INDEX name_index\nON users(last_name, first_name, age);")
+        );
+    }
+
+    #[test]
+    fn test_can_parse_a_create_unique_index_statement() {
+        let input_parser = SqlParser();
+        let query = input_parser.parse_query(String::from(
+            "CREATE UNIQUE INDEX email_index ON users(email);",
+        ));
+
+        assert_eq!(
+            query.unwrap().statement.to_string(),
+            String::from("CREATE UNIQUE INDEX email_index\nON users(email);")
+        );
+    }
+
+    #[test]
+    fn test_can_parse_a_create_index_statement_using_hash() {
+        let input_parser = SqlParser();
+        let query = input_parser.parse_query(String::from(
+            "CREATE INDEX email_index ON users(email) USING HASH;",
+        ));
+
+        assert_eq!(
+            query.unwrap().statement.to_string(),
+            String::from("CREATE INDEX email_index\nON users(email) USING HASH;")
+        );
+
+        let query = input_parser.parse_query(String::from(
+            "CREATE INDEX email_index ON users(email) USING bitmap;",
+        ));
+
+        assert!(query.is_err());
+    }
+
+    #[test]
+    fn test_can_parse_partial_and_expression_indexes() {
+        let input_parser = SqlParser();
+        let query = input_parser.parse_query(String::from(
+            "CREATE INDEX open_orders ON orders (customer_id) WHERE status = 'open';",
+        ));
+
+        assert_eq!(
+            query.unwrap().statement.to_string(),
+            String::from("CREATE INDEX open_orders\nON orders(customer_id) WHERE status = 'open';")
+        );
+
+        let query = input_parser.parse_query(String::from(
+            "CREATE UNIQUE INDEX email_index ON users (LOWER(email)) USING HASH;",
+        ));
+
+        assert_eq!(
+            query.unwrap().statement.to_string(),
+            String::from("CREATE UNIQUE INDEX email_index\nON users(LOWER(email)) USING HASH;")
+        );
+    }
+
+    #[test]
+    fn test_can_parse_a_create_index_statement_with_included_columns() {
+        let input_parser = SqlParser();
+        let query = input_parser.parse_query(String::from(
+            "CREATE INDEX email_index ON users (email) INCLUDE (name, age) WHERE age > 18;",
+        ));
+
+        assert_eq!(
+            query.unwrap().statement.to_string(),
+            String::from(
+                "CREATE INDEX email_index\nON users(email) INCLUDE (name, age) WHERE age > 18;"
+            )
+        );
+    }
 }
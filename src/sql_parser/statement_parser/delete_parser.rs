@@ -0,0 +1,58 @@
+use crate::sql_parser::query::Statement;
+
+use super::where_clause_parser::parse_where_clauses;
+use super::StatementParser;
+
+const WHERE_GRAPHEME: &str = "WHERE";
+
+pub struct DeleteStatementParser();
+
+impl StatementParser for DeleteStatementParser {
+    fn parse_statement(&mut self, graphemes: Vec<String>) -> super::StatementResult {
+        // `DELETE FROM` comes first and the semicolon last
+        match &graphemes[2..graphemes.len() - 1] {
+            [table_name] => Ok(Statement::Delete {
+                table_name: table_name.clone(),
+                where_clauses: vec![],
+            }),
+            [table_name, where_grapheme, where_graphemes @ ..]
+                if where_grapheme.to_uppercase() == WHERE_GRAPHEME =>
+            {
+                Ok(Statement::Delete {
+                    table_name: table_name.clone(),
+                    where_clauses: parse_where_clauses(where_graphemes)?,
+                })
+            }
+            _ => Err(String::from("Invalid query.")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sql_parser::SqlParser;
+
+    #[test]
+    fn test_can_parse_a_delete_statement() {
+        let input_parser = SqlParser();
+
+        for (input, output) in [
+            ("DELETE FROM users;", "DELETE FROM users;"),
+            (
+                "delete from users where id >= 5 AND name = 'rust';",
+                "DELETE FROM users WHERE id >= 5 AND name = 'rust';",
+            ),
+        ] {
+            let query = input_parser.parse_query(String::from(input));
+
+            assert_eq!(query.unwrap().statement.to_string(), output);
+        }
+
+        assert!(input_parser
+            .parse_query(String::from("DELETE FROM;"))
+            .is_err());
+        assert!(input_parser
+            .parse_query(String::from("DELETE FROM users id = 5;"))
+            .is_err());
+    }
+}
@@ -0,0 +1,59 @@
+use crate::sql_parser::query::{Statement, StatementType};
+
+use super::select_parser::SelectStatementParser;
+use super::StatementParser;
+
+pub struct ExplainStatementParser();
+
+impl StatementParser for ExplainStatementParser {
+    fn parse_statement(&mut self, graphemes: Vec<String>) -> super::StatementResult {
+        let analyze = graphemes
+            .get(1)
+            .is_some_and(|grapheme| grapheme.eq_ignore_ascii_case("ANALYZE"));
+        let graphemes = graphemes[if analyze { 2 } else { 1 }..].to_vec();
+
+        match graphemes
+            .first()
+            .map(|grapheme| StatementType::from(grapheme.as_str()))
+        {
+            Some(StatementType::Select) => Ok(Statement::Explain {
+                statement: Box::new(SelectStatementParser::new().parse_statement(graphemes)?),
+                analyze,
+            }),
+            _ => Err(String::from(
+                "EXPLAIN [ANALYZE] needs to be followed by a SELECT statement.",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sql_parser::SqlParser;
+
+    #[test]
+    fn test_can_parse_an_explain_statement() {
+        let input_parser = SqlParser();
+
+        let query =
+            input_parser.parse_query(String::from("explain SELECT name FROM users WHERE id = 1;"));
+        assert_eq!(
+            query.unwrap().statement.to_string(),
+            "EXPLAIN SELECT name FROM users WHERE id = 1;"
+        );
+
+        let query = input_parser.parse_query(String::from("EXPLAIN analyze SELECT * FROM users;"));
+        assert_eq!(
+            query.unwrap().statement.to_string(),
+            "EXPLAIN ANALYZE SELECT * FROM users;"
+        );
+
+        assert!(input_parser
+            .parse_query(String::from("EXPLAIN ANALYZE users;"))
+            .is_err());
+        assert!(input_parser
+            .parse_query(String::from("EXPLAIN DELETE FROM users;"))
+            .is_err());
+        assert!(input_parser.parse_query(String::from("EXPLAIN;")).is_err());
+    }
+}
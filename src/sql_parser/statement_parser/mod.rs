@@ -1,12 +1,29 @@
+mod analyze_parser;
+mod checkpoint_parser;
+mod create_index_parser;
 mod create_table_parser;
+mod delete_parser;
+mod explain_parser;
 mod insert_into_parser;
+mod pragma_parser;
 mod select_parser;
-mod create_index_parser;
+mod transaction_parser;
+mod update_parser;
+mod vacuum_parser;
+mod where_clause_parser;
 
-use crate::sql_parser::statement_parser::create_table_parser::CreateTableStatementParser;
+use crate::sql_parser::statement_parser::analyze_parser::AnalyzeStatementParser;
+use crate::sql_parser::statement_parser::checkpoint_parser::CheckpointStatementParser;
 use crate::sql_parser::statement_parser::create_index_parser::CreateIndexStatementParser;
+use crate::sql_parser::statement_parser::create_table_parser::CreateTableStatementParser;
+use crate::sql_parser::statement_parser::delete_parser::DeleteStatementParser;
+use crate::sql_parser::statement_parser::explain_parser::ExplainStatementParser;
 use crate::sql_parser::statement_parser::insert_into_parser::InsertIntoParser;
+use crate::sql_parser::statement_parser::pragma_parser::PragmaStatementParser;
 use crate::sql_parser::statement_parser::select_parser::SelectStatementParser;
+use crate::sql_parser::statement_parser::transaction_parser::TransactionStatementParser;
+use crate::sql_parser::statement_parser::update_parser::UpdateStatementParser;
+use crate::sql_parser::statement_parser::vacuum_parser::VacuumStatementParser;
 
 use std::result;
 
@@ -22,6 +39,19 @@ pub fn statement_parser_factory(statement_type: StatementType) -> Box<dyn Statem
         StatementType::CreateIndex => Box::new(CreateIndexStatementParser::new()),
         StatementType::Select => Box::new(SelectStatementParser::new()),
         StatementType::InsertInto => Box::new(InsertIntoParser::new()),
+        StatementType::Update => Box::new(UpdateStatementParser()),
+        StatementType::Begin
+        | StatementType::Commit
+        | StatementType::Rollback
+        | StatementType::Savepoint
+        | StatementType::Release
+        | StatementType::SetTransaction => Box::new(TransactionStatementParser::new(statement_type)),
+        StatementType::Checkpoint => Box::new(CheckpointStatementParser()),
+        StatementType::Delete => Box::new(DeleteStatementParser()),
+        StatementType::Pragma => Box::new(PragmaStatementParser()),
+        StatementType::Vacuum => Box::new(VacuumStatementParser()),
+        StatementType::Explain => Box::new(ExplainStatementParser()),
+        StatementType::Analyze => Box::new(AnalyzeStatementParser()),
         _ => panic!("Not implemented yet."),
     }
 }
@@ -0,0 +1,54 @@
+use crate::sql_parser::query::Statement;
+
+use super::StatementParser;
+
+const INTEGRITY_CHECK: &str = "INTEGRITY_CHECK";
+
+pub struct PragmaStatementParser();
+
+impl StatementParser for PragmaStatementParser {
+    fn parse_statement(&mut self, graphemes: Vec<String>) -> super::StatementResult {
+        let graphemes: Vec<&str> = graphemes.iter().map(String::as_str).collect();
+
+        match graphemes.as_slice() {
+            [_, pragma, ";"] if pragma.eq_ignore_ascii_case(INTEGRITY_CHECK) => {
+                Ok(Statement::IntegrityCheck { table_name: None })
+            }
+            [_, pragma, "(", table_name, ")", ";"]
+                if pragma.eq_ignore_ascii_case(INTEGRITY_CHECK) =>
+            {
+                Ok(Statement::IntegrityCheck {
+                    table_name: Some(table_name.to_string()),
+                })
+            }
+            [_, pragma, ..] => Err(format!("Unknown pragma '{}'.", pragma)),
+            _ => Err(String::from("Invalid query.")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sql_parser::SqlParser;
+
+    #[test]
+    fn test_can_parse_an_integrity_check() {
+        let input_parser = SqlParser();
+
+        let query = input_parser.parse_query(String::from("pragma integrity_check;"));
+        assert_eq!(
+            query.unwrap().statement.to_string(),
+            "PRAGMA integrity_check;"
+        );
+
+        let query = input_parser.parse_query(String::from("PRAGMA integrity_check(users);"));
+        assert_eq!(
+            query.unwrap().statement.to_string(),
+            "PRAGMA integrity_check(users);"
+        );
+
+        assert!(input_parser
+            .parse_query(String::from("PRAGMA journal_mode;"))
+            .is_err());
+    }
+}
@@ -1,13 +1,13 @@
-use std::collections::HashMap;
-
-use crate::sql_parser::query::Statement;
+use crate::sql_parser::query::{Join, OrderBy, Statement};
 
+use super::where_clause_parser::parse_where_clauses;
 use super::StatementParser;
 
 const SELECT_GRAPHEME: &str = "SELECT";
-const FROM_GRAPHEME: &str = "FROM";
-const WHERE_GRAPHEME: &str = "WHERE";
-const AND_GRAPHEME: &str = "AND";
+const INNER_GRAPHEME: &str = "INNER";
+const BY_GRAPHEME: &str = "BY";
+const DESC_GRAPHEME: &str = "DESC";
+const ASC_GRAPHEME: &str = "ASC";
 
 pub struct SelectStatementParser {
     state: ParserState,
@@ -17,44 +17,64 @@ impl StatementParser for SelectStatementParser {
     fn parse_statement(&mut self, graphemes: Vec<String>) -> super::StatementResult {
         let mut selection: Vec<String> = Vec::new();
         let mut table_name = String::new();
-        let mut last_where_attribute = String::new();
-        let mut where_clauses: HashMap<String, String> = HashMap::new();
+        let mut join_graphemes: Vec<Vec<String>> = Vec::new();
+        let mut where_graphemes: Vec<String> = Vec::new();
+        let mut group_by_graphemes: Vec<String> = Vec::new();
+        let mut order_by_graphemes: Vec<String> = Vec::new();
+        let mut limit_graphemes: Vec<String> = Vec::new();
+        let mut offset_graphemes: Vec<String> = Vec::new();
 
         for grapheme in graphemes {
             let changed_parser_state = self.change_parser_state(&grapheme);
 
             if changed_parser_state {
+                if let ParserState::Join = self.state {
+                    join_graphemes.push(vec![]);
+                }
+
+                continue;
+            }
+
+            if grapheme == ";" {
                 continue;
             }
 
             match self.state {
                 ParserState::Selection => {
                     if grapheme.to_uppercase() != SELECT_GRAPHEME && grapheme != "," {
-                        selection.push(grapheme);
+                        push_selected_item(&mut selection, grapheme);
                     }
                 }
                 ParserState::TableName => {
-                    if grapheme != ";" {
+                    if grapheme.to_uppercase() != INNER_GRAPHEME {
                         table_name = grapheme;
                     }
                 }
-                ParserState::WhereClauses => {
-                    if grapheme != "=" && grapheme != AND_GRAPHEME {
-                        if last_where_attribute.is_empty() {
-                            last_where_attribute = grapheme;
-                        } else {
-                            where_clauses.insert(last_where_attribute.to_string(), grapheme);
-                            last_where_attribute = String::new();
-                        }
+                ParserState::Join => {
+                    if grapheme.to_uppercase() != INNER_GRAPHEME {
+                        join_graphemes.last_mut().unwrap().push(grapheme);
                     }
                 }
+                ParserState::WhereClauses => where_graphemes.push(grapheme),
+                ParserState::GroupBy => group_by_graphemes.push(grapheme),
+                ParserState::OrderBy => order_by_graphemes.push(grapheme),
+                ParserState::Limit => limit_graphemes.push(grapheme),
+                ParserState::Offset => offset_graphemes.push(grapheme),
             }
         }
 
         Ok(Statement::Select {
             selection,
             table_name,
-            where_clauses,
+            where_clauses: parse_where_clauses(&where_graphemes)?,
+            joins: join_graphemes
+                .iter()
+                .map(|graphemes| parse_join(graphemes))
+                .collect::<Result<_, _>>()?,
+            group_by: parse_group_by(&group_by_graphemes)?,
+            order_by: parse_order_by(&order_by_graphemes)?,
+            limit: parse_count(&limit_graphemes, "LIMIT")?,
+            offset: parse_count(&offset_graphemes, "OFFSET")?,
         })
     }
 }
@@ -66,38 +86,164 @@ impl SelectStatementParser {
         }
     }
 
+    /// Moves on to the clause a keyword starts, as long as clauses come in the order of the grammar.
     fn change_parser_state(&mut self, grapheme: &str) -> bool {
-        match self.state {
-            ParserState::Selection => {
-                if grapheme.to_uppercase() == FROM_GRAPHEME {
-                    self.state = ParserState::TableName;
-                    true
-                } else {
-                    false
-                }
-            }
-            ParserState::TableName => {
-                if grapheme.to_uppercase() == WHERE_GRAPHEME {
-                    self.state = ParserState::WhereClauses;
-                    true
-                } else {
-                    false
-                }
+        let next_state = match grapheme.to_uppercase().as_str() {
+            "FROM" => ParserState::TableName,
+            "JOIN" => ParserState::Join,
+            "WHERE" => ParserState::WhereClauses,
+            "GROUP" => ParserState::GroupBy,
+            "ORDER" => ParserState::OrderBy,
+            "LIMIT" => ParserState::Limit,
+            "OFFSET" => ParserState::Offset,
+            _ => return false,
+        };
+
+        let follows = match next_state {
+            ParserState::TableName => matches!(self.state, ParserState::Selection),
+            ParserState::Join => matches!(self.state, ParserState::TableName | ParserState::Join),
+            _ => {
+                self.state.position() >= ParserState::TableName.position()
+                    && next_state.position() > self.state.position()
             }
-            ParserState::WhereClauses => false,
+        };
+
+        if follows {
+            self.state = next_state;
         }
+
+        follows
     }
 }
 
 enum ParserState {
-    TableName,
     Selection,
+    TableName,
+    Join,
     WhereClauses,
+    GroupBy,
+    OrderBy,
+    Limit,
+    Offset,
+}
+
+impl ParserState {
+    fn position(&self) -> u8 {
+        match self {
+            ParserState::Selection => 0,
+            ParserState::TableName => 1,
+            ParserState::Join => 2,
+            ParserState::WhereClauses => 3,
+            ParserState::GroupBy => 4,
+            ParserState::OrderBy => 5,
+            ParserState::Limit => 6,
+            ParserState::Offset => 7,
+        }
+    }
+}
+
+/// Adds a grapheme to the selection. Parentheses were split off by the tokenizer,
+/// so they and everything between them are glued back onto the function before them.
+fn push_selected_item(selection: &mut Vec<String>, grapheme: String) {
+    match selection.last_mut() {
+        Some(function) if grapheme == "(" => {
+            *function = function.to_uppercase();
+            function.push_str(&grapheme);
+        }
+        Some(item) if item.matches('(').count() > item.matches(')').count() => {
+            item.push_str(&grapheme)
+        }
+        _ => selection.push(grapheme),
+    }
+}
+
+fn parse_join(graphemes: &[String]) -> Result<Join, String> {
+    match graphemes {
+        [table_name, on, left_column, equals, right_column]
+            if on.eq_ignore_ascii_case("ON") && equals == "=" =>
+        {
+            Ok(Join {
+                table_name: table_name.clone(),
+                left_column: left_column.clone(),
+                right_column: right_column.clone(),
+            })
+        }
+        _ => Err(String::from(
+            "A join needs the form 'JOIN table ON column = column'.",
+        )),
+    }
+}
+
+fn parse_group_by(graphemes: &[String]) -> Result<Vec<String>, String> {
+    let columns = match graphemes.split_first() {
+        Some((by, columns)) if by.to_uppercase() == BY_GRAPHEME => columns,
+        _ if graphemes.is_empty() => return Ok(vec![]),
+        _ => return Err(String::from("GROUP needs to be followed by BY.")),
+    };
+
+    columns
+        .split(|grapheme| grapheme == ",")
+        .map(|column| match column {
+            [column_name] => Ok(column_name.clone()),
+            _ => Err(String::from("Invalid GROUP BY clause.")),
+        })
+        .collect()
+}
+
+fn parse_order_by(graphemes: &[String]) -> Result<Vec<OrderBy>, String> {
+    let columns = match graphemes.split_first() {
+        Some((by, columns)) if by.to_uppercase() == BY_GRAPHEME => columns,
+        _ if graphemes.is_empty() => return Ok(vec![]),
+        _ => return Err(String::from("ORDER needs to be followed by BY.")),
+    };
+
+    columns
+        .split(|grapheme| grapheme == ",")
+        .map(|column| {
+            let (column, descending) = match column.split_last() {
+                Some((direction, rest)) if direction.to_uppercase() == ASC_GRAPHEME => {
+                    (rest, false)
+                }
+                Some((direction, rest)) if direction.to_uppercase() == DESC_GRAPHEME => {
+                    (rest, true)
+                }
+                _ => (column, false),
+            };
+
+            // aggregates like COUNT(*) can be ordered by as well
+            let mut items = vec![];
+            for grapheme in column {
+                push_selected_item(&mut items, grapheme.clone());
+            }
+
+            match items.as_slice() {
+                [column_name] => Ok(OrderBy {
+                    column_name: column_name.clone(),
+                    descending,
+                }),
+                _ => Err(String::from("Invalid ORDER BY clause.")),
+            }
+        })
+        .collect()
+}
+
+fn parse_count(graphemes: &[String], clause: &str) -> Result<Option<usize>, String> {
+    match graphemes {
+        [] => Ok(None),
+        [count] => count
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("{} needs a non-negative number.", clause)),
+        _ => Err(format!("{} needs a single number.", clause)),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::sql_parser::{query::Statement, SqlParser};
+    use crate::sql_parser::{
+        query::{Condition, Function, Predicate, Statement},
+        SqlParser,
+    };
 
     #[test]
     fn test_can_create_a_parsed_input_from_a_simple_select_query() {
@@ -134,7 +280,10 @@ mod tests {
                 selection,
                 ..
             } => {
-                assert_eq!(where_clauses.get("id").unwrap(), "5");
+                assert_eq!(
+                    where_clauses,
+                    vec![Condition::new("id", Predicate::Equal(String::from("5")))]
+                );
                 assert_eq!(selection.len(), 1);
                 assert_eq!(selection.first().unwrap(), "*");
             }
@@ -151,10 +300,112 @@ mod tests {
 
         match query.unwrap().statement {
             Statement::Select { where_clauses, .. } => {
-                assert_eq!(where_clauses.get("ranking_number").unwrap(), "1");
-                assert_eq!(where_clauses.get("name").unwrap(), "'rust'");
+                assert_eq!(
+                    where_clauses,
+                    vec![
+                        Condition::new("ranking_number", Predicate::Equal(String::from("1"))),
+                        Condition::new("name", Predicate::Equal(String::from("'rust'"))),
+                    ]
+                );
             }
             _ => panic!(),
         }
     }
+
+    #[test]
+    fn test_can_parse_comparisons_between_and_like_in_where_clauses() {
+        let input_parser = SqlParser();
+        let query = input_parser.parse_query(String::from(
+            "SELECT * FROM users WHERE age>=18 AND id BETWEEN 1 AND 10 AND name LIKE 'jo%';",
+        ));
+
+        match query.unwrap().statement {
+            Statement::Select { where_clauses, .. } => assert_eq!(
+                where_clauses,
+                vec![
+                    Condition::new("age", Predicate::GreaterThanOrEqual(String::from("18"))),
+                    Condition::new(
+                        "id",
+                        Predicate::Between(String::from("1"), String::from("10"))
+                    ),
+                    Condition::new("name", Predicate::Like(String::from("'jo%'"))),
+                ]
+            ),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_rejects_incomplete_where_clauses() {
+        let input_parser = SqlParser();
+
+        assert!(input_parser
+            .parse_query(String::from("SELECT * FROM users WHERE id >;"))
+            .is_err());
+        assert!(input_parser
+            .parse_query(String::from("SELECT * FROM users WHERE id BETWEEN 1;"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_can_parse_functions_in_where_clauses() {
+        let input_parser = SqlParser();
+        let query = input_parser.parse_query(String::from(
+            "SELECT * FROM users WHERE LOWER(email) = 'john@mail.com';",
+        ));
+
+        match query.unwrap().statement {
+            Statement::Select { where_clauses, .. } => assert_eq!(
+                where_clauses,
+                vec![Condition::with_function(
+                    Function::Lower,
+                    "email",
+                    Predicate::Equal(String::from("'john@mail.com'"))
+                )]
+            ),
+            _ => panic!(),
+        }
+
+        assert!(input_parser
+            .parse_query(String::from("SELECT * FROM users WHERE TRIM(email) = 'a';"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_can_parse_joins_grouping_ordering_and_limits() {
+        let input_parser = SqlParser();
+
+        for (input, parsed) in [
+            (
+                "SELECT users.name, orders.total FROM users INNER JOIN orders ON users.id = orders.user_id;",
+                "SELECT users.name, orders.total FROM users JOIN orders ON users.id = orders.user_id;",
+            ),
+            (
+                "SELECT city, count(*), MAX(age) FROM users GROUP BY city ORDER BY count(*) DESC;",
+                "SELECT city, COUNT(*), MAX(age) FROM users GROUP BY city ORDER BY COUNT(*) DESC;",
+            ),
+            (
+                "SELECT * FROM users WHERE age > 20 ORDER BY age DESC, name LIMIT 5 OFFSET 10;",
+                "SELECT * FROM users WHERE age > 20 ORDER BY age DESC, name LIMIT 5 OFFSET 10;",
+            ),
+        ] {
+            assert_eq!(
+                input_parser
+                    .parse_query(String::from(input))
+                    .unwrap()
+                    .statement
+                    .to_string(),
+                parsed
+            );
+        }
+
+        for input in [
+            "SELECT * FROM users ORDER age;",
+            "SELECT * FROM users LIMIT many;",
+            "SELECT * FROM users JOIN orders users.id = orders.user_id;",
+            "SELECT * FROM users LIMIT 5 WHERE id = 1;",
+        ] {
+            assert!(input_parser.parse_query(String::from(input)).is_err());
+        }
+    }
 }
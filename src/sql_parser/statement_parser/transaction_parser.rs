@@ -0,0 +1,125 @@
+use crate::sql_parser::query::{IsolationLevel, Statement, StatementType};
+
+use super::StatementParser;
+
+// `BEGIN TRANSACTION;` and `COMMIT WORK;` mean the same as `BEGIN;` and `COMMIT;`
+const OPTIONAL_GRAPHEMES: [&str; 2] = ["TRANSACTION", "WORK"];
+const TO_GRAPHEME: &str = "TO";
+const SAVEPOINT_GRAPHEME: &str = "SAVEPOINT";
+const ISOLATION_GRAPHEME: &str = "ISOLATION";
+const LEVEL_GRAPHEME: &str = "LEVEL";
+
+pub struct TransactionStatementParser {
+    statement_type: StatementType,
+}
+
+impl StatementParser for TransactionStatementParser {
+    fn parse_statement(&mut self, graphemes: Vec<String>) -> super::StatementResult {
+        // the first grapheme names the statement and the last one is the semicolon
+        let arguments: Vec<String> = graphemes[1..graphemes.len() - 1]
+            .iter()
+            .filter(|grapheme| {
+                !OPTIONAL_GRAPHEMES.contains(&grapheme.to_uppercase().as_str())
+                    && grapheme.to_uppercase() != SAVEPOINT_GRAPHEME
+            })
+            .cloned()
+            .collect();
+
+        match (&self.statement_type, arguments.as_slice()) {
+            (StatementType::Begin, []) => Ok(Statement::Begin),
+            (StatementType::Commit, []) => Ok(Statement::Commit),
+            (StatementType::Rollback, []) => Ok(Statement::Rollback),
+            (StatementType::Rollback, [to, name]) if to.to_uppercase() == TO_GRAPHEME => {
+                Ok(Statement::RollbackToSavepoint { name: name.clone() })
+            }
+            (StatementType::Savepoint, [name]) => Ok(Statement::Savepoint { name: name.clone() }),
+            (StatementType::Release, [name]) => {
+                Ok(Statement::ReleaseSavepoint { name: name.clone() })
+            }
+            (StatementType::SetTransaction, [isolation, level, name @ ..])
+                if isolation.to_uppercase() == ISOLATION_GRAPHEME
+                    && level.to_uppercase() == LEVEL_GRAPHEME =>
+            {
+                match IsolationLevel::from_graphemes(name) {
+                    Some(level) => Ok(Statement::SetIsolationLevel { level }),
+                    None => Err(format!(
+                        "Unsupported isolation level '{}'. Please use 'READ COMMITTED', 'REPEATABLE READ' or 'SNAPSHOT'.",
+                        name.join(" ")
+                    )),
+                }
+            }
+            _ => Err(String::from("Invalid query.")),
+        }
+    }
+}
+
+impl TransactionStatementParser {
+    pub fn new(statement_type: StatementType) -> Self {
+        Self { statement_type }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sql_parser::SqlParser;
+
+    #[test]
+    fn test_can_parse_transaction_statements() {
+        let input_parser = SqlParser();
+
+        for (input, output) in [
+            ("BEGIN;", "BEGIN;"),
+            ("begin transaction;", "BEGIN;"),
+            ("COMMIT;", "COMMIT;"),
+            ("COMMIT WORK;", "COMMIT;"),
+            ("ROLLBACK;", "ROLLBACK;"),
+            ("SAVEPOINT batch;", "SAVEPOINT batch;"),
+            (
+                "rollback to savepoint batch;",
+                "ROLLBACK TO SAVEPOINT batch;",
+            ),
+            ("ROLLBACK TO batch;", "ROLLBACK TO SAVEPOINT batch;"),
+            ("RELEASE SAVEPOINT batch;", "RELEASE SAVEPOINT batch;"),
+            ("RELEASE batch;", "RELEASE SAVEPOINT batch;"),
+            (
+                "SET TRANSACTION ISOLATION LEVEL READ COMMITTED;",
+                "SET TRANSACTION ISOLATION LEVEL READ COMMITTED;",
+            ),
+            (
+                "set transaction isolation level repeatable read;",
+                "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ;",
+            ),
+            (
+                "SET TRANSACTION ISOLATION LEVEL SNAPSHOT;",
+                "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ;",
+            ),
+        ] {
+            let query = input_parser.parse_query(String::from(input));
+
+            assert_eq!(query.unwrap().statement.to_string(), output);
+        }
+    }
+
+    #[test]
+    fn test_rejects_transaction_statements_with_extra_graphemes() {
+        let input_parser = SqlParser();
+
+        assert!(input_parser
+            .parse_query(String::from("BEGIN users;"))
+            .is_err());
+        assert!(input_parser
+            .parse_query(String::from("COMMIT TRANSACTION now;"))
+            .is_err());
+        assert!(input_parser
+            .parse_query(String::from("SAVEPOINT;"))
+            .is_err());
+        assert!(input_parser
+            .parse_query(String::from("ROLLBACK batch;"))
+            .is_err());
+        assert!(input_parser
+            .parse_query(String::from(
+                "SET TRANSACTION ISOLATION LEVEL SERIALIZABLE;"
+            ))
+            .is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use crate::sql_parser::query::Statement;
+
+use super::where_clause_parser::parse_where_clauses;
+use super::StatementParser;
+
+const SET_GRAPHEME: &str = "SET";
+const WHERE_GRAPHEME: &str = "WHERE";
+
+pub struct UpdateStatementParser();
+
+impl StatementParser for UpdateStatementParser {
+    fn parse_statement(&mut self, graphemes: Vec<String>) -> super::StatementResult {
+        // `UPDATE` comes first and the semicolon last
+        let [table_name, set_grapheme, rest @ ..] = &graphemes[1..graphemes.len() - 1] else {
+            return Err(String::from("Invalid query."));
+        };
+
+        if set_grapheme.to_uppercase() != SET_GRAPHEME {
+            return Err(String::from("Invalid query."));
+        }
+
+        let (assignment_graphemes, where_clauses) = match rest
+            .iter()
+            .position(|grapheme| grapheme.to_uppercase() == WHERE_GRAPHEME)
+        {
+            Some(position) if position + 1 < rest.len() => (
+                &rest[..position],
+                parse_where_clauses(&rest[position + 1..])?,
+            ),
+            _ => (rest, vec![]),
+        };
+
+        let assignments = assignment_graphemes
+            .split(|grapheme| grapheme == ",")
+            .map(|assignment| match assignment {
+                [column_name, equals, value] if equals == "=" => {
+                    Ok((column_name.clone(), value.clone()))
+                }
+                _ => Err(String::from("Invalid assignment in SET clause.")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Statement::Update {
+            table_name: table_name.clone(),
+            assignments,
+            where_clauses,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sql_parser::SqlParser;
+
+    #[test]
+    fn test_can_parse_an_update_statement() {
+        let input_parser = SqlParser();
+
+        for (input, output) in [
+            (
+                "UPDATE users SET name = 'rust';",
+                "UPDATE users SET name = 'rust';",
+            ),
+            (
+                "update users set name='rust', age = 5 where id >= 5 AND name = 'c';",
+                "UPDATE users SET name = 'rust', age = 5 WHERE id >= 5 AND name = 'c';",
+            ),
+        ] {
+            let query = input_parser.parse_query(String::from(input));
+
+            assert_eq!(query.unwrap().statement.to_string(), output);
+        }
+
+        for input in [
+            "UPDATE users;",
+            "UPDATE users SET;",
+            "UPDATE users SET name 'rust';",
+            "UPDATE users name = 'rust';",
+            "UPDATE users SET name = 'rust' WHERE;",
+        ] {
+            assert!(input_parser.parse_query(String::from(input)).is_err());
+        }
+    }
+}
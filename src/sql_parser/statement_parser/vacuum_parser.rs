@@ -0,0 +1,37 @@
+use crate::sql_parser::query::Statement;
+
+use super::StatementParser;
+
+pub struct VacuumStatementParser();
+
+impl StatementParser for VacuumStatementParser {
+    fn parse_statement(&mut self, graphemes: Vec<String>) -> super::StatementResult {
+        match graphemes.as_slice() {
+            [_, semicolon] if semicolon == ";" => Ok(Statement::Vacuum { table_name: None }),
+            [_, table_name, semicolon] if semicolon == ";" => Ok(Statement::Vacuum {
+                table_name: Some(table_name.clone()),
+            }),
+            _ => Err(String::from("Invalid query.")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sql_parser::SqlParser;
+
+    #[test]
+    fn test_can_parse_a_vacuum_statement() {
+        let input_parser = SqlParser();
+
+        let query = input_parser.parse_query(String::from("vacuum;"));
+        assert_eq!(query.unwrap().statement.to_string(), "VACUUM;");
+
+        let query = input_parser.parse_query(String::from("VACUUM users;"));
+        assert_eq!(query.unwrap().statement.to_string(), "VACUUM users;");
+
+        assert!(input_parser
+            .parse_query(String::from("VACUUM users, orders;"))
+            .is_err());
+    }
+}
@@ -0,0 +1,64 @@
+use crate::sql_parser::query::{Condition, Function, Predicate};
+
+const AND_GRAPHEME: &str = "AND";
+const BETWEEN_GRAPHEME: &str = "BETWEEN";
+
+/// Parses `operand operator value` triples joined by AND, where `BETWEEN lower AND upper` takes two values
+/// and the operand is either a column or a function applied to one, like `LOWER(email)`.
+pub fn parse_where_clauses(graphemes: &[String]) -> Result<Vec<Condition>, String> {
+    let mut where_clauses = Vec::new();
+    let mut position = 0;
+
+    while position < graphemes.len() {
+        let (column_name, function) = match graphemes.get(position..position + 4) {
+            Some([function, open, column_name, close]) if open == "(" && close == ")" => {
+                match Function::from_name(function) {
+                    Some(function) => {
+                        position += 3;
+                        (column_name, Some(function))
+                    }
+                    None => return Err(format!("Unsupported function '{}'.", function)),
+                }
+            }
+            _ => match graphemes.get(position) {
+                Some(column_name) => (column_name, None),
+                None => return Err(String::from("Invalid where clause.")),
+            },
+        };
+
+        let (Some(operator), Some(value)) =
+            (graphemes.get(position + 1), graphemes.get(position + 2))
+        else {
+            return Err(String::from("Invalid where clause."));
+        };
+
+        let predicate = if operator.to_uppercase() == BETWEEN_GRAPHEME {
+            match (graphemes.get(position + 3), graphemes.get(position + 4)) {
+                (Some(and), Some(upper)) if and.to_uppercase() == AND_GRAPHEME => {
+                    position += 2;
+                    Predicate::Between(value.to_string(), upper.to_string())
+                }
+                _ => return Err(String::from("BETWEEN requires a lower and an upper bound.")),
+            }
+        } else {
+            match Predicate::new(operator, value.to_string()) {
+                Some(predicate) => predicate,
+                None => return Err(format!("Unsupported operator '{}'.", operator)),
+            }
+        };
+
+        where_clauses.push(match function {
+            Some(function) => Condition::with_function(function, column_name, predicate),
+            None => Condition::new(column_name, predicate),
+        });
+        position += 3;
+
+        match graphemes.get(position) {
+            Some(and) if and.to_uppercase() == AND_GRAPHEME => position += 1,
+            Some(grapheme) => return Err(format!("Unexpected '{}' in where clause.", grapheme)),
+            None => {}
+        }
+    }
+
+    Ok(where_clauses)
+}
@@ -7,12 +7,16 @@ pub fn log_engine_output(response: Result<EngineResponse, String>, duration: Dur
         Ok(response) => {
             println!("Success ({} microseconds)", duration.as_micros());
 
-            if response.table.is_some() {
-                println!("{:?}", response.table.unwrap());
+            if let Some(table) = response.table {
+                println!("{:?}", table);
             }
 
-            if response.records.is_some() {
-                println!("{:?}", response.records.unwrap());
+            if let Some(records) = response.records {
+                println!("{:?}", records);
+            }
+
+            if let Some(message) = response.message {
+                println!("{}", message);
             }
         }
         Err(message) => println!("ERROR: {}", message),
@@ -31,3 +35,45 @@ where
 
     (duration, result)
 }
+
+/// Strips the single quotes around a string literal, e.g. `'john'` becomes `john`.
+pub fn unquote(literal: &str) -> &str {
+    literal
+        .strip_prefix('\'')
+        .and_then(|literal| literal.strip_suffix('\''))
+        .unwrap_or(literal)
+}
+
+const CRC32C_TABLE: [u32; 256] = crc32c_table();
+
+const fn crc32c_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut byte = 0;
+
+    while byte < 256 {
+        let mut crc = byte as u32;
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0x82F6_3B78 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+
+        table[byte] = crc;
+        byte += 1;
+    }
+
+    table
+}
+
+/// CRC-32C (Castagnoli) checksum of the bytes, which detects more of the errors
+/// storage produces than CRC-32 (IEEE) does.
+pub fn crc32c(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, byte| {
+        CRC32C_TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
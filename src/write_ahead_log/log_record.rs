@@ -0,0 +1,89 @@
+use std::collections::BTreeSet;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// A single file change, holding the complete after-image of the bytes it touches
+/// so that applying it twice has the same effect as applying it once.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum LogRecord {
+    /// Replaces the whole file, creating it if necessary.
+    Write {
+        path: String,
+        contents: Vec<u8>,
+    },
+    /// Overwrites the given ranges of the file, keeping everything else.
+    Patch {
+        path: String,
+        writes: Vec<(u64, Vec<u8>)>,
+    },
+    Remove {
+        path: String,
+    },
+}
+
+impl LogRecord {
+    pub fn path(&self) -> &str {
+        match self {
+            LogRecord::Write { path, .. }
+            | LogRecord::Patch { path, .. }
+            | LogRecord::Remove { path } => path,
+        }
+    }
+
+    pub fn apply(&self) -> io::Result<()> {
+        match self {
+            LogRecord::Write { path, contents } => {
+                create_parent_dir(path)?;
+
+                fs::File::create(path)?.write_all(contents)
+            }
+            LogRecord::Patch { path, writes } => {
+                create_parent_dir(path)?;
+
+                let mut file = OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(false)
+                    .open(path)?;
+
+                for (offset, bytes) in writes {
+                    file.seek(SeekFrom::Start(*offset))?;
+                    file.write_all(bytes)?;
+                }
+
+                Ok(())
+            }
+            LogRecord::Remove { path } => match fs::remove_file(path) {
+                Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+                result => result,
+            },
+        }
+    }
+}
+
+/// Syncs the files and the directories holding them, skipping those that no longer exist.
+pub fn sync_paths<'a>(paths: impl Iterator<Item = &'a str>) -> io::Result<()> {
+    let mut synced = BTreeSet::new();
+
+    for path in paths {
+        let path = Path::new(path);
+
+        for path in [Some(path), path.parent()].into_iter().flatten() {
+            if synced.insert(path.to_path_buf()) && path.exists() {
+                fs::File::open(path)?.sync_all()?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn create_parent_dir(path: &str) -> io::Result<()> {
+    match Path::new(path).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => fs::create_dir_all(parent),
+        _ => Ok(()),
+    }
+}
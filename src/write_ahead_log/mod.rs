@@ -1,89 +1,422 @@
-use std::{collections::HashMap, time::Instant};
-mod identifier;
+mod log_record;
 
-use crate::{dynamic_record, engine::Engine};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::sync::{Mutex, MutexGuard};
 
-use self::identifier::Identifier;
+use serde::{Deserialize, Serialize};
 
-pub struct WAL {
-    entries: Vec<Entry>,
-    engine: dyn Engine,
+use crate::utils::crc32c;
+
+use self::log_record::sync_paths;
+pub use self::log_record::LogRecord;
+
+// every frame starts with the length of its payload and the checksum of the payload
+const FRAME_HEADER_SIZE: usize = 8;
+
+// a commit that grows the log beyond this size is followed by a checkpoint
+const CHECKPOINT_LOG_SIZE: u64 = 1 << 20;
+
+/// The payload of a frame.
+#[derive(Serialize, Deserialize, Debug)]
+enum LogEntry {
+    Commit {
+        lsn: u64,
+        records: Vec<LogRecord>,
+    },
+    /// All commits up to and including the LSN are synced to the data files.
+    Checkpoint {
+        lsn: u64,
+    },
 }
 
-impl WAL {
-    pub fn append_entry(
-        &mut self,
-        identifier: Identifier,
-        changes: Vec<dynamic_record::DynamicRecord>,
-    ) -> Result<(), String> {
-        let entry = self.new_entry(identifier, changes);
-
-        match entry {
-            Ok(entry) => {
-                self.entries.push(entry);
-                Ok(())
-            }
-            Err(message) => Err(message),
+impl LogEntry {
+    fn lsn(&self) -> u64 {
+        match self {
+            LogEntry::Commit { lsn, .. } | LogEntry::Checkpoint { lsn } => *lsn,
+        }
+    }
+
+    fn records(&self) -> &[LogRecord] {
+        match self {
+            LogEntry::Commit { records, .. } => records,
+            LogEntry::Checkpoint { .. } => &[],
+        }
+    }
+}
+
+// the next LSN of every log opened by this process, keyed by its path. Engines of one
+// process share their logs, so the lock also keeps appending, applying and checkpointing
+// from interleaving, and recovery only runs when a process opens a log for the first time.
+static OPEN_LOGS: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
+
+/// A durable redo log. Every commit is appended as one checksummed frame and synced
+/// before its records are applied to the data files. A checkpoint syncs the data files
+/// and truncates the log, so that recovery only replays the commits after it. A frame
+/// that is torn or fails its checksum was never committed and is discarded.
+pub struct WriteAheadLog {
+    path: String,
+    file: Mutex<fs::File>,
+}
+
+impl WriteAheadLog {
+    /// Opens the log at the path, replaying the commits after its last checkpoint
+    /// unless this process has already done so.
+    pub fn open(path: &str) -> io::Result<Self> {
+        Self::open_with(path, true)
+    }
+
+    /// Opens the log at the path for reading only. The commits after its last checkpoint
+    /// are left to the process that holds the database for writing.
+    pub fn open_without_recovery(path: &str) -> io::Result<Self> {
+        Self::open_with(path, false)
+    }
+
+    fn open_with(path: &str, recover: bool) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        let log = WriteAheadLog {
+            path: path.to_string(),
+            file: Mutex::new(file),
+        };
+
+        let mut open_logs = lock();
+
+        if !open_logs.contains_key(path) {
+            let next_lsn = if recover {
+                log.recover()?
+            } else {
+                log.next_lsn()?
+            };
+
+            open_logs.insert(path.to_string(), next_lsn);
+        }
+
+        Ok(log)
+    }
+
+    /// Logs and applies the records, checkpointing once the log grows too large.
+    pub fn commit(&self, records: Vec<LogRecord>) -> io::Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        let mut open_logs = lock();
+        let next_lsn = open_logs.get_mut(&self.path).unwrap();
+        let lsn = *next_lsn;
+
+        let commit = LogEntry::Commit { lsn, records };
+        self.append(&commit)?;
+        *next_lsn += 1;
+
+        for record in commit.records() {
+            record.apply()?;
+        }
+
+        if self.file.lock().unwrap().metadata()?.len() > CHECKPOINT_LOG_SIZE {
+            self.checkpoint_locked(lsn)?;
         }
+
+        Ok(())
+    }
+
+    /// Syncs all data files changed since the last checkpoint and truncates the log,
+    /// returning the LSN of the last commit the checkpoint covers.
+    pub fn checkpoint(&self) -> io::Result<u64> {
+        let open_logs = lock();
+        let lsn = open_logs[&self.path] - 1;
+
+        self.checkpoint_locked(lsn)?;
+
+        Ok(lsn)
     }
 
-    fn new_entry(
-        &self,
-        identifier: Identifier,
-        changes: Vec<dynamic_record::DynamicRecord>,
-    ) -> Result<Entry, String> {
-        let mut where_clauses = HashMap::new();
-        where_clauses.insert(identifier.column_name, identifier.column_value.to_string());
-        let column_names = changes
-            .first()
-            .unwrap()
-            .fields
-            .keys()
-            .cloned()
-            .collect::<Vec<String>>();
-
-        let engine_result = self
-            .engine
-            .select(identifier.table_name, column_names, where_clauses);
-
-        match engine_result {
-            Ok(response) => {
-                let records = response.records.unwrap();
-
-                Ok(Entry::new(response.table.unwrap().name, records, changes))
+    fn checkpoint_locked(&self, lsn: u64) -> io::Result<()> {
+        let entries = self.read_entries()?;
+
+        sync_paths(
+            after_last_checkpoint(&entries)
+                .iter()
+                .flat_map(|entry| entry.records().iter().map(|record| record.path())),
+        )?;
+
+        // the data files hold all commits now, so the log can start over
+        self.file.lock().unwrap().set_len(0)?;
+        self.append(&LogEntry::Checkpoint { lsn })
+    }
+
+    /// Replays the commits after the last checkpoint and returns the next LSN.
+    fn recover(&self) -> io::Result<u64> {
+        let entries = self.read_entries()?;
+
+        for entry in after_last_checkpoint(&entries) {
+            for record in entry.records() {
+                record.apply()?;
             }
-            Err(message) => Err(message),
         }
+
+        let lsn = entries.iter().map(LogEntry::lsn).max().unwrap_or(0);
+
+        self.checkpoint_locked(lsn)?;
+
+        Ok(lsn + 1)
+    }
+
+    fn next_lsn(&self) -> io::Result<u64> {
+        let lsn = self.read_entries()?.iter().map(LogEntry::lsn).max();
+
+        Ok(lsn.unwrap_or(0) + 1)
+    }
+
+    fn append(&self, entry: &LogEntry) -> io::Result<()> {
+        let mut file = self.file.lock().unwrap();
+
+        file.write_all(&frame(entry))?;
+        file.sync_data()
+    }
+
+    fn read_entries(&self) -> io::Result<Vec<LogEntry>> {
+        let buffer = fs::read(&self.path)?;
+
+        Ok(complete_frames(&buffer))
     }
 }
 
-struct Entry {
-    created_at: Instant,
-    table_name: String,
-    old_records: Vec<dynamic_record::DynamicRecord>,
-    changes: Vec<dynamic_record::DynamicRecord>,
+fn lock() -> MutexGuard<'static, BTreeMap<String, u64>> {
+    OPEN_LOGS.lock().unwrap_or_else(|error| error.into_inner())
 }
 
-impl Entry {
-    pub fn new(
-        table_name: String,
-        old_records: Vec<dynamic_record::DynamicRecord>,
-        changes: Vec<dynamic_record::DynamicRecord>,
-    ) -> Self {
-        Self {
-            created_at: Instant::now(),
-            table_name,
-            old_records,
-            changes,
+fn frame(entry: &LogEntry) -> Vec<u8> {
+    let payload = bincode::serialize(entry).unwrap();
+
+    let mut frame = Vec::with_capacity(FRAME_HEADER_SIZE + payload.len());
+    frame.extend((payload.len() as u32).to_le_bytes());
+    frame.extend(crc32c(&payload).to_le_bytes());
+    frame.extend(payload);
+
+    frame
+}
+
+/// Decodes frames from the start of the log until the first one that is incomplete or corrupted.
+fn complete_frames(mut buffer: &[u8]) -> Vec<LogEntry> {
+    let mut entries = vec![];
+
+    while buffer.len() >= FRAME_HEADER_SIZE {
+        let length = u32::from_le_bytes(buffer[0..4].try_into().unwrap()) as usize;
+        let checksum = u32::from_le_bytes(buffer[4..8].try_into().unwrap());
+
+        let Some(payload) = buffer.get(FRAME_HEADER_SIZE..FRAME_HEADER_SIZE + length) else {
+            break;
+        };
+
+        if crc32c(payload) != checksum {
+            break;
         }
+
+        let Ok(entry) = bincode::deserialize(payload) else {
+            break;
+        };
+
+        entries.push(entry);
+        buffer = &buffer[FRAME_HEADER_SIZE + length..];
     }
+
+    entries
+}
+
+fn after_last_checkpoint(entries: &[LogEntry]) -> &[LogEntry] {
+    let start = entries
+        .iter()
+        .rposition(|entry| matches!(entry, LogEntry::Checkpoint { .. }))
+        .map_or(0, |position| position + 1);
+
+    &entries[start..]
 }
 
 #[cfg(test)]
 mod tests {
+    use rand::{distributions::Alphanumeric, Rng};
+
+    use super::*;
+
+    struct LogDir(String);
+
+    impl LogDir {
+        fn new() -> Self {
+            let dir_name: String = rand::thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(10)
+                .map(char::from)
+                .collect();
+
+            let path = std::env::temp_dir().join(dir_name);
+            fs::create_dir(&path).unwrap();
+
+            LogDir(path.to_str().unwrap().to_string())
+        }
+
+        fn path(&self, file_name: &str) -> String {
+            format!("{}/{}", self.0, file_name)
+        }
+    }
+
+    impl Drop for LogDir {
+        fn drop(&mut self) {
+            fs::remove_dir_all(&self.0).ok();
+        }
+    }
+
+    fn write(path: String, contents: &[u8]) -> LogRecord {
+        LogRecord::Write {
+            path,
+            contents: contents.to_vec(),
+        }
+    }
+
+    fn log_size(dir: &LogDir) -> usize {
+        fs::metadata(dir.path("wal.log")).unwrap().len() as usize
+    }
 
     #[test]
-    fn test_entry_can_be_properly_constructed() {
+    fn test_commits_stay_in_the_log_until_a_checkpoint() {
+        let dir = LogDir::new();
+        let log = WriteAheadLog::open(&dir.path("wal.log")).unwrap();
+
+        log.commit(vec![
+            write(dir.path("page.bin"), &[1, 2, 3, 4]),
+            LogRecord::Patch {
+                path: dir.path("page.bin"),
+                writes: vec![(2, vec![9])],
+            },
+        ])
+        .unwrap();
+        log.commit(vec![write(dir.path("other.bin"), &[5])])
+            .unwrap();
+
+        assert_eq!(fs::read(dir.path("page.bin")).unwrap(), vec![1, 2, 9, 4]);
+        assert_eq!(
+            complete_frames(&fs::read(dir.path("wal.log")).unwrap()).len(),
+            3
+        );
 
+        assert_eq!(log.checkpoint().unwrap(), 2);
+        assert_eq!(
+            log_size(&dir),
+            frame(&LogEntry::Checkpoint { lsn: 2 }).len()
+        );
+    }
+
+    #[test]
+    fn test_replays_commits_that_were_logged_but_not_applied() {
+        let dir = LogDir::new();
+
+        fs::write(dir.path("page.bin"), b"torn").unwrap();
+
+        // a crash right after the commit was synced to the log
+        let commit = LogEntry::Commit {
+            lsn: 1,
+            records: vec![
+                write(dir.path("page.bin"), b"complete"),
+                LogRecord::Remove {
+                    path: dir.path("stale.bin"),
+                },
+            ],
+        };
+        fs::write(dir.path("wal.log"), frame(&commit)).unwrap();
+
+        WriteAheadLog::open(&dir.path("wal.log")).unwrap();
+
+        assert_eq!(fs::read(dir.path("page.bin")).unwrap(), b"complete");
+        assert_eq!(
+            log_size(&dir),
+            frame(&LogEntry::Checkpoint { lsn: 1 }).len()
+        );
+    }
+
+    #[test]
+    fn test_recovery_starts_at_the_last_checkpoint() {
+        let dir = LogDir::new();
+
+        fs::write(dir.path("checkpointed.bin"), b"current").unwrap();
+
+        let mut log_contents = frame(&LogEntry::Commit {
+            lsn: 4,
+            records: vec![write(dir.path("checkpointed.bin"), b"outdated")],
+        });
+        log_contents.extend(frame(&LogEntry::Checkpoint { lsn: 4 }));
+        log_contents.extend(frame(&LogEntry::Commit {
+            lsn: 5,
+            records: vec![write(dir.path("recent.bin"), b"recent")],
+        }));
+        fs::write(dir.path("wal.log"), log_contents).unwrap();
+
+        let log = WriteAheadLog::open(&dir.path("wal.log")).unwrap();
+
+        assert_eq!(fs::read(dir.path("checkpointed.bin")).unwrap(), b"current");
+        assert_eq!(fs::read(dir.path("recent.bin")).unwrap(), b"recent");
+
+        log.commit(vec![write(dir.path("recent.bin"), b"newer")])
+            .unwrap();
+        assert_eq!(log.checkpoint().unwrap(), 6);
+    }
+
+    #[test]
+    fn test_checkpoints_once_the_log_grows_too_large() {
+        let dir = LogDir::new();
+        let log = WriteAheadLog::open(&dir.path("wal.log")).unwrap();
+
+        log.commit(vec![write(
+            dir.path("large.bin"),
+            &vec![7; CHECKPOINT_LOG_SIZE as usize],
+        )])
+        .unwrap();
+
+        assert_eq!(
+            log_size(&dir),
+            frame(&LogEntry::Checkpoint { lsn: 1 }).len()
+        );
+    }
+
+    #[test]
+    fn test_discards_torn_and_corrupted_frames() {
+        let dir = LogDir::new();
+
+        let committed = frame(&LogEntry::Commit {
+            lsn: 1,
+            records: vec![write(dir.path("committed.bin"), &[1])],
+        });
+        let mut corrupted = frame(&LogEntry::Commit {
+            lsn: 1,
+            records: vec![write(dir.path("corrupted.bin"), &[2])],
+        });
+        *corrupted.last_mut().unwrap() ^= 0xFF;
+        let torn = frame(&LogEntry::Commit {
+            lsn: 2,
+            records: vec![write(dir.path("torn.bin"), &[3])],
+        });
+
+        let mut log_contents = committed;
+        log_contents.extend(&torn[..torn.len() - 1]);
+        fs::write(dir.path("wal.log"), &log_contents).unwrap();
+
+        WriteAheadLog::open(&dir.path("wal.log")).unwrap();
+
+        fs::write(dir.path("corrupted.log"), corrupted).unwrap();
+
+        WriteAheadLog::open(&dir.path("corrupted.log")).unwrap();
+
+        assert_eq!(fs::read(dir.path("committed.bin")).unwrap(), vec![1]);
+        assert!(!std::path::Path::new(&dir.path("torn.bin")).exists());
+        assert!(!std::path::Path::new(&dir.path("corrupted.bin")).exists());
+    }
+
+    #[test]
+    fn test_checksum_matches_crc32c() {
+        assert_eq!(crc32c(b"123456789"), 0xE306_9283);
     }
 }